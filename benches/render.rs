@@ -0,0 +1,82 @@
+// Benchmarks of the renderer, run with `cargo bench`. They give a baseline to measure the impact
+// of changes to the bounding volume hierarchy, the parallelism or the vector math. Nothing is
+// written to disk, the images are only rendered into a buffer.
+#![allow(clippy::needless_return)]
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use raytracing::camera::camera::Camera;
+use raytracing::hittables::bvh::BvhNode;
+use raytracing::hittables::hittables::{Hittable, Hittables};
+use raytracing::hittables::sphere::Sphere;
+use raytracing::materials::arena::MaterialArena;
+use raytracing::materials::materials::Lambertian;
+use raytracing::raycaster::ray::Ray;
+use raytracing::scenes::scenes::random_spheres;
+use raytracing::util::utils::{Interval, POSITIVE_INFINITY};
+use raytracing::vector::vector::{Color, Point, Vec3};
+use std::sync::Arc;
+
+/// Create the world of the cover image in a bounding volume hierarchy, as it is rendered by the
+/// binary.
+fn cover_world() -> Hittables {
+    let mut world: Hittables = random_spheres(42);
+    let materials: MaterialArena = world.take_materials();
+    return Hittables::with_materials(vec![Arc::new(BvhNode::from_hittables(world))], materials);
+}
+
+/// Render the cover image at a small resolution with few samples per pixel.
+fn bench_render(c: &mut Criterion) {
+    let world: Hittables = cover_world();
+    let camera: Camera = Camera::builder()
+        .aspect_ratio(16.0 / 9.0)
+        .width(64)
+        .samples(4)
+        .max_depth(10)
+        .center(Point::new(13.0, 2.0, 3.0))
+        .look_at(Point::new(0.0, 0.0, 0.0))
+        .vfov(20.0)
+        .defocus_angle(0.6)
+        .focus_dist(10.0)
+        .seed(42)
+        .build();
+    c.bench_function("render_to_buffer cover 64x36 4spp", |b| {
+        b.iter(|| camera.render_to_buffer(black_box(&world)))
+    });
+}
+
+/// Intersect a single sphere with a ray that hits it and with one that misses it.
+fn bench_sphere_hit(c: &mut Criterion) {
+    let mut materials: MaterialArena = MaterialArena::new();
+    let material = materials.add(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    let sphere: Sphere = Sphere::new(Point::new(0.0, 0.0, -1.0), 0.5, material);
+    let interval: Interval = Interval::new(0.001, POSITIVE_INFINITY);
+    let hit: Ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vec3::new(0.1, 0.1, -1.0), 0.0);
+    let miss: Ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vec3::new(1.0, 1.0, -1.0), 0.0);
+    c.bench_function("Sphere::ray_hit hit", |b| {
+        b.iter(|| sphere.ray_hit(black_box(&hit), interval))
+    });
+    c.bench_function("Sphere::ray_hit miss", |b| {
+        b.iter(|| sphere.ray_hit(black_box(&miss), interval))
+    });
+}
+
+/// The vector operations that are used most while tracing rays.
+fn bench_vec3(c: &mut Criterion) {
+    let a: Vec3 = Vec3::new(1.0, 2.0, 3.0);
+    let b: Vec3 = Vec3::new(-4.0, 0.5, 2.0);
+    c.bench_function("Vec3::dot", |bench| {
+        bench.iter(|| black_box(a).dot(&black_box(b)))
+    });
+    c.bench_function("Vec3::cross", |bench| {
+        bench.iter(|| black_box(a).cross(&black_box(b)))
+    });
+    c.bench_function("Vec3::unit_vector", |bench| {
+        bench.iter(|| black_box(a).unit_vector())
+    });
+    c.bench_function("Vec3 add and scale", |bench| {
+        bench.iter(|| black_box(a) + black_box(b) * 0.5)
+    });
+}
+
+criterion_group!(benches, bench_render, bench_sphere_hit, bench_vec3);
+criterion_main!(benches);
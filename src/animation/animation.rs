@@ -0,0 +1,99 @@
+use crate::camera::camera::Camera;
+use crate::error::error::RenderError;
+use crate::hittables::hittables::Hittables;
+use crate::transform::transform::Mat4;
+use crate::util::utils;
+use crate::util::utils::PpmFormat;
+use crate::vector::vector::{Point, Vec3};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Get the angle in degrees of `frame` out of `frame_count` frames of a full turn, such that the
+/// frames are evenly spread and the last frame does not repeat the first.
+pub fn orbit_angle(frame: usize, frame_count: usize) -> f64 {
+    return 360.0 * (frame as f64) / (frame_count as f64);
+}
+
+/// Get the position of a camera that starts at `center` and orbits around the point `look_at`
+/// by `angle` degrees, rotating around the `vup` axis through `look_at`. This is the camera
+/// position of a turntable animation.
+pub fn orbit_center(center: Point, look_at: Point, vup: Vec3, angle: f64) -> Point {
+    let orbit: Mat4 =
+        Mat4::translation(look_at) * Mat4::rotation(vup, angle) * Mat4::translation(-look_at);
+    return orbit.mul_point(center);
+}
+
+/// Get the path of the image of `frame` in `directory`, e.g. `directory/frame_0007.ppm`.
+pub fn frame_path(directory: &Path, frame: usize) -> PathBuf {
+    return directory.join(format!("frame_{frame:04}.ppm"));
+}
+
+/// Render `frame_count` frames of the `world` into `directory`, see `frame_path`. The camera of
+/// every frame is created by `camera_for_frame`, e.g. a camera that orbits around the scene with
+/// `orbit_center` at the `orbit_angle` of the frame for a turntable animation. The frames are
+/// rendered one after the other, each with `Camera::render`.
+pub fn animate<F: Fn(usize) -> Camera>(
+    world: &Hittables,
+    frame_count: usize,
+    directory: &Path,
+    camera_for_frame: F,
+) -> Result<(), RenderError> {
+    for frame in 0..frame_count {
+        let path: PathBuf = frame_path(directory, frame);
+        log::info!("Rendering frame {} of {frame_count}", frame + 1);
+        let camera: Camera = camera_for_frame(frame);
+        utils::create_parent_dir(&path)?;
+        let file: File = File::create(&path).map_err(|err| with_path(err, &path))?;
+        let mut writer = BufWriter::new(file);
+        camera.render(&mut writer, world, PpmFormat::Binary)?;
+        writer.flush().map_err(|err| with_path(err, &path))?;
+    }
+    return Ok(());
+}
+
+/// Add the `path` of the frame to an I/O error, so that the error message says which file failed.
+fn with_path(err: std::io::Error, path: &Path) -> std::io::Error {
+    return std::io::Error::new(err.kind(), format!("`{}`: {err}", path.display()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn three_frames_orbit_through_three_distinct_centers() {
+        let center: Point = Point::new(0.0, 1.0, 4.0);
+        let look_at: Point = Point::new(0.0, 1.0, 0.0);
+        let vup: Vec3 = Vec3::new(0.0, 1.0, 0.0);
+        let centers: Vec<Point> = (0..3)
+            .map(|frame| orbit_center(center, look_at, vup, orbit_angle(frame, 3)))
+            .collect();
+        assert!((centers[0] - center).length() < 1e-12);
+        for (k, frame_center) in centers.iter().enumerate() {
+            // Every center stays on the circle around `look_at`, at the height of the start.
+            assert!(((*frame_center - look_at).length() - 4.0).abs() < 1e-12);
+            assert!((frame_center.y - 1.0).abs() < 1e-12);
+            for other in &centers[k + 1..] {
+                assert!((*frame_center - *other).length() > 1.0);
+            }
+        }
+
+        let directory: PathBuf = std::env::temp_dir().join("raytracing_animation_test");
+        let camera_for_frame = |frame: usize| {
+            return Camera::builder()
+                .aspect_ratio(1.0)
+                .width(2)
+                .samples(1)
+                .max_depth(1)
+                .center(centers[frame])
+                .look_at(look_at)
+                .build();
+        };
+        animate(&Hittables::init(), 3, &directory, camera_for_frame).unwrap();
+        for frame in 0..3 {
+            assert!(frame_path(&directory, frame).is_file());
+        }
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
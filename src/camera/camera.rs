@@ -1,10 +1,22 @@
-use crate::hittables::hittables::Hittables;
+use crate::checkpoint::checkpoint::Checkpoint;
+use crate::environment::environment::{Environment, GradientSky};
+use crate::error::error::RenderError;
+use crate::hittables::hittables::{Hittable, Hittables};
+use crate::lights::lights::Light;
+use crate::postprocess::postprocess::{PostProcess, Vignette};
 use crate::raycaster::ray::Ray;
 use crate::util::utils;
+use crate::util::utils::{ColorTransfer, PixelFilter, PpmFormat, RngState, Sampler, ToneMap};
 use crate::vector::vector::{Color, Point, Vec3};
+use std::fmt;
+use std::io::Write;
 use std::ops::Neg;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
 
-use indicatif::ProgressBar;
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::sync::Arc;
 
 /// Camera structure that stores the essential information about the camera and contains methods
 /// for rendering the world through ray casting.
@@ -17,8 +29,31 @@ pub struct Camera {
     pub vfov: f64,
     pub look_at: Point,
     pub vup: Vec3,
+    pub roll: f64,
     pub defocus_angle: f64,
     pub focus_dist: f64,
+    pub shutter_open: f64,
+    pub shutter_close: f64,
+    pub tone_map: ToneMap,
+    pub color_transfer: ColorTransfer,
+    pub exposure: f64,
+    pub environment: Box<dyn Environment>,
+    pub firefly_clamp: Option<f64>,
+    pub shadow_epsilon: f64,
+    pub fog_color: Color,
+    pub fog_density: f64,
+    pub post_processes: Vec<Box<dyn PostProcess>>,
+    pub render_mode: RenderMode,
+    pub projection: Projection,
+    pub pixel_filter: PixelFilter,
+    pub sampler: Sampler,
+    pub lens_sampler: Sampler,
+    pub lights: Vec<Box<dyn Light>>,
+    pub light_surfaces: Hittables,
+    pub seed: Option<u64>,
+    pub threads: Option<usize>,
+    pub rays_traced: AtomicU64,
+    pub camera_rays: AtomicU64,
     pub image_height: i32,
     pub pixel_upper_left_center: Point,
     pub pixel_delta_u: Vec3,
@@ -31,9 +66,405 @@ pub struct Camera {
     pub defocus_v: Vec3,
 }
 
+/// What the camera renders. `PathTrace` renders the full image by tracing rays through all their
+/// bounces. `Normals` is a debug mode that colors the first surface a ray hits by its normal, with
+/// every component mapped from [-1,1] to [0,1], and does not follow any bounces. `Depth` renders
+/// the distance to the first surface a ray hits as a gray value, where black is at the camera and
+/// white is at `max_distance` or further. Rays that hit nothing are white. The depth is a data
+/// pass, so it is written without exposure, tone mapping and gamma encoding. `Albedo` renders the
+/// base color of the material of the first surface a ray hits, without any lighting, which is
+/// the albedo pass that denoisers take alongside the noisy image. It is written without exposure
+/// and tone mapping.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RenderMode {
+    PathTrace,
+    Normals,
+    Depth { max_distance: f64 },
+    Albedo,
+}
+
+/// How rays are cast from the camera through the viewport. With a `Perspective` projection, all
+/// rays start at the camera center (or on the defocus disk) and go through their pixel, so far
+/// away objects appear smaller. With an `Orthographic` projection, all rays have the same direction
+/// as the camera looks in and start on the plane through the camera center that is parallel to
+/// the viewport, right behind their pixel. Objects then appear equally large at any distance,
+/// which is useful for technical and architectural renders. The visible area is the viewport,
+/// whose size follows from the vertical field of view and the focus distance. There is no defocus
+/// blur in orthographic mode. An `Equirectangular` projection renders a 360 degree panorama, e.g.
+/// for VR or to capture an environment map. All rays start at the camera center and the horizontal
+/// and vertical position of a pixel give the longitude and latitude of its direction, where the
+/// center of the image looks at `look_at`. The image always has an aspect ratio of 2:1, the field
+/// of view and the defocus blur are ignored. A `Fisheye` projection renders a circular wide angle
+/// image that fits in the image, with the equidistant fisheye model: the angle of a ray with the
+/// viewing direction grows linearly with the distance of its pixel from the image center, up to
+/// half of `fov_degrees` at the edge of the circle. All rays start at the camera center, and the
+/// pixels outside the circle stay black, no rays are cast for them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Projection {
+    Perspective,
+    Orthographic,
+    Equirectangular,
+    Fisheye { fov_degrees: f64 },
+}
+
+/// Statistics of a render. `rays_traced` counts the rays that were sent into the world by
+/// `Ray::ray_color`, e.g. the camera rays and all their bounces. Shadow rays towards the light
+/// sources are not counted. `avg_depth` is the average amount of rays traced per camera ray, which
+/// is the average length of the paths. Only the camera rays that were actually traced count, e.g.
+/// not those of the pixels outside the image circle of a fisheye camera.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RenderStats {
+    pub rays_traced: u64,
+    pub wall_time: Duration,
+    pub avg_depth: f64,
+}
+
+impl fmt::Display for RenderStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "traced {} rays in {:.2?}, with an average depth of {:.2}",
+            self.rays_traced, self.wall_time, self.avg_depth
+        )
+    }
+}
+
+/// Builder for a `Camera`, which names every parameter instead of passing them in order to
+/// `Camera::initialize`. Parameters that are not set keep their default value, see `Default`.
+/// The derived quantities of the camera are computed by `build`.
+pub struct CameraBuilder {
+    pub aspect_ratio: f64,
+    pub image_width: i32,
+    pub center: Point,
+    pub samples_per_pixel: i32,
+    pub max_depth: i32,
+    pub vfov: f64,
+    pub look_at: Point,
+    pub vup: Vec3,
+    pub roll: f64,
+    pub defocus_angle: f64,
+    pub focus_dist: f64,
+    pub auto_focus: bool,
+    pub shutter_open: f64,
+    pub shutter_close: f64,
+    pub tone_map: ToneMap,
+    pub color_transfer: ColorTransfer,
+    pub exposure: f64,
+    pub environment: Box<dyn Environment>,
+    pub firefly_clamp: Option<f64>,
+    pub shadow_epsilon: f64,
+    pub fog_color: Color,
+    pub fog_density: f64,
+    pub post_processes: Vec<Box<dyn PostProcess>>,
+    pub render_mode: RenderMode,
+    pub projection: Projection,
+    pub pixel_filter: PixelFilter,
+    pub sampler: Sampler,
+    pub lens_sampler: Sampler,
+    pub lights: Vec<Box<dyn Light>>,
+    pub light_surfaces: Hittables,
+    pub seed: Option<u64>,
+    pub threads: Option<usize>,
+}
+
+impl Default for CameraBuilder {
+    /// By default, the camera sits at the origin looking down the negative z-axis with a
+    /// vertical field of view of 90 degrees. It renders a 400 pixels wide 16:9 image with 100
+    /// samples per pixel and at most 50 bounces, without defocus blur or motion blur.
+    fn default() -> Self {
+        Self {
+            aspect_ratio: 16.0 / 9.0,
+            image_width: 400,
+            center: Point::new(0.0, 0.0, 0.0),
+            samples_per_pixel: 100,
+            max_depth: 50,
+            vfov: 90.0,
+            look_at: Point::new(0.0, 0.0, -1.0),
+            vup: Vec3::new(0.0, 1.0, 0.0),
+            roll: 0.0,
+            defocus_angle: 0.0,
+            focus_dist: 10.0,
+            auto_focus: false,
+            shutter_open: 0.0,
+            shutter_close: 0.0,
+            tone_map: ToneMap::None,
+            color_transfer: ColorTransfer::Gamma(2.0),
+            exposure: 0.0,
+            environment: Box::new(GradientSky::default()),
+            firefly_clamp: None,
+            shadow_epsilon: 0.001,
+            fog_color: Color::new(1.0, 1.0, 1.0),
+            fog_density: 0.0,
+            post_processes: Vec::new(),
+            render_mode: RenderMode::PathTrace,
+            projection: Projection::Perspective,
+            pixel_filter: PixelFilter::Box,
+            sampler: Sampler::Random,
+            lens_sampler: Sampler::Random,
+            lights: Vec::new(),
+            light_surfaces: Hittables::init(),
+            seed: None,
+            threads: None,
+        }
+    }
+}
+
+impl CameraBuilder {
+    /// Create new `CameraBuilder` with the default parameters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Set the aspect ratio, which is defined as the width/height.
+    pub fn aspect_ratio(mut self, aspect_ratio: f64) -> Self {
+        self.aspect_ratio = aspect_ratio;
+        self
+    }
+    /// Set the width of the image in pixels.
+    pub fn width(mut self, image_width: i32) -> Self {
+        self.image_width = image_width;
+        self
+    }
+    /// Set the center of the camera, e.g. the point the camera looks from.
+    pub fn center(mut self, center: Point) -> Self {
+        self.center = center;
+        self
+    }
+    /// Set the amount of samples per pixel.
+    pub fn samples(mut self, samples_per_pixel: i32) -> Self {
+        self.samples_per_pixel = samples_per_pixel;
+        self
+    }
+    /// Set the maximum amount of bounces a ray can do.
+    pub fn max_depth(mut self, max_depth: i32) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+    /// Set the vertical field of view in degrees.
+    pub fn vfov(mut self, vfov: f64) -> Self {
+        self.vfov = vfov;
+        self
+    }
+    /// Set the point the camera looks at.
+    pub fn look_at(mut self, look_at: Point) -> Self {
+        self.look_at = look_at;
+        self
+    }
+    /// Set the camera relative up direction.
+    pub fn vup(mut self, vup: Vec3) -> Self {
+        self.vup = vup;
+        self
+    }
+    /// Set the roll angle in degrees, which rotates the camera counterclockwise around its view
+    /// direction, so the horizon in the image tilts clockwise.
+    pub fn roll(mut self, roll: f64) -> Self {
+        self.roll = roll;
+        self
+    }
+    /// Set the angle of the defocus cone in degrees, zero disables defocus blur.
+    pub fn defocus_angle(mut self, defocus_angle: f64) -> Self {
+        self.defocus_angle = defocus_angle;
+        self
+    }
+    /// Set the distance from the camera center to the plane of perfect focus.
+    pub fn focus_dist(mut self, focus_dist: f64) -> Self {
+        self.focus_dist = focus_dist;
+        self
+    }
+    /// If `auto_focus` is true, the `look_at` point is in perfect focus, e.g. the focus distance
+    /// is the distance from the camera center to the `look_at` point and `focus_dist` is ignored.
+    pub fn auto_focus(mut self, auto_focus: bool) -> Self {
+        self.auto_focus = auto_focus;
+        self
+    }
+    /// Set the times at which the shutter opens and closes. If they are equal, there is no
+    /// motion blur.
+    pub fn shutter(mut self, shutter_open: f64, shutter_close: f64) -> Self {
+        self.shutter_open = shutter_open;
+        self.shutter_close = shutter_close;
+        self
+    }
+    /// Set the tone mapping operator.
+    pub fn tone_map(mut self, tone_map: ToneMap) -> Self {
+        self.tone_map = tone_map;
+        self
+    }
+    /// Set the gamma used to encode the image, e.g. the color transfer is `Gamma(gamma)`.
+    pub fn gamma(mut self, gamma: f64) -> Self {
+        self.color_transfer = ColorTransfer::Gamma(gamma);
+        self
+    }
+    /// Set the color transfer function used to encode the image.
+    pub fn color_transfer(mut self, color_transfer: ColorTransfer) -> Self {
+        self.color_transfer = color_transfer;
+        self
+    }
+    /// Set the exposure in stops.
+    pub fn exposure(mut self, exposure: f64) -> Self {
+        self.exposure = exposure;
+        self
+    }
+    /// Set the environment that is seen by rays that hit nothing.
+    pub fn environment(mut self, environment: Box<dyn Environment>) -> Self {
+        self.environment = environment;
+        self
+    }
+    /// Set the maximum of every color channel of a single sample, see `Camera::clamp_sample`.
+    pub fn firefly_clamp(mut self, max_radiance: f64) -> Self {
+        self.firefly_clamp = Some(max_radiance);
+        self
+    }
+    /// Set the smallest distance along a ray, in ray parameter units, at which a hit counts, see
+    /// `Ray::ray_color`. Scenes with very small geometry need a smaller value, scenes with very
+    /// large geometry a larger one.
+    pub fn shadow_epsilon(mut self, shadow_epsilon: f64) -> Self {
+        self.shadow_epsilon = shadow_epsilon;
+        self
+    }
+    /// Set the color and density of the fog, see `Camera::apply_fog`. A density of zero disables
+    /// the fog.
+    pub fn fog(mut self, fog_color: Color, fog_density: f64) -> Self {
+        self.fog_color = fog_color;
+        self.fog_density = fog_density;
+        self
+    }
+    /// Add a `PostProcess` that is applied after the ones that were added before.
+    pub fn post_process(mut self, post_process: Box<dyn PostProcess>) -> Self {
+        self.post_processes.push(post_process);
+        self
+    }
+    /// Add a `Vignette` post-process with the given `strength`, see `utils::vignette`.
+    pub fn vignette(self, strength: f64) -> Self {
+        return self.post_process(Box::new(Vignette::new(strength)));
+    }
+    /// Set what is rendered, see `RenderMode`.
+    pub fn render_mode(mut self, render_mode: RenderMode) -> Self {
+        self.render_mode = render_mode;
+        self
+    }
+    /// Set how rays are cast through the viewport, see `Projection`.
+    pub fn projection(mut self, projection: Projection) -> Self {
+        self.projection = projection;
+        self
+    }
+    /// Set the filter that spreads the samples of a pixel around its center, see `PixelFilter`.
+    pub fn pixel_filter(mut self, pixel_filter: PixelFilter) -> Self {
+        self.pixel_filter = pixel_filter;
+        self
+    }
+    /// Set the source of the random numbers of the pixel offsets, see `Sampler`.
+    pub fn sampler(mut self, sampler: Sampler) -> Self {
+        self.sampler = sampler;
+        self
+    }
+    /// Set the source of the random numbers of the points on the lens from which the rays start
+    /// with defocus blur, see `Sampler`. A `Halton` lens sampler gives smoother bokeh at low
+    /// amounts of samples.
+    pub fn lens_sampler(mut self, lens_sampler: Sampler) -> Self {
+        self.lens_sampler = lens_sampler;
+        self
+    }
+    /// Add a `Light` source, which is sampled directly at every diffuse hit.
+    pub fn light(mut self, light: Box<dyn Light>) -> Self {
+        self.lights.push(light);
+        self
+    }
+    /// Add a copy of an emissive object of the world, such as a `Quad` with a `DiffuseLight`
+    /// material, whose surface is sampled directly at every diffuse hit. The object must
+    /// implement `Hittable::random_point_toward`.
+    pub fn light_surface(mut self, light_surface: Arc<dyn Hittable>) -> Self {
+        self.light_surfaces.add(light_surface);
+        self
+    }
+    /// Set the seed of the random numbers, which makes the render reproducible, see
+    /// `Camera::seed_pixel`.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+    /// Set the amount of threads that render the image, e.g. to leave a core free for other
+    /// work, see `Camera::thread_pool`.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads);
+        self
+    }
+    /// Build the `Camera`, computing all derived quantities.
+    pub fn build(self) -> Camera {
+        let focus_dist: f64 = {
+            if self.auto_focus {
+                (self.center - self.look_at).length()
+            } else {
+                self.focus_dist
+            }
+        };
+        // A panorama covers 360 degrees horizontally and 180 degrees vertically.
+        let aspect_ratio: f64 = {
+            if self.projection == Projection::Equirectangular {
+                2.0
+            } else {
+                self.aspect_ratio
+            }
+        };
+        let mut camera: Camera = Camera::initialize(
+            aspect_ratio,
+            self.image_width,
+            self.center,
+            self.samples_per_pixel,
+            self.max_depth,
+            self.vfov,
+            self.look_at,
+            self.vup,
+            self.roll,
+            self.defocus_angle,
+            focus_dist,
+            self.shutter_open,
+            self.shutter_close,
+        );
+        camera.tone_map = self.tone_map;
+        camera.color_transfer = self.color_transfer;
+        camera.exposure = self.exposure;
+        camera.environment = self.environment;
+        camera.firefly_clamp = self.firefly_clamp;
+        camera.shadow_epsilon = self.shadow_epsilon;
+        camera.fog_color = self.fog_color;
+        camera.fog_density = self.fog_density;
+        camera.post_processes = self.post_processes;
+        camera.render_mode = self.render_mode;
+        camera.projection = self.projection;
+        camera.pixel_filter = self.pixel_filter;
+        camera.sampler = self.sampler;
+        camera.lens_sampler = self.lens_sampler;
+        camera.lights = self.lights;
+        camera.light_surfaces = self.light_surfaces;
+        camera.seed = self.seed;
+        camera.threads = self.threads;
+        return camera;
+    }
+}
+
+/// A rectangular block of rendered pixels, stored in scanline order. The upper left pixel of the
+/// tile lies at (x0, y0) in the full image.
+pub struct Tile {
+    pub x0: i32,
+    pub y0: i32,
+    pub width: i32,
+    pub height: i32,
+    pub pixels: Vec<Color>,
+}
+
 impl Camera {
+    /// Create a `CameraBuilder` with the default parameters.
+    pub fn builder() -> CameraBuilder {
+        return CameraBuilder::default();
+    }
+
     /// Using only base information that cannot be inferred using other values,
-    /// create a new instance of `Camera`.
+    /// create a new instance of `Camera`. By default, the background is the default
+    /// `GradientSky`, the exposure is zero stops, no tone mapping is applied, the samples are not
+    /// clamped, hits closer than a ray parameter of 0.001 are ignored, the image is path traced
+    /// with a perspective projection and randomly sampled on the pixels and the lens with a box
+    /// pixel filter, there are no `Light` sources or light surfaces, there is no fog and there are
+    /// no post-processes, the random numbers are not seeded, the image is rendered on all cores
+    /// and it is encoded with a gamma of 2, e.g. `ColorTransfer::Gamma(2.0)`.
     pub fn initialize(
         aspect_ratio: f64,
         image_width: i32,
@@ -43,8 +474,11 @@ impl Camera {
         vfov: f64,
         look_at: Point,
         vup: Vec3,
+        roll: f64,
         defocus_angle: f64,
         focus_dist: f64,
+        shutter_open: f64,
+        shutter_close: f64,
     ) -> Self {
         // Compute rendered image height from given width and aspect ratio
         let image_height = (image_width as f64) / aspect_ratio;
@@ -73,6 +507,9 @@ impl Camera {
         let w: Vec3 = (center - look_at).unit_vector();
         let u: Vec3 = vup.cross(&w).unit_vector();
         let v: Vec3 = w.cross(&u);
+        // Roll the camera by rotating u and v around w.
+        let (sin_roll, cos_roll) = utils::degrees_to_radians(roll).sin_cos();
+        let (u, v) = (u * cos_roll + v * sin_roll, v * cos_roll - u * sin_roll);
 
         // Define viewport coordinate system (u,v). The x-axis points from left to right, but the
         // y-axis points from up to down.
@@ -88,7 +525,7 @@ impl Camera {
         let pixel_upper_left_center: Point =
             viewport_upper_left + (pixel_delta_u + pixel_delta_v) * 0.5;
         // Compute pixel sample scale from samples per pixel
-        let pixel_sample_scale: f64 = 1.0 / (samples_per_pixel as f64);
+        let pixel_sample_scale: f64 = utils::sample_scale(samples_per_pixel);
 
         // Compute Camera defocus disk basis vectors
         let defocus_radius: f64 = focus_dist * utils::degrees_to_radians(defocus_angle / 2.0).tan();
@@ -104,8 +541,31 @@ impl Camera {
             vfov,
             look_at,
             vup,
+            roll,
             defocus_angle,
             focus_dist,
+            shutter_open,
+            shutter_close,
+            tone_map: ToneMap::None,
+            color_transfer: ColorTransfer::Gamma(2.0),
+            exposure: 0.0,
+            environment: Box::new(GradientSky::default()),
+            firefly_clamp: None,
+            shadow_epsilon: 0.001,
+            fog_color: Color::new(1.0, 1.0, 1.0),
+            fog_density: 0.0,
+            post_processes: Vec::new(),
+            render_mode: RenderMode::PathTrace,
+            projection: Projection::Perspective,
+            pixel_filter: PixelFilter::Box,
+            sampler: Sampler::Random,
+            lens_sampler: Sampler::Random,
+            lights: Vec::new(),
+            light_surfaces: Hittables::init(),
+            seed: None,
+            threads: None,
+            rays_traced: AtomicU64::new(0),
+            camera_rays: AtomicU64::new(0),
             image_height,
             pixel_upper_left_center,
             pixel_delta_u,
@@ -120,37 +580,1377 @@ impl Camera {
     }
 
     /// Given a `world` of `Hittable` objects, render the scene using ray casting and
-    /// save the resulting render in the provided `file`.
-    pub fn render(&self, file: &mut std::fs::File, world: &Hittables) {
-        // Write PPM identifier line
-        utils::add_ppm_header(file, self.image_width, self.image_height);
-        // Initialise progress bar
-        println!("Scanlines remaining");
-        let prog_bar = ProgressBar::new(self.image_height as u64);
-        // Render each pixel
-        for j in 0..self.image_height {
-            // Increment progress bar
-            prog_bar.inc(1);
-            for i in 0..self.image_width {
-                // Initialise color to black
-                let mut color: Color = Color::new(0.0, 0.0, 0.0);
-                // Loop through samples per pixel
-                for _ in 0..self.samples_per_pixel {
-                    // Get a ray
-                    let ray = Ray::get_ray(i, j, self);
-                    color += ray.ray_color(world, self.max_depth);
+    /// save the resulting render in the provided `writer` using the PPM `format`. The statistics
+    /// of the render are logged and returned.
+    pub fn render<W: Write>(
+        &self,
+        writer: &mut W,
+        world: &Hittables,
+        format: PpmFormat,
+    ) -> Result<RenderStats, RenderError> {
+        return self.render_cancellable(writer, world, &AtomicBool::new(false), format);
+    }
+
+    /// Render the scene as in `render`, but stop early when the `stop` flag is set, e.g. by a
+    /// Ctrl-C handler. The flag is checked before every scanline, the scanlines that were not
+    /// rendered yet stay black, and the partial image is written to the `writer` all the same.
+    pub fn render_cancellable<W: Write>(
+        &self,
+        writer: &mut W,
+        world: &Hittables,
+        stop: &AtomicBool,
+        format: PpmFormat,
+    ) -> Result<RenderStats, RenderError> {
+        self.rays_traced.store(0, Ordering::Relaxed);
+        self.camera_rays.store(0, Ordering::Relaxed);
+        let start: Instant = Instant::now();
+        let buffer: Vec<Color> = self.render_to_buffer_until(world, stop);
+        let wall_time: Duration = start.elapsed();
+        // Compute the statistics
+        let rays_traced: u64 = self.rays_traced.load(Ordering::Relaxed);
+        let camera_rays: u64 = self.camera_rays.load(Ordering::Relaxed);
+        let avg_depth: f64 = {
+            if camera_rays > 0 {
+                rays_traced as f64 / camera_rays as f64
+            } else {
+                0.0
+            }
+        };
+        let stats: RenderStats = RenderStats {
+            rays_traced,
+            wall_time,
+            avg_depth,
+        };
+        log::info!("Render statistics: {stats}");
+        // Write the image
+        self.write_ppm(writer, &buffer, format)?;
+        return Ok(stats);
+    }
+
+    /// Given a `world` of `Hittable` objects, render the scene using ray casting and return the
+    /// resulting pixel colors in scanline order. The scanlines are rendered in parallel into the
+    /// buffer. If the `fog_density` is positive, the path traced image is fogged afterwards, and
+    /// then the `post_processes` are applied in order.
+    pub fn render_to_buffer(&self, world: &Hittables) -> Vec<Color> {
+        return self.render_to_buffer_until(world, &AtomicBool::new(false));
+    }
+
+    /// Render the scene into a buffer as in `render_to_buffer`, but stop early when the `stop`
+    /// flag is set, see `render_cancellable`. A partial image is neither fogged nor post-processed.
+    pub fn render_to_buffer_until(&self, world: &Hittables, stop: &AtomicBool) -> Vec<Color> {
+        // The pool is built once and shared by the render and the fog.
+        let pool: Option<ThreadPool> = self.thread_pool();
+        let mut buffer: Vec<Color> =
+            self.render_buffer_until(pool.as_ref(), |i, j| self.render_pixel(i, j, world), stop);
+        if stop.load(Ordering::Relaxed) {
+            log::warn!("Render stopped early, the unrendered pixels are black");
+            return buffer;
+        }
+        self.finish_buffer(&mut buffer, world, pool.as_ref());
+        return buffer;
+    }
+
+    /// Finish the rendered image `buffer` of a path traced render: if the `fog_density` is
+    /// positive, the image is fogged with `apply_fog` in the `pool`, and then the `post_processes`
+    /// are applied in order. The debug render modes are left as they are.
+    fn finish_buffer(&self, buffer: &mut [Color], world: &Hittables, pool: Option<&ThreadPool>) {
+        if self.render_mode != RenderMode::PathTrace {
+            return;
+        }
+        if self.fog_density > 0.0 {
+            self.apply_fog(buffer, world, pool);
+        }
+        for post_process in self.post_processes.iter() {
+            post_process.apply(buffer, self.image_width, self.image_height);
+        }
+    }
+
+    /// Blend every pixel of the rendered image `buffer` towards the `fog_color` by the fraction
+    /// $1 - e^{-\text{density} \cdot d}$ of light that is scattered by the fog, with $d$ the
+    /// distance to the first surface that is seen through the pixel. Distant objects fade into
+    /// the fog, while near objects stay crisp, and the background is fully covered. The fraction
+    /// is averaged over the samples of the pixel, as in the depth pass, for anti-aliased edges.
+    /// The fraction is rendered in the `pool`, see `thread_pool`.
+    pub fn apply_fog(&self, buffer: &mut [Color], world: &Hittables, pool: Option<&ThreadPool>) {
+        let fog: Vec<Color> = self.render_buffer_with(pool, |i, j| {
+            if self.outside_image_circle(i, j) {
+                return Color::new(0.0, 0.0, 0.0);
+            }
+            self.seed_pixel(i, j);
+            let mut transmittance: f64 = 0.0;
+            for sample in 0..self.samples_per_pixel {
+                let ray = Ray::get_ray(i, j, sample, self);
+                transmittance += (-self.fog_density * ray.hit_distance(world, self)).exp();
+            }
+            let amount: f64 = 1.0 - transmittance * self.pixel_sample_scale;
+            return Color::new(amount, amount, amount);
+        });
+        for (color, amount) in buffer.iter_mut().zip(fog) {
+            *color = color.lerp(&self.fog_color, amount.x);
+        }
+    }
+
+    /// Render every pixel (i,j) of the image with `pixel_color` and return the resulting pixel
+    /// colors in scanline order. The scanlines are rendered in parallel into the buffer, on the
+    /// threads of the `pool`, see `thread_pool`.
+    fn render_buffer_with<F: Fn(i32, i32) -> Color + Sync>(
+        &self,
+        pool: Option<&ThreadPool>,
+        pixel_color: F,
+    ) -> Vec<Color> {
+        return self.render_buffer_until(pool, pixel_color, &AtomicBool::new(false));
+    }
+
+    /// Render the pixels as in `render_buffer_with`, but skip the remaining scanlines once the
+    /// `stop` flag is set, which then stay black.
+    fn render_buffer_until<F: Fn(i32, i32) -> Color + Sync>(
+        &self,
+        pool: Option<&ThreadPool>,
+        pixel_color: F,
+        stop: &AtomicBool,
+    ) -> Vec<Color> {
+        // Initialise progress bar, it counts the rendered pixels.
+        let prog_bar = utils::progress_bar((self.image_width * self.image_height) as u64, "Pixels");
+        // Initialise the image buffer to black
+        let mut buffer: Vec<Color> =
+            vec![Color::new(0.0, 0.0, 0.0); (self.image_width * self.image_height) as usize];
+        // Render each scanline on a separate thread
+        install(pool, || {
+            buffer
+                .par_chunks_mut(self.image_width as usize)
+                .enumerate()
+                .for_each(|(j, scanline)| {
+                    if stop.load(Ordering::Relaxed) {
+                        return;
+                    }
+                    for (i, color) in scanline.iter_mut().enumerate() {
+                        *color = pixel_color(i as i32, j as i32);
+                        // Increment progress bar
+                        prog_bar.inc(1);
+                    }
+                });
+        });
+        // Finish progress bar
+        prog_bar.finish();
+        return buffer;
+    }
+
+    /// Given a `world` of `Hittable` objects, render the depth pass of the scene and save it in
+    /// the provided `writer` using the PPM `format`, independent of the `render_mode`. See
+    /// `RenderMode::Depth` for how the distance to the first hit is encoded.
+    pub fn render_depth<W: Write>(
+        &self,
+        writer: &mut W,
+        world: &Hittables,
+        max_distance: f64,
+        format: PpmFormat,
+    ) -> Result<(), RenderError> {
+        let buffer: Vec<Color> = self.render_buffer_with(self.thread_pool().as_ref(), |i, j| {
+            if self.outside_image_circle(i, j) {
+                return Color::new(0.0, 0.0, 0.0);
+            }
+            // Average the depth over the samples of the pixel for anti-aliased edges.
+            self.seed_pixel(i, j);
+            let mut color: Color = Color::new(0.0, 0.0, 0.0);
+            for sample in 0..self.samples_per_pixel {
+                let ray = Ray::get_ray(i, j, sample, self);
+                color += ray.depth_color(world, max_distance, self);
+            }
+            return color * self.pixel_sample_scale;
+        });
+        // Write the image
+        return self.encode_ppm(
+            writer,
+            &buffer,
+            format,
+            1.0,
+            ToneMap::None,
+            ColorTransfer::Gamma(1.0),
+        );
+    }
+
+    /// Given a `world` of `Hittable` objects, render the albedo pass of the scene and save it in
+    /// the provided `writer` using the PPM `format`, independent of the `render_mode`. See
+    /// `RenderMode::Albedo` for what the albedo pass contains.
+    pub fn render_albedo<W: Write>(
+        &self,
+        writer: &mut W,
+        world: &Hittables,
+        format: PpmFormat,
+    ) -> Result<(), RenderError> {
+        let buffer: Vec<Color> = self.render_buffer_with(self.thread_pool().as_ref(), |i, j| {
+            if self.outside_image_circle(i, j) {
+                return Color::new(0.0, 0.0, 0.0);
+            }
+            // Average the albedo over the samples of the pixel for anti-aliased edges.
+            self.seed_pixel(i, j);
+            let mut color: Color = Color::new(0.0, 0.0, 0.0);
+            for sample in 0..self.samples_per_pixel {
+                let ray = Ray::get_ray(i, j, sample, self);
+                color += ray.albedo_color(world, self);
+            }
+            return color * self.pixel_sample_scale;
+        });
+        // Write the image
+        let transfer: ColorTransfer = self.color_transfer;
+        return self.encode_ppm(writer, &buffer, format, 1.0, ToneMap::None, transfer);
+    }
+
+    /// Given a `world` of `Hittable` objects, render the scene while showing the progress in a
+    /// window of the size of the image, and return the resulting pixel colors in scanline order.
+    /// The scanlines are rendered in parallel on background threads, while this thread draws
+    /// every finished scanline into the window. The window stays open after the render is done,
+    /// until it is closed or Escape is pressed. Once every scanline is done, the image is fogged
+    /// and post-processed as in `render_to_buffer` and the window shows the finished image.
+    /// Closing the window early stops the render, the scanlines that were not rendered yet are
+    /// black and the image is not finished. The returned buffer can be saved with `write_ppm`.
+    #[cfg(feature = "preview")]
+    pub fn render_preview(&self, world: &Hittables) -> Result<Vec<Color>, RenderError> {
+        use minifb::{Key, Window, WindowOptions};
+        use std::sync::mpsc;
+
+        let width: usize = self.image_width as usize;
+        let height: usize = self.image_height as usize;
+        let mut window = Window::new(
+            "Render preview (Esc to close)",
+            width,
+            height,
+            WindowOptions::default(),
+        )?;
+        window.set_target_fps(30);
+
+        // The image buffer, and the frame buffer of the window with one 0RGB value per pixel.
+        let mut buffer: Vec<Color> = vec![Color::new(0.0, 0.0, 0.0); width * height];
+        let mut frame_buffer: Vec<u32> = vec![0; width * height];
+        let (exposure_scale, tone_map, transfer) = self.encoding();
+        let to_frame = |color: &Color| {
+            let [r, g, b] = utils::color_to_bytes(&(*color * exposure_scale), tone_map, transfer);
+            return ((r as u32) << 16) | ((g as u32) << 8) | (b as u32);
+        };
+
+        // The pool is built once and shared by the render and the fog.
+        let pool: Option<ThreadPool> = self.thread_pool();
+        let pool: Option<&ThreadPool> = pool.as_ref();
+        let cancelled = AtomicBool::new(false);
+        let (sender, receiver) = mpsc::channel::<(usize, Vec<Color>)>();
+        return std::thread::scope(|scope| {
+            // Render each scanline on a separate thread and send it to the window when finished.
+            let cancelled: &AtomicBool = &cancelled;
+            scope.spawn(move || {
+                install(pool, || {
+                    (0..height)
+                        .into_par_iter()
+                        .for_each_with(sender, |sender, j| {
+                            if cancelled.load(Ordering::Relaxed) {
+                                return;
+                            }
+                            let scanline: Vec<Color> = (0..width)
+                                .map(|i| self.render_pixel(i as i32, j as i32, world))
+                                .collect();
+                            // Sending only fails if the window is already closed.
+                            let _ = sender.send((j, scanline));
+                        });
+                });
+            });
+
+            // Draw the finished scanlines until the window is closed.
+            let mut result: Result<(), RenderError> = Ok(());
+            let mut scanlines_done: usize = 0;
+            while window.is_open() && !window.is_key_down(Key::Escape) {
+                for (j, scanline) in receiver.try_iter() {
+                    for (i, color) in scanline.iter().enumerate() {
+                        frame_buffer[j * width + i] = to_frame(color);
+                    }
+                    buffer[j * width..(j + 1) * width].copy_from_slice(&scanline);
+                    scanlines_done += 1;
+                    // Finish the image once the last scanline is in, and show it.
+                    if scanlines_done == height {
+                        self.finish_buffer(&mut buffer, world, pool);
+                        for (pixel, color) in frame_buffer.iter_mut().zip(buffer.iter()) {
+                            *pixel = to_frame(color);
+                        }
+                    }
+                }
+                if let Err(err) = window.update_with_buffer(&frame_buffer, width, height) {
+                    result = Err(err.into());
+                    break;
                 }
-                // Write color to file
-                color *= self.pixel_sample_scale;
-                utils::write_color(file, &color);
             }
+            // Stop the rendering threads that are still busy.
+            cancelled.store(true, Ordering::Relaxed);
+            return result.map(|_| buffer);
+        });
+    }
+
+    /// Given a `world` of `Hittable` objects, render the scene in `passes` that each trace one
+    /// more sample through every pixel, so that the image refines over time instead of finishing
+    /// one pixel at a time. The samples are summed in an accumulation buffer, and after every pass
+    /// `callback` gets the average over the passes so far together with the zero-based index of
+    /// the pass, e.g. to show the intermediate image in a window. The final average is fogged and
+    /// post-processed as in `render_to_buffer` and returned, the intermediate images are not.
+    /// Every pixel keeps its own random number generator between the passes, so with a `seed` and
+    /// `passes` equal to `samples_per_pixel` the result is identical to `render_to_buffer`. Every
+    /// pixel has a sequence of `samples_per_pixel` samples, see `Ray::get_ray`, so at most that
+    /// many passes are rendered.
+    pub fn render_progressive<F: FnMut(&[Color], i32)>(
+        &self,
+        world: &Hittables,
+        passes: i32,
+        callback: F,
+    ) -> Vec<Color> {
+        // Without a seed, the generators of the pixels are seeded randomly.
+        let seed: u64 = self.seed.unwrap_or_else(rand::random);
+        let mut checkpoint: Checkpoint = Checkpoint::new(self.image_width, self.image_height, seed);
+        // The pool is built once and shared by the passes and the fog.
+        let pool: Option<ThreadPool> = self.thread_pool();
+        self.accumulate_passes(world, &mut checkpoint, passes, pool.as_ref(), callback);
+        let mut buffer: Vec<Color> = checkpoint.average();
+        self.finish_buffer(&mut buffer, world, pool.as_ref());
+        return buffer;
+    }
+
+    /// Get the image of the progressive render in `checkpoint`, e.g. of `resume_progressive`,
+    /// which is the average of its samples, fogged and post-processed as in `render_to_buffer`.
+    pub fn progressive_image(&self, world: &Hittables, checkpoint: &Checkpoint) -> Vec<Color> {
+        let mut buffer: Vec<Color> = checkpoint.average();
+        self.finish_buffer(&mut buffer, world, self.thread_pool().as_ref());
+        return buffer;
+    }
+
+    /// Resume the progressive render of `render_progressive` from `checkpoint`, e.g. loaded with
+    /// `load_checkpoint`, for `passes` more passes and return the new checkpoint. The passes
+    /// continue where the checkpoint stopped, with the `seed` of the checkpoint, so rendering 50
+    /// passes, saving and resuming for 50 more gives the same image as 100 passes in one go. The
+    /// index of the pass given to `callback` counts the passes of the checkpoint as well. The
+    /// checkpoint must have the size of the image, otherwise an `InvalidInput` error is returned.
+    /// The final image is made from the checkpoint with `progressive_image`.
+    pub fn resume_progressive<F: FnMut(&[Color], i32)>(
+        &self,
+        world: &Hittables,
+        checkpoint: Checkpoint,
+        passes: i32,
+        callback: F,
+    ) -> Result<Checkpoint, RenderError> {
+        if checkpoint.width != self.image_width || checkpoint.height != self.image_height {
+            return Err(RenderError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "checkpoint of {}x{} pixels does not fit an image of {}x{} pixels",
+                    checkpoint.width, checkpoint.height, self.image_width, self.image_height
+                ),
+            )));
+        }
+        let mut checkpoint: Checkpoint = checkpoint;
+        let pool: Option<ThreadPool> = self.thread_pool();
+        self.accumulate_passes(world, &mut checkpoint, passes, pool.as_ref(), callback);
+        return Ok(checkpoint);
+    }
+
+    /// Add `passes` passes of one sample per pixel to the accumulation buffer of `checkpoint`,
+    /// see `render_progressive`, and call `callback` with the average after every pass. The
+    /// passes run in the `pool`, see `thread_pool`. The generators of the pixels are restored from
+    /// the checkpoint before and stored in it after. The passes stop once the checkpoint has
+    /// `samples_per_pixel` samples, since the next sample would take the sampler into the sequence
+    /// of the next pixel.
+    fn accumulate_passes<F: FnMut(&[Color], i32)>(
+        &self,
+        world: &Hittables,
+        checkpoint: &mut Checkpoint,
+        passes: i32,
+        pool: Option<&ThreadPool>,
+        mut callback: F,
+    ) {
+        let remaining: i32 = (self.samples_per_pixel - checkpoint.samples_done).max(0);
+        if passes > remaining {
+            log::warn!(
+                "Only {remaining} of the {passes} passes are rendered, every pixel has {} samples",
+                self.samples_per_pixel
+            );
+        }
+        let passes: i32 = passes.min(remaining);
+        let width: usize = self.image_width as usize;
+        let seed: u64 = checkpoint.seed;
+        let mut rng_states: Vec<RngState> = checkpoint
+            .rng_positions
+            .iter()
+            .enumerate()
+            .map(|(k, &position)| {
+                let mut rng_state: RngState =
+                    RngState::for_pixel(seed, (k % width) as i32, (k / width) as i32);
+                rng_state.set_word_pos(position);
+                rng_state
+            })
+            .collect();
+        // Initialise progress bar, it counts the finished passes.
+        let prog_bar = utils::progress_bar(passes.max(0) as u64, "Passes");
+        for _ in 0..passes {
+            let sample: i32 = checkpoint.samples_done;
+            // Trace one sample through every pixel, each scanline on a separate thread.
+            install(pool, || {
+                checkpoint
+                    .accumulation
+                    .par_chunks_mut(width)
+                    .zip(rng_states.par_chunks_mut(width))
+                    .enumerate()
+                    .for_each(|(j, (scanline, rng_scanline))| {
+                        for (i, (color, rng_state)) in
+                            scanline.iter_mut().zip(rng_scanline).enumerate()
+                        {
+                            if self.outside_image_circle(i as i32, j as i32) {
+                                continue;
+                            }
+                            utils::swap_rng(rng_state);
+                            let ray = Ray::get_ray(i as i32, j as i32, sample, self);
+                            *color += self.clamp_sample(ray.ray_color(world, self.max_depth, self));
+                            utils::swap_rng(rng_state);
+                        }
+                    });
+            });
+            checkpoint.samples_done += 1;
+            callback(&checkpoint.average(), sample);
+            // Increment progress bar
+            prog_bar.inc(1);
         }
         // Finish progress bar
         prog_bar.finish();
+        for (position, rng_state) in checkpoint.rng_positions.iter_mut().zip(&rng_states) {
+            *position = rng_state.word_pos();
+        }
+    }
+
+    /// Bake the light arriving at `origin` in the `world` from every direction into an
+    /// equirectangular light probe of `resolution` by `resolution / 2` pixels, e.g. an environment
+    /// map for other renderers, which can be saved with `utils::write_pfm`. The longitude and
+    /// latitude of every probe pixel give its direction, with the center of the probe looking
+    /// down the negative z-axis and the top row looking straight up the y-axis. A single ray is
+    /// traced from `origin` through the center of every pixel with `ray_color`, independent of the
+    /// projection and the defocus blur of the camera, at the time the shutter opens. The colors
+    /// are the linear radiance without exposure, tone mapping or clamping.
+    pub fn bake_probe(&self, world: &Hittables, origin: Point, resolution: i32) -> Vec<Color> {
+        let width: i32 = resolution.max(1);
+        let height: i32 = (width / 2).max(1);
+        let mut probe: Vec<Color> = vec![Color::new(0.0, 0.0, 0.0); (width * height) as usize];
+        // Trace each row of the probe on a separate thread
+        install(self.thread_pool().as_ref(), || {
+            probe
+                .par_chunks_mut(width as usize)
+                .enumerate()
+                .for_each(|(j, row)| {
+                    for (i, color) in row.iter_mut().enumerate() {
+                        self.seed_pixel(i as i32, j as i32);
+                        let (longitude, latitude) = utils::equirectangular_angles(
+                            i as f64 + 0.5,
+                            j as f64 + 0.5,
+                            width,
+                            height,
+                        );
+                        let direction: Vec3 = Vec3::new(
+                            latitude.cos() * longitude.sin(),
+                            latitude.sin(),
+                            -latitude.cos() * longitude.cos(),
+                        );
+                        let ray: Ray = Ray::new(origin, direction, self.shutter_open);
+                        *color = ray.ray_color(world, self.max_depth, self);
+                    }
+                });
+        });
+        return probe;
+    }
+
+    /// Given a `world` of `Hittable` objects, render the scene in square tiles of `tile_size`
+    /// pixels and save the resulting render in the provided `writer` using the PPM `format`. The
+    /// tiles are rendered in parallel, each into their own small buffer, and are composited into
+    /// the full image once all tiles are done. Tiles at the right and bottom edges are clamped to
+    /// the image dimensions. The full image is fogged and post-processed as in `render_to_buffer`,
+    /// so with a `seed` it is identical to that of `render`.
+    pub fn render_tiled<W: Write>(
+        &self,
+        writer: &mut W,
+        world: &Hittables,
+        tile_size: i32,
+        format: PpmFormat,
+    ) -> Result<(), RenderError> {
+        // A tile must contain at least one pixel.
+        let tile_size: i32 = tile_size.max(1);
+        let tile_corners: Vec<(i32, i32)> = self.tile_corners(tile_size);
+        // Initialise progress bar, it counts the rendered pixels of the finished tiles.
+        let prog_bar = utils::progress_bar((self.image_width * self.image_height) as u64, "Tiles");
+        // The pool is built once and shared by the tiles and the fog.
+        let pool: Option<ThreadPool> = self.thread_pool();
+        // Render each tile on a separate thread
+        let tiles: Vec<Tile> = install(pool.as_ref(), || {
+            tile_corners
+                .par_iter()
+                .map(|&(x0, y0)| {
+                    let tile = self.render_tile(x0, y0, tile_size, world);
+                    // Increment progress bar
+                    prog_bar.inc(tile.pixels.len() as u64);
+                    tile
+                })
+                .collect()
+        });
+        // Finish progress bar
+        prog_bar.finish();
+
+        // Composite the tiles into the full image buffer
+        let mut buffer: Vec<Color> =
+            vec![Color::new(0.0, 0.0, 0.0); (self.image_width * self.image_height) as usize];
+        for tile in &tiles {
+            for (k, tile_scanline) in tile.pixels.chunks(tile.width as usize).enumerate() {
+                let start: usize = ((tile.y0 + k as i32) * self.image_width + tile.x0) as usize;
+                buffer[start..start + tile.width as usize].copy_from_slice(tile_scanline);
+            }
+        }
+        self.finish_buffer(&mut buffer, world, pool.as_ref());
+
+        // Write the image
+        return self.write_ppm(writer, &buffer, format);
+    }
+
+    /// Get the upper left pixel of every tile with sides of `tile_size` pixels, in scanline order.
+    /// Together, the tiles cover every pixel of the image exactly once, see `render_tile`.
+    pub fn tile_corners(&self, tile_size: i32) -> Vec<(i32, i32)> {
+        let tile_size: usize = tile_size.max(1) as usize;
+        return (0..self.image_height)
+            .step_by(tile_size)
+            .flat_map(|y0| {
+                (0..self.image_width)
+                    .step_by(tile_size)
+                    .map(move |x0| (x0, y0))
+            })
+            .collect();
+    }
+
+    /// Render the tile with upper left pixel (x0, y0) and sides of `tile_size` pixels. The tile
+    /// is clamped so that it does not extend beyond the image.
+    pub fn render_tile(&self, x0: i32, y0: i32, tile_size: i32, world: &Hittables) -> Tile {
+        let x1: i32 = (x0 + tile_size).min(self.image_width);
+        let y1: i32 = (y0 + tile_size).min(self.image_height);
+        let mut pixels: Vec<Color> = Vec::with_capacity(((x1 - x0) * (y1 - y0)) as usize);
+        for j in y0..y1 {
+            for i in x0..x1 {
+                pixels.push(self.render_pixel(i, j, world));
+            }
+        }
+        return Tile {
+            x0,
+            y0,
+            width: x1 - x0,
+            height: y1 - y0,
+            pixels,
+        };
+    }
+
+    /// Given a `world` of `Hittable` objects, render only the pixels (i,j) with `x0 <= i < x1`
+    /// and `y0 <= j < y1` and save the resulting render in the provided `writer` using the PPM
+    /// `format`. The image keeps its full size, the pixels outside the region are left black. The
+    /// region is clamped to the image dimensions. The image is fogged and post-processed as in
+    /// `render_to_buffer`, over the full image, so the fog also covers the pixels outside the
+    /// region. With a `seed`, the pixels inside the region are identical to those of a full
+    /// render, unless a post-process such as `Bloom` spreads light between the pixels.
+    pub fn render_region<W: Write>(
+        &self,
+        writer: &mut W,
+        world: &Hittables,
+        x0: i32,
+        y0: i32,
+        x1: i32,
+        y1: i32,
+        format: PpmFormat,
+    ) -> Result<(), RenderError> {
+        let columns: std::ops::Range<i32> = x0.max(0)..x1.min(self.image_width);
+        let rows: std::ops::Range<i32> = y0.max(0)..y1.min(self.image_height);
+        // The pool is built once and shared by the render and the fog.
+        let pool: Option<ThreadPool> = self.thread_pool();
+        let mut buffer: Vec<Color> = self.render_buffer_with(pool.as_ref(), |i, j| {
+            if columns.contains(&i) && rows.contains(&j) {
+                return self.render_pixel(i, j, world);
+            }
+            return Color::new(0.0, 0.0, 0.0);
+        });
+        self.finish_buffer(&mut buffer, world, pool.as_ref());
+        // Write the image
+        return self.write_ppm(writer, &buffer, format);
+    }
+
+    /// Write the rendered image `buffer` to `writer` as a PPM image of the given `format`, encoded
+    /// as described by `encoding`.
+    pub fn write_ppm<W: Write>(
+        &self,
+        writer: &mut W,
+        buffer: &[Color],
+        format: PpmFormat,
+    ) -> Result<(), RenderError> {
+        let (exposure_scale, tone_map, transfer) = self.encoding();
+        return self.encode_ppm(writer, buffer, format, exposure_scale, tone_map, transfer);
+    }
+
+    /// Get the exposure scale, tone mapping and color transfer with which rendered colors are
+    /// encoded for display. The colors are scaled by the exposure before they are tone mapped and
+    /// transfer encoded. Depth passes are written as is, albedo passes are only transfer encoded.
+    pub fn encoding(&self) -> (f64, ToneMap, ColorTransfer) {
+        return match self.render_mode {
+            RenderMode::Depth { .. } => (1.0, ToneMap::None, ColorTransfer::Gamma(1.0)),
+            RenderMode::Albedo => (1.0, ToneMap::None, self.color_transfer),
+            RenderMode::PathTrace | RenderMode::Normals => {
+                (self.exposure_scale(), self.tone_map, self.color_transfer)
+            }
+        };
+    }
+
+    /// Write the image `buffer` to `writer` as a PPM image of the given `format`. The colors are
+    /// multiplied by `exposure_scale`, tone mapped with `tone_map` and encoded with `transfer`.
+    fn encode_ppm<W: Write>(
+        &self,
+        writer: &mut W,
+        buffer: &[Color],
+        format: PpmFormat,
+        exposure_scale: f64,
+        tone_map: ToneMap,
+        transfer: ColorTransfer,
+    ) -> Result<(), RenderError> {
+        // Non-finite colors are written as black, report them since they point to a bug.
+        let non_finite: usize = buffer.iter().filter(|color| !color.is_finite()).count();
+        if non_finite > 0 {
+            log::warn!(
+                "{non_finite} pixels have an infinite or NaN color, they are written as black"
+            );
+        }
+        match format {
+            PpmFormat::Ascii => {
+                // Write PPM identifier line
+                utils::add_ppm_header(writer, self.image_width, self.image_height)?;
+                // Write colors
+                for color in buffer {
+                    let color: Color = *color * exposure_scale;
+                    utils::write_color(writer, &color, tone_map, transfer)?;
+                }
+            }
+            PpmFormat::Binary => {
+                // Write PPM identifier line
+                utils::add_ppm_header_p6(writer, self.image_width, self.image_height)?;
+                // Write colors
+                for color in buffer {
+                    let color: Color = *color * exposure_scale;
+                    utils::write_color_p6(writer, &color, tone_map, transfer)?;
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    /// Get the factor by which the colors are scaled for the exposure. Every stop doubles the
+    /// brightness, e.g. the factor is $2^{\text{exposure}}$.
+    pub fn exposure_scale(&self) -> f64 {
+        return 2f64.powf(self.exposure);
+    }
+
+    /// Compute the color of the pixel at location (i,j) by averaging the colors of
+    /// `samples_per_pixel` rays sent through that pixel.
+    pub fn render_pixel(&self, i: i32, j: i32, world: &Hittables) -> Color {
+        if self.outside_image_circle(i, j) {
+            return Color::new(0.0, 0.0, 0.0);
+        }
+        self.seed_pixel(i, j);
+        self.camera_rays
+            .fetch_add(self.samples_per_pixel as u64, Ordering::Relaxed);
+        // Initialise color to black
+        let mut color: Color = Color::new(0.0, 0.0, 0.0);
+        // Loop through samples per pixel
+        for sample in 0..self.samples_per_pixel {
+            // Get a ray
+            let ray = Ray::get_ray(i, j, sample, self);
+            color += self.clamp_sample(ray.ray_color(world, self.max_depth, self));
+        }
+        return color * self.pixel_sample_scale;
+    }
+
+    /// Build the thread pool with `threads` threads on which the image is rendered. Without
+    /// `threads`, there is no pool of its own and the global `rayon` pool renders the image on all
+    /// cores. Zero threads also means all cores. If the pool cannot be built, this is logged and
+    /// the global pool is used instead.
+    /// Building a pool spawns its threads, so every render builds it once and passes it on.
+    pub fn thread_pool(&self) -> Option<ThreadPool> {
+        let threads: usize = self.threads?;
+        return match ThreadPoolBuilder::new().num_threads(threads).build() {
+            Ok(pool) => Some(pool),
+            Err(err) => {
+                log::warn!("Could not build a pool of {threads} threads, using all cores: {err}");
+                None
+            }
+        };
+    }
+
+    /// If the camera has a `seed`, seed the random number generator of the current thread for
+    /// pixel (i,j), see `utils::pixel_rng`. Every pixel then draws the same random numbers no
+    /// matter which thread renders it, so a seeded render is bit-identical for any amount of
+    /// threads.
+    pub fn seed_pixel(&self, i: i32, j: i32) {
+        if let Some(seed) = self.seed {
+            utils::seed_pixel_rng(seed, i, j);
+        }
     }
 
-    pub fn defocus_disk_sample(&self) -> Point {
-        let p: Vec3 = Vec3::get_random_in_unit_disk();
+    /// Clamp every color channel of a single `sample` to `firefly_clamp`, if it is set. Rare
+    /// paths that find a bright light through a specular bounce carry a huge radiance, which
+    /// shows up as isolated bright pixels, "fireflies", that do not average out. Clamping before
+    /// averaging removes them, but it also removes energy, so the image is biased to be slightly
+    /// darker around bright highlights and light sources.
+    pub fn clamp_sample(&self, sample: Color) -> Color {
+        return match self.firefly_clamp {
+            Some(max_radiance) => Color::new(
+                sample.x.min(max_radiance),
+                sample.y.min(max_radiance),
+                sample.z.min(max_radiance),
+            ),
+            None => sample,
+        };
+    }
+
+    /// Get a random time at which a ray is cast, uniformly sampled between the opening and
+    /// closing of the shutter. If they are equal, no motion blur occurs and no random number is
+    /// drawn.
+    pub fn shutter_sample(&self) -> f64 {
+        if self.shutter_open == self.shutter_close {
+            return self.shutter_open;
+        }
+        return utils::get_random_in_range(self.shutter_open, self.shutter_close);
+    }
+
+    /// Get the distance of the location (x,y) on the image, in pixels from the upper left corner,
+    /// to the image center, relative to the radius of the largest circle that fits in the image.
+    pub fn image_circle_radius(&self, x: f64, y: f64) -> f64 {
+        let half_width: f64 = self.image_width as f64 / 2.0;
+        let half_height: f64 = self.image_height as f64 / 2.0;
+        let dx: f64 = x - half_width;
+        let dy: f64 = y - half_height;
+        return (dx * dx + dy * dy).sqrt() / half_width.min(half_height);
+    }
+
+    /// Check if the center of pixel (i,j) lies outside the circular image of a `Fisheye`
+    /// projection. With any other projection, every pixel lies inside the image.
+    pub fn outside_image_circle(&self, i: i32, j: i32) -> bool {
+        if let Projection::Fisheye { .. } = self.projection {
+            return self.image_circle_radius(i as f64 + 0.5, j as f64 + 0.5) > 1.0;
+        }
+        return false;
+    }
+
+    /// Get the direction of the ray through the location (x,y) on the image, in pixels from the
+    /// upper left corner, with the equidistant fisheye model, see `Projection::Fisheye`. The angle
+    /// with the viewing direction $-w$ is the relative distance to the image center times half of
+    /// `fov_degrees`, and the direction is turned towards (x,y) around the viewing direction.
+    pub fn fisheye_direction(&self, x: f64, y: f64, fov_degrees: f64) -> Vec3 {
+        let theta: f64 =
+            self.image_circle_radius(x, y) * utils::degrees_to_radians(fov_degrees) / 2.0;
+        // The image y-axis points down, while v points up.
+        let phi: f64 =
+            (self.image_height as f64 / 2.0 - y).atan2(x - self.image_width as f64 / 2.0);
+        let sideways: Vec3 = self.u * phi.cos() + self.v * phi.sin();
+        return sideways * theta.sin() - self.w * theta.cos();
+    }
+
+    /// Get the point on the defocus disk from which the ray of the sample with the given
+    /// `sample_index` starts, see `Sampler::sample_lens`.
+    pub fn defocus_disk_sample(&self, sample_index: u64) -> Point {
+        let p: Vec3 = self.lens_sampler.sample_lens(sample_index);
         return self.center + (self.defocus_u * p.x) + (self.defocus_v * p.y);
     }
 }
+
+/// Run `op` in the `pool`, see `Camera::thread_pool`, so that the parallel iterators inside of it
+/// are spread over its threads. Without a pool, `op` runs in the global `rayon` pool.
+fn install<R: Send, OP: FnOnce() -> R + Send>(pool: Option<&ThreadPool>, op: OP) -> R {
+    return match pool {
+        Some(pool) => pool.install(op),
+        None => op(),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hittables::plane::Plane;
+    use crate::hittables::sphere::Sphere;
+    use crate::materials::materials::{DiffuseLight, Lambertian};
+
+    #[test]
+    fn tiles_of_three_cover_a_ten_by_ten_image_once() {
+        let camera: Camera = Camera::builder()
+            .aspect_ratio(1.0)
+            .width(10)
+            .samples(1)
+            .max_depth(1)
+            .build();
+        let world: Hittables = Hittables::init();
+        let mut coverage: Vec<i32> = vec![0; 100];
+        for (x0, y0) in camera.tile_corners(3) {
+            let tile: Tile = camera.render_tile(x0, y0, 3, &world);
+            assert_eq!(tile.pixels.len(), (tile.width * tile.height) as usize);
+            for j in y0..y0 + tile.height {
+                for i in x0..x0 + tile.width {
+                    coverage[(j * 10 + i) as usize] += 1;
+                }
+            }
+        }
+        assert!(coverage.iter().all(|&count| count == 1));
+
+        // The tiled image has a pixel for every one of the 100 pixels.
+        let mut output: Vec<u8> = Vec::new();
+        camera
+            .render_tiled(&mut output, &world, 3, PpmFormat::Binary)
+            .unwrap();
+        let header: &[u8] = b"P6\n10 10\n255\n";
+        assert!(output.starts_with(header));
+        assert_eq!(output.len() - header.len(), 100 * 3);
+    }
+
+    #[test]
+    fn sphere_straight_ahead_covers_the_center_pixel() {
+        let camera: Camera = Camera::builder()
+            .aspect_ratio(1.0)
+            .width(11)
+            .samples(4)
+            .max_depth(4)
+            .seed(1)
+            .build();
+        let mut world: Hittables = Hittables::init();
+        let red = world.add_material(Lambertian::new(Color::new(0.8, 0.1, 0.1)));
+        world.add(Arc::new(Sphere::new(Point::new(0.0, 0.0, -2.0), 0.5, red)));
+
+        let buffer: Vec<Color> = camera.render_to_buffer(&world);
+        assert_eq!(buffer.len(), 11 * 11);
+        let sky: Color = GradientSky::default().sample(Vec3::new(0.0, 0.0, -1.0));
+        let center: Color = buffer[5 * 11 + 5];
+        assert_ne!(center, sky);
+        assert!(center.x > center.z);
+        // The corner looks past the sphere at the sky.
+        assert!(buffer[0].z >= buffer[0].x);
+    }
+
+    #[test]
+    fn render_writes_a_plain_ppm_into_a_byte_buffer() {
+        let camera: Camera = Camera::builder()
+            .aspect_ratio(2.0)
+            .width(4)
+            .samples(1)
+            .max_depth(1)
+            .build();
+        let mut output: Vec<u8> = Vec::new();
+        camera
+            .render(&mut output, &Hittables::init(), PpmFormat::Ascii)
+            .unwrap();
+        assert!(output.starts_with(b"P3\n"));
+        let text: String = String::from_utf8(output).unwrap();
+        assert_eq!(text.lines().nth(1), Some("4 2"));
+        assert_eq!(text.lines().count(), 3 + 4 * 2);
+    }
+
+    #[test]
+    fn exposure_of_one_stop_doubles_the_pixel_values() {
+        let encode = |exposure: f64| {
+            let camera: Camera = Camera::builder()
+                .aspect_ratio(1.0)
+                .width(1)
+                .gamma(1.0)
+                .exposure(exposure)
+                .build();
+            let mut output: Vec<u8> = Vec::new();
+            let buffer: Vec<Color> = vec![Color::new(0.1, 0.2, 0.3)];
+            camera
+                .write_ppm(&mut output, &buffer, PpmFormat::Binary)
+                .unwrap();
+            return output[output.len() - 3..].to_vec();
+        };
+        let base: Vec<u8> = encode(0.0);
+        let doubled: Vec<u8> = encode(1.0);
+        for (base, doubled) in base.iter().zip(doubled.iter()) {
+            assert!((*doubled as i32 - 2 * *base as i32).abs() <= 1);
+        }
+    }
+
+    #[test]
+    fn builder_and_initialize_derive_the_same_image_height() {
+        for (aspect_ratio, width) in [(16.0 / 9.0, 400), (4.0 / 3.0, 101), (3.0, 2)] {
+            let built: Camera = Camera::builder()
+                .aspect_ratio(aspect_ratio)
+                .width(width)
+                .build();
+            let defaults: CameraBuilder = CameraBuilder::default();
+            let initialized: Camera = Camera::initialize(
+                aspect_ratio,
+                width,
+                defaults.center,
+                defaults.samples_per_pixel,
+                defaults.max_depth,
+                defaults.vfov,
+                defaults.look_at,
+                defaults.vup,
+                defaults.roll,
+                defaults.defocus_angle,
+                defaults.focus_dist,
+                defaults.shutter_open,
+                defaults.shutter_close,
+            );
+            assert_eq!(built.image_height, initialized.image_height);
+            assert_eq!(built.pixel_delta_u, initialized.pixel_delta_u);
+        }
+        // An image is at least one pixel high.
+        assert_eq!(
+            Camera::builder()
+                .aspect_ratio(3.0)
+                .width(2)
+                .build()
+                .image_height,
+            1
+        );
+    }
+
+    #[test]
+    fn firefly_clamp_bounds_every_sample() {
+        let camera: Camera = Camera::builder()
+            .aspect_ratio(1.0)
+            .width(4)
+            .samples(8)
+            .firefly_clamp(2.0)
+            .build();
+        let clamped: Color = camera.clamp_sample(Color::new(50.0, 1.0, 2.5));
+        assert_eq!(clamped, Color::new(2.0, 1.0, 2.0));
+
+        // A light filling the view is far brighter than the clamp, but no pixel exceeds it.
+        let mut world: Hittables = Hittables::init();
+        let light = world.add_material(DiffuseLight::new(Color::new(40.0, 40.0, 40.0)));
+        world.add(Arc::new(Sphere::new(
+            Point::new(0.0, 0.0, 0.0),
+            10.0,
+            light,
+        )));
+        let buffer: Vec<Color> = camera.render_to_buffer(&world);
+        for color in buffer {
+            assert!(color.as_array().iter().all(|&channel| channel <= 2.0));
+            assert!(color.x > 1.9);
+        }
+    }
+
+    /// A writer whose every write fails, like a full disk.
+    struct FailingWriter;
+
+    impl Write for FailingWriter {
+        fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+            return Err(std::io::Error::other("disk full"));
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            return Ok(());
+        }
+    }
+
+    #[test]
+    fn failing_writer_gives_an_error_instead_of_exiting() {
+        let camera: Camera = Camera::builder()
+            .aspect_ratio(1.0)
+            .width(2)
+            .samples(1)
+            .max_depth(1)
+            .build();
+        let world: Hittables = Hittables::init();
+        for format in [PpmFormat::Ascii, PpmFormat::Binary] {
+            let result = camera.render(&mut FailingWriter, &world, format);
+            assert!(matches!(result, Err(RenderError::Io(_))));
+            let buffer: Vec<Color> = vec![Color::new(0.5, 0.5, 0.5); 4];
+            assert!(camera
+                .write_ppm(&mut FailingWriter, &buffer, format)
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn one_and_four_threads_render_identical_buffers() {
+        let render = |threads: usize| {
+            let camera: Camera = Camera::builder()
+                .aspect_ratio(1.0)
+                .width(12)
+                .samples(8)
+                .max_depth(8)
+                .seed(64)
+                .threads(threads)
+                .build();
+            let mut world: Hittables = Hittables::init();
+            let gray = world.add_material(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+            world.add(Arc::new(Sphere::new(Point::new(0.0, 0.0, -2.0), 0.8, gray)));
+            return camera.render_to_buffer(&world);
+        };
+        assert_eq!(render(1), render(4));
+    }
+
+    #[test]
+    fn region_is_black_outside_and_matches_a_full_render_inside() {
+        let camera: Camera = Camera::builder()
+            .aspect_ratio(1.0)
+            .width(8)
+            .samples(4)
+            .max_depth(4)
+            .seed(72)
+            .build();
+        let mut world: Hittables = Hittables::init();
+        let gray = world.add_material(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        world.add(Arc::new(Sphere::new(Point::new(0.0, 0.0, -2.0), 0.8, gray)));
+
+        let mut full: Vec<u8> = Vec::new();
+        camera.render(&mut full, &world, PpmFormat::Binary).unwrap();
+        let mut region: Vec<u8> = Vec::new();
+        camera
+            .render_region(&mut region, &world, 2, 3, 6, 5, PpmFormat::Binary)
+            .unwrap();
+        let header: &[u8] = b"P6\n8 8\n255\n";
+        assert!(region.starts_with(header));
+        assert_eq!(region.len(), full.len());
+        for j in 0..8 {
+            for i in 0..8 {
+                let start: usize = header.len() + 3 * (j * 8 + i);
+                let pixel: &[u8] = &region[start..start + 3];
+                if (2..6).contains(&i) && (3..5).contains(&j) {
+                    assert_eq!(pixel, &full[start..start + 3]);
+                    assert_ne!(pixel, [0, 0, 0]);
+                } else {
+                    assert_eq!(pixel, [0, 0, 0]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn auto_focus_focuses_on_the_look_at_point() {
+        let builder = || {
+            return Camera::builder()
+                .center(Point::new(3.0, 4.0, 0.0))
+                .look_at(Point::new(0.0, 0.0, -12.0))
+                .focus_dist(2.0);
+        };
+        assert_eq!(builder().auto_focus(true).build().focus_dist, 13.0);
+        assert_eq!(builder().build().focus_dist, 2.0);
+    }
+
+    #[test]
+    fn quarter_roll_swaps_the_pixel_deltas() {
+        let camera = |roll: f64| {
+            return Camera::builder()
+                .aspect_ratio(1.0)
+                .width(10)
+                .roll(roll)
+                .build();
+        };
+        let level: Camera = camera(0.0);
+        let rolled: Camera = camera(90.0);
+        let close = |a: Vec3, b: Vec3| (a - b).length() < 1e-12;
+        assert!(close(rolled.pixel_delta_u, -level.pixel_delta_v));
+        assert!(close(rolled.pixel_delta_v, level.pixel_delta_u));
+        // No roll is the camera without a roll.
+        let default: Camera = Camera::builder().aspect_ratio(1.0).width(10).build();
+        assert_eq!(level.pixel_delta_u, default.pixel_delta_u);
+        assert_eq!(level.pixel_delta_v, default.pixel_delta_v);
+    }
+
+    #[test]
+    fn render_statistics_count_every_camera_ray() {
+        let camera: Camera = Camera::builder()
+            .aspect_ratio(1.0)
+            .width(6)
+            .samples(3)
+            .max_depth(5)
+            .seed(77)
+            .build();
+        let mut world: Hittables = Hittables::init();
+        let gray = world.add_material(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        world.add(Arc::new(Sphere::new(Point::new(0.0, 0.0, -2.0), 0.8, gray)));
+        let stats: RenderStats = camera
+            .render(&mut Vec::new(), &world, PpmFormat::Binary)
+            .unwrap();
+        assert!(stats.rays_traced >= 6 * 6 * 3);
+        assert!(stats.avg_depth >= 1.0 && stats.avg_depth <= 5.0);
+
+        // The black pixels outside the image circle of a fisheye camera are not traced, so they
+        // do not lower the average depth of an empty world, which is one.
+        let fisheye: Camera = Camera::builder()
+            .aspect_ratio(1.0)
+            .width(6)
+            .samples(3)
+            .projection(Projection::Fisheye { fov_degrees: 180.0 })
+            .build();
+        let stats: RenderStats = fisheye
+            .render(&mut Vec::new(), &Hittables::init(), PpmFormat::Binary)
+            .unwrap();
+        assert!(stats.rays_traced < 6 * 6 * 3);
+        assert_eq!(stats.avg_depth, 1.0);
+    }
+
+    #[test]
+    fn far_wall_is_foggier_than_a_near_wall() {
+        let fog: Color = Color::new(1.0, 1.0, 1.0);
+        let render = |distance: f64| {
+            let camera: Camera = Camera::builder()
+                .aspect_ratio(1.0)
+                .width(1)
+                .samples(4)
+                .max_depth(2)
+                .seed(79)
+                .fog(fog, 0.1)
+                .build();
+            let mut world: Hittables = Hittables::init();
+            let black = world.add_material(Lambertian::new(Color::new(0.0, 0.0, 0.0)));
+            world.add(Arc::new(Plane::new(
+                Point::new(0.0, 0.0, -distance),
+                Vec3::new(0.0, 0.0, 1.0),
+                black,
+            )));
+            return camera.render_to_buffer(&world)[0];
+        };
+        let near: Color = render(1.0);
+        let far: Color = render(20.0);
+        assert!((fog - far).length() < (fog - near).length());
+        // The black walls only get their color from the fog, which never covers them fully.
+        assert!(near.x > 0.0 && far.x < 1.0);
+    }
+
+    /// Post-process that doubles every pixel.
+    struct Double;
+
+    impl PostProcess for Double {
+        fn apply(&self, buffer: &mut [Color], _width: i32, _height: i32) {
+            for color in buffer.iter_mut() {
+                *color *= 2.0;
+            }
+        }
+    }
+
+    /// Post-process that adds a gray of 0.1 to every pixel.
+    struct AddGray;
+
+    impl PostProcess for AddGray {
+        fn apply(&self, buffer: &mut [Color], _width: i32, _height: i32) {
+            for color in buffer.iter_mut() {
+                *color += Color::new(0.1, 0.1, 0.1);
+            }
+        }
+    }
+
+    #[test]
+    fn post_processes_are_applied_in_order() {
+        let sky: Color = Color::new(0.25, 0.25, 0.25);
+        let camera: Camera = Camera::builder()
+            .aspect_ratio(1.0)
+            .width(2)
+            .samples(1)
+            .environment(Box::new(GradientSky::new(sky, sky)))
+            .post_process(Box::new(Double))
+            .post_process(Box::new(AddGray))
+            .build();
+        let buffer: Vec<Color> = camera.render_to_buffer(&Hittables::init());
+        assert_eq!(buffer.len(), 4);
+        for color in buffer {
+            // Adding the gray first would give 0.7 instead.
+            assert!((color - Color::new(0.6, 0.6, 0.6)).length() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn fisheye_center_looks_ahead_and_the_edge_at_half_the_fov() {
+        let camera: Camera = Camera::builder()
+            .aspect_ratio(1.0)
+            .width(10)
+            .projection(Projection::Fisheye { fov_degrees: 120.0 })
+            .build();
+        let center: Vec3 = camera.fisheye_direction(5.0, 5.0, 120.0);
+        assert!((center - camera.w.neg()).length() < 1e-12);
+        // Right and top edge of the image circle, 60 degrees away from the viewing direction.
+        for (x, y) in [(10.0, 5.0), (5.0, 0.0)] {
+            let edge: Vec3 = camera.fisheye_direction(x, y, 120.0);
+            let angle: f64 = edge.unit_vector().dot(&camera.w.neg()).acos();
+            assert!((angle - utils::degrees_to_radians(60.0)).abs() < 1e-12);
+        }
+        assert!(camera.fisheye_direction(10.0, 5.0, 120.0).dot(&camera.u) > 0.0);
+        assert!(camera.fisheye_direction(5.0, 0.0, 120.0).dot(&camera.v) > 0.0);
+        // The corners lie outside the image circle and stay black.
+        assert!(camera.outside_image_circle(0, 0));
+        assert!(!camera.outside_image_circle(5, 5));
+        let buffer: Vec<Color> = camera.render_to_buffer(&Hittables::init());
+        assert_eq!(buffer[0], Color::new(0.0, 0.0, 0.0));
+        assert_ne!(buffer[5 * 10 + 5], Color::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn stop_flag_set_before_rendering_gives_a_black_image() {
+        let camera: Camera = Camera::builder()
+            .aspect_ratio(2.0)
+            .width(6)
+            .samples(2)
+            .build();
+        let mut output: Vec<u8> = Vec::new();
+        camera
+            .render_cancellable(
+                &mut output,
+                &Hittables::init(),
+                &AtomicBool::new(true),
+                PpmFormat::Binary,
+            )
+            .unwrap();
+        let header: &[u8] = b"P6\n6 3\n255\n";
+        assert!(output.starts_with(header));
+        assert_eq!(output.len(), header.len() + 6 * 3 * 3);
+        assert!(output[header.len()..].iter().all(|&byte| byte == 0));
+    }
+
+    #[test]
+    fn binary_render_into_a_byte_buffer_parses_as_ppm() {
+        let camera: Camera = Camera::builder()
+            .aspect_ratio(4.0 / 3.0)
+            .width(8)
+            .samples(1)
+            .max_depth(2)
+            .build();
+        let mut output: Vec<u8> = Vec::new();
+        camera
+            .render(&mut output, &Hittables::init(), PpmFormat::Binary)
+            .unwrap();
+        // The header is the magic number, the width, the height and the maximum value, each
+        // followed by a single whitespace character.
+        let fields: Vec<&[u8]> = output
+            .splitn(5, |&byte| byte.is_ascii_whitespace())
+            .collect();
+        assert_eq!(fields[0], b"P6");
+        assert_eq!(fields[1], b"8");
+        assert_eq!(fields[2], b"6");
+        assert_eq!(fields[3], b"255");
+        assert_eq!(fields[4].len(), 8 * 6 * 3);
+    }
+
+    #[test]
+    fn thread_pool_has_the_requested_size_and_keeps_the_output() {
+        let camera = |threads: Option<usize>| {
+            let mut camera: Camera = Camera::builder()
+                .aspect_ratio(1.0)
+                .width(6)
+                .samples(4)
+                .max_depth(4)
+                .seed(101)
+                .fog(Color::new(0.5, 0.5, 0.5), 0.05)
+                .build();
+            camera.threads = threads;
+            return camera;
+        };
+        assert!(camera(None).thread_pool().is_none());
+        let pool: ThreadPool = camera(Some(3)).thread_pool().unwrap();
+        assert_eq!(pool.current_num_threads(), 3);
+        assert_eq!(install(Some(&pool), rayon::current_num_threads), 3);
+
+        let mut world: Hittables = Hittables::init();
+        let gray = world.add_material(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        world.add(Arc::new(Sphere::new(Point::new(0.0, 0.0, -2.0), 0.8, gray)));
+        let global: Vec<Color> = camera(None).render_to_buffer(&world);
+        assert_eq!(camera(Some(3)).render_to_buffer(&world), global);
+        let progressive = |threads: Option<usize>| {
+            return camera(threads).render_progressive(&world, 4, |_, _| {});
+        };
+        assert_eq!(progressive(Some(3)), progressive(None));
+    }
+
+    #[test]
+    fn progressive_render_equals_the_direct_render() {
+        let camera: Camera = Camera::builder()
+            .aspect_ratio(1.0)
+            .width(6)
+            .samples(5)
+            .max_depth(4)
+            .seed(102)
+            .sampler(Sampler::Halton)
+            .fog(Color::new(0.6, 0.6, 0.7), 0.1)
+            .vignette(0.5)
+            .build();
+        let mut world: Hittables = Hittables::init();
+        let gray = world.add_material(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        world.add(Arc::new(Sphere::new(Point::new(0.0, 0.0, -2.0), 0.8, gray)));
+
+        let direct: Vec<Color> = camera.render_to_buffer(&world);
+        let mut passes_seen: Vec<i32> = Vec::new();
+        let progressive: Vec<Color> =
+            camera.render_progressive(&world, 5, |_, pass| passes_seen.push(pass));
+        assert_eq!(progressive, direct);
+        assert_eq!(passes_seen, vec![0, 1, 2, 3, 4]);
+
+        // Passes beyond the samples of a pixel are not rendered.
+        let mut passes_seen: Vec<i32> = Vec::new();
+        let overrun: Vec<Color> =
+            camera.render_progressive(&world, 8, |_, pass| passes_seen.push(pass));
+        assert_eq!(overrun, direct);
+        assert_eq!(passes_seen.len(), 5);
+    }
+
+    #[test]
+    fn probe_top_row_samples_the_sky_top() {
+        let bottom: Color = Color::new(0.0, 0.0, 0.0);
+        let top: Color = Color::new(0.2, 0.6, 1.0);
+        let camera: Camera = Camera::builder()
+            .environment(Box::new(GradientSky::new(bottom, top)))
+            .seed(106)
+            .build();
+        let mut world: Hittables = Hittables::init();
+        let ground = world.add_material(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        world.add(Arc::new(Plane::new(
+            Point::new(0.0, -1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            ground,
+        )));
+        let probe: Vec<Color> = camera.bake_probe(&world, Point::new(0.0, 0.0, 0.0), 64);
+        assert_eq!(probe.len(), 64 * 32);
+        // The centers of the top row lie half a pixel, 1/64 of a half turn, below straight up.
+        let sky_top: Color = GradientSky::new(bottom, top).sample(Vec3::new(0.0, 1.0, 0.0));
+        for color in &probe[..64] {
+            assert!(
+                (*color - sky_top).length() < 1e-3,
+                "{color:?} is not the sky top"
+            );
+        }
+        // The bottom row looks down at the gray ground, which only reflects the upper sky.
+        for color in &probe[64 * 31..] {
+            assert!(color.y < 0.5 * top.y);
+        }
+    }
+
+    #[test]
+    fn tiled_and_region_renders_are_post_processed_like_render() {
+        let builder = || {
+            return Camera::builder()
+                .aspect_ratio(1.0)
+                .width(8)
+                .samples(4)
+                .max_depth(3)
+                .seed(81);
+        };
+        let camera: Camera = builder().vignette(0.8).build();
+        let mut world: Hittables = Hittables::init();
+        let gray = world.add_material(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        world.add(Arc::new(Sphere::new(Point::new(0.0, 0.0, -2.0), 0.5, gray)));
+
+        let mut direct: Vec<u8> = Vec::new();
+        camera
+            .render(&mut direct, &world, PpmFormat::Binary)
+            .unwrap();
+        let mut tiled: Vec<u8> = Vec::new();
+        camera
+            .render_tiled(&mut tiled, &world, 3, PpmFormat::Binary)
+            .unwrap();
+        assert_eq!(tiled, direct);
+        let mut region: Vec<u8> = Vec::new();
+        camera
+            .render_region(&mut region, &world, 0, 0, 8, 8, PpmFormat::Binary)
+            .unwrap();
+        assert_eq!(region, direct);
+
+        // The vignette did darken the image.
+        let mut plain: Vec<u8> = Vec::new();
+        builder()
+            .build()
+            .render_tiled(&mut plain, &world, 3, PpmFormat::Binary)
+            .unwrap();
+        assert_ne!(plain, tiled);
+    }
+}
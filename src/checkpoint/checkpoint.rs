@@ -0,0 +1,262 @@
+use crate::error::error::RenderError;
+use crate::util::utils;
+use crate::vector::vector::Color;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+/// First bytes of a checkpoint file, followed by the version of the format.
+const MAGIC: &[u8; 4] = b"RTCP";
+/// Version of the checkpoint file format.
+const VERSION: u32 = 1;
+/// Largest amount of pixels of a checkpoint that is read, e.g. 8192 by 8192 pixels, so that a
+/// corrupt header cannot make `read_checkpoint` allocate an arbitrary amount of memory.
+pub const MAX_CHECKPOINT_PIXELS: usize = 1 << 26;
+
+/// The state of a progressive render after `samples_done` passes, from which the render can be
+/// resumed with `Camera::resume_progressive`. The `accumulation` buffer holds the sum of the
+/// samples of every pixel in scanline order, and `rng_positions` the position of the random
+/// number generator of every pixel, which is seeded with `seed`, see `utils::pixel_rng`. Resuming
+/// continues the same sequences of random numbers, so stopping and resuming does not change the
+/// image.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Checkpoint {
+    pub width: i32,
+    pub height: i32,
+    pub seed: u64,
+    pub samples_done: i32,
+    pub accumulation: Vec<Color>,
+    pub rng_positions: Vec<u128>,
+}
+
+impl Checkpoint {
+    /// Create new `Checkpoint` instance of an image of `width` by `height` pixels without any
+    /// samples, whose pixels draw their random numbers from generators seeded with `seed`.
+    pub fn new(width: i32, height: i32, seed: u64) -> Self {
+        let pixels: usize = (width * height) as usize;
+        Self {
+            width,
+            height,
+            seed,
+            samples_done: 0,
+            accumulation: vec![Color::new(0.0, 0.0, 0.0); pixels],
+            rng_positions: vec![0; pixels],
+        }
+    }
+    /// Get the average of the samples of every pixel, e.g. the image rendered so far, scaled in
+    /// the same way as `Camera::render_pixel` with `utils::sample_scale`. Without any samples, the
+    /// image is black.
+    pub fn average(&self) -> Vec<Color> {
+        let scale: f64 = utils::sample_scale(self.samples_done);
+        return self
+            .accumulation
+            .iter()
+            .map(|color| *color * scale)
+            .collect();
+    }
+}
+
+/// Write `checkpoint` to `writer` in the binary checkpoint format: the magic bytes `RTCP`, the
+/// format version, the width, height and amount of samples as `u32`, the seed as `u64`, and then
+/// for every pixel its accumulated color as three `f64` and its generator position as `u128`. All
+/// numbers are little endian, and the colors are stored exactly.
+pub fn write_checkpoint<W: Write>(writer: &mut W, checkpoint: &Checkpoint) -> std::io::Result<()> {
+    writer.write_all(MAGIC)?;
+    writer.write_all(&VERSION.to_le_bytes())?;
+    writer.write_all(&(checkpoint.width as u32).to_le_bytes())?;
+    writer.write_all(&(checkpoint.height as u32).to_le_bytes())?;
+    writer.write_all(&(checkpoint.samples_done as u32).to_le_bytes())?;
+    writer.write_all(&checkpoint.seed.to_le_bytes())?;
+    for (color, position) in checkpoint
+        .accumulation
+        .iter()
+        .zip(&checkpoint.rng_positions)
+    {
+        for component in color.as_array() {
+            writer.write_all(&component.to_le_bytes())?;
+        }
+        writer.write_all(&position.to_le_bytes())?;
+    }
+    return Ok(());
+}
+
+/// Read a checkpoint in the binary format of `write_checkpoint` from `reader`. A file that does
+/// not start with the magic bytes, has another version or ends early gives an error. So does a
+/// header whose dimensions or amount of samples do not fit an `i32`, or whose image has more than
+/// `MAX_CHECKPOINT_PIXELS` pixels, which is checked before the buffers are allocated.
+pub fn read_checkpoint<R: Read>(reader: &mut R) -> std::io::Result<Checkpoint> {
+    let invalid = |message: String| std::io::Error::new(std::io::ErrorKind::InvalidData, message);
+    let mut magic: [u8; 4] = [0; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(invalid("not a checkpoint file".to_string()));
+    }
+    let version: u32 = u32::from_le_bytes(read_bytes(reader)?);
+    if version != VERSION {
+        return Err(invalid(format!(
+            "checkpoint version {version} is not supported"
+        )));
+    }
+    let mut read_i32 = |name: &str| {
+        let value: u32 = u32::from_le_bytes(read_bytes(reader)?);
+        return i32::try_from(value)
+            .map_err(|_| invalid(format!("checkpoint {name} {value} is too large")));
+    };
+    let width: i32 = read_i32("width")?;
+    let height: i32 = read_i32("height")?;
+    let samples_done: i32 = read_i32("amount of samples")?;
+    let pixels: Option<i32> = width.checked_mul(height);
+    if pixels.is_none_or(|pixels| pixels as usize > MAX_CHECKPOINT_PIXELS) {
+        return Err(invalid(format!(
+            "checkpoint of {width}x{height} pixels is larger than {MAX_CHECKPOINT_PIXELS} pixels"
+        )));
+    }
+    let seed: u64 = u64::from_le_bytes(read_bytes(reader)?);
+    let mut checkpoint: Checkpoint = Checkpoint::new(width, height, seed);
+    checkpoint.samples_done = samples_done;
+    for (color, position) in checkpoint
+        .accumulation
+        .iter_mut()
+        .zip(checkpoint.rng_positions.iter_mut())
+    {
+        color.x = f64::from_le_bytes(read_bytes(reader)?);
+        color.y = f64::from_le_bytes(read_bytes(reader)?);
+        color.z = f64::from_le_bytes(read_bytes(reader)?);
+        *position = u128::from_le_bytes(read_bytes(reader)?);
+    }
+    return Ok(checkpoint);
+}
+
+/// Read the next `N` bytes from `reader`.
+fn read_bytes<R: Read, const N: usize>(reader: &mut R) -> std::io::Result<[u8; N]> {
+    let mut bytes: [u8; N] = [0; N];
+    reader.read_exact(&mut bytes)?;
+    return Ok(bytes);
+}
+
+/// Save `checkpoint` to the file at `path`, see `write_checkpoint`. The path is added to the
+/// message of I/O errors.
+pub fn save_checkpoint(path: &Path, checkpoint: &Checkpoint) -> Result<(), RenderError> {
+    let with_path = |err: std::io::Error| {
+        let message: String = format!("could not write checkpoint `{}`: {err}", path.display());
+        RenderError::Io(std::io::Error::new(err.kind(), message))
+    };
+    let mut writer = BufWriter::new(File::create(path).map_err(with_path)?);
+    write_checkpoint(&mut writer, checkpoint).map_err(with_path)?;
+    writer.flush().map_err(with_path)?;
+    return Ok(());
+}
+
+/// Load the checkpoint from the file at `path`, see `read_checkpoint`. The path is added to the
+/// message of I/O errors.
+pub fn load_checkpoint(path: &Path) -> Result<Checkpoint, RenderError> {
+    let with_path = |err: std::io::Error| {
+        let message: String = format!("could not read checkpoint `{}`: {err}", path.display());
+        RenderError::Io(std::io::Error::new(err.kind(), message))
+    };
+    let mut reader = BufReader::new(File::open(path).map_err(with_path)?);
+    return read_checkpoint(&mut reader).map_err(with_path);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera::camera::Camera;
+    use crate::hittables::hittables::Hittables;
+    use crate::hittables::sphere::Sphere;
+    use crate::materials::materials::Lambertian;
+    use crate::vector::vector::Point;
+    use std::sync::Arc;
+
+    /// Encode a checkpoint header of the given dimensions and amount of samples.
+    fn header(width: u32, height: u32, samples_done: u32) -> Vec<u8> {
+        let mut bytes: Vec<u8> = MAGIC.to_vec();
+        for value in [VERSION, width, height, samples_done] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes.extend_from_slice(&7_u64.to_le_bytes());
+        return bytes;
+    }
+
+    #[test]
+    fn written_checkpoint_reads_back_equal() {
+        let mut checkpoint: Checkpoint = Checkpoint::new(3, 2, 103);
+        checkpoint.samples_done = 12;
+        for (k, (color, position)) in checkpoint
+            .accumulation
+            .iter_mut()
+            .zip(checkpoint.rng_positions.iter_mut())
+            .enumerate()
+        {
+            *color = Color::new(k as f64 * 0.1, 1.0 / 3.0, 1e300);
+            *position = (k as u128) << 70;
+        }
+        let mut bytes: Vec<u8> = Vec::new();
+        write_checkpoint(&mut bytes, &checkpoint).unwrap();
+        assert_eq!(bytes.len(), 4 + 4 * 4 + 8 + 6 * (3 * 8 + 16));
+        assert_eq!(read_checkpoint(&mut bytes.as_slice()).unwrap(), checkpoint);
+
+        // A file that ends early is an error.
+        let truncated: &[u8] = &bytes[..bytes.len() - 1];
+        let error: std::io::Error = read_checkpoint(&mut &truncated[..]).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn oversized_header_is_rejected_before_allocating() {
+        for (width, height, samples_done) in [
+            (u32::MAX, 1, 0),
+            (1, 1 << 31, 0),
+            (1, 1, u32::MAX),
+            (1 << 16, 1 << 16, 0),
+            (1 << 14, 1 << 13, 0),
+        ] {
+            let bytes: Vec<u8> = header(width, height, samples_done);
+            let error: std::io::Error = read_checkpoint(&mut bytes.as_slice()).unwrap_err();
+            assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        }
+        // The largest allowed image gets through the header.
+        let bytes: Vec<u8> = header(1 << 13, 1 << 13, 0);
+        let error: std::io::Error = read_checkpoint(&mut bytes.as_slice()).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn fifty_passes_saved_and_resumed_for_fifty_more_equal_one_hundred() {
+        let camera: Camera = Camera::builder()
+            .aspect_ratio(1.0)
+            .width(3)
+            .samples(100)
+            .max_depth(3)
+            .seed(103)
+            .build();
+        let mut world: Hittables = Hittables::init();
+        let gray = world.add_material(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        world.add(Arc::new(Sphere::new(Point::new(0.0, 0.0, -2.0), 0.8, gray)));
+
+        let start: Checkpoint = Checkpoint::new(3, 3, 103);
+        let halfway: Checkpoint = camera
+            .resume_progressive(&world, start, 50, |_, _| {})
+            .unwrap();
+        assert_eq!(halfway.samples_done, 50);
+        let mut bytes: Vec<u8> = Vec::new();
+        write_checkpoint(&mut bytes, &halfway).unwrap();
+        let loaded: Checkpoint = read_checkpoint(&mut bytes.as_slice()).unwrap();
+        let mut passes_seen: Vec<i32> = Vec::new();
+        let done: Checkpoint = camera
+            .resume_progressive(&world, loaded, 50, |_, pass| passes_seen.push(pass))
+            .unwrap();
+        assert_eq!(passes_seen.first(), Some(&50));
+        assert_eq!(done.samples_done, 100);
+        assert_eq!(
+            camera.progressive_image(&world, &done),
+            camera.render_to_buffer(&world)
+        );
+
+        // A checkpoint of another size does not fit the image.
+        let wrong_size: Checkpoint = Checkpoint::new(4, 3, 103);
+        assert!(camera
+            .resume_progressive(&world, wrong_size, 1, |_, _| {})
+            .is_err());
+    }
+}
@@ -0,0 +1,112 @@
+use clap::Parser;
+use log::LevelFilter;
+use std::path::PathBuf;
+
+/// Command line arguments of the raytracer. Every argument has a default, so running without
+/// arguments renders the cover image at 400 pixels wide. Invalid values, such as a zero width or
+/// a negative amount of samples, are rejected by the parser with an error message and a non-zero
+/// exit code.
+#[derive(Parser, Clone, Debug, PartialEq)]
+#[command(
+    version,
+    about = "Render a scene with a path tracing raytracer.",
+    allow_negative_numbers = true
+)]
+pub struct Args {
+    /// Width of the rendered image in pixels, the height follows from the aspect ratio.
+    #[arg(long, default_value_t = 400, value_parser = clap::value_parser!(i32).range(1..))]
+    pub width: i32,
+    /// Amount of rays sampled per pixel.
+    #[arg(long, default_value_t = 100, value_parser = clap::value_parser!(i32).range(1..))]
+    pub samples: i32,
+    /// Maximum amount of bounces of a ray.
+    #[arg(long, default_value_t = 50, value_parser = clap::value_parser!(i32).range(1..))]
+    pub max_depth: i32,
+    /// Path of the output PPM image, or `-` to write the image to stdout. The depth and albedo
+    /// passes are then written to the current directory.
+    #[arg(long, default_value = "result/image.ppm")]
+    pub output: PathBuf,
+    /// JSON or TOML scene file to render instead of the cover image. The camera settings,
+    /// including the width, samples and maximum depth, are then taken from the scene file.
+    #[cfg(feature = "serde")]
+    #[arg(long)]
+    pub scene: Option<PathBuf>,
+    /// Also render a depth pass into `depth.ppm`, next to the output image. Distances from zero
+    /// up to the given maximum distance are encoded from black to white.
+    #[arg(long, value_name = "MAX_DISTANCE", value_parser = parse_positive)]
+    pub depth: Option<f64>,
+    /// Also render an albedo pass into `albedo.ppm`, next to the output image.
+    #[arg(long)]
+    pub albedo: bool,
+    /// Minimum level of the logged records: off, error, warn, info, debug or trace. The `RUST_LOG`
+    /// environment variable takes precedence.
+    #[arg(long, default_value_t = LevelFilter::Info)]
+    pub log_level: LevelFilter,
+    /// Write the log to stderr instead of the log file.
+    #[arg(long)]
+    pub log_stderr: bool,
+    /// Show the render in a window while it progresses. The image is saved once the window is
+    /// closed.
+    #[cfg(feature = "preview")]
+    #[arg(long)]
+    pub preview: bool,
+    /// Seed for the random number generators, which makes the scene reproducible.
+    #[arg(long)]
+    pub seed: Option<u64>,
+    /// Amount of threads that render the image, by default or with zero all cores are used.
+    #[arg(long)]
+    pub threads: Option<usize>,
+}
+
+/// Parse a strictly positive, finite floating point number.
+fn parse_positive(value: &str) -> Result<f64, String> {
+    let number: f64 = value
+        .parse()
+        .map_err(|err| format!("`{value}` is not a number: {err}"))?;
+    if !number.is_finite() || number <= 0.0 {
+        return Err(format!("{value} is not a positive number"));
+    }
+    return Ok(number);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_and_representative_arguments() {
+        let args: Args = Args::try_parse_from(["raytracing"]).unwrap();
+        assert_eq!((args.width, args.samples, args.max_depth), (400, 100, 50));
+        assert_eq!(args.output, PathBuf::from("result/image.ppm"));
+        assert_eq!(args.seed, None);
+
+        let args: Args = Args::try_parse_from([
+            "raytracing",
+            "--width",
+            "64",
+            "--samples",
+            "8",
+            "--max-depth",
+            "4",
+            "--output",
+            "out/test.ppm",
+            "--seed",
+            "7",
+            "--depth",
+            "12.5",
+        ])
+        .unwrap();
+        assert_eq!((args.width, args.samples, args.max_depth), (64, 8, 4));
+        assert_eq!(args.output, PathBuf::from("out/test.ppm"));
+        assert_eq!(args.seed, Some(7));
+        assert_eq!(args.depth, Some(12.5));
+    }
+
+    #[test]
+    fn invalid_values_are_rejected() {
+        assert!(Args::try_parse_from(["raytracing", "--width", "0"]).is_err());
+        assert!(Args::try_parse_from(["raytracing", "--samples", "-4"]).is_err());
+        assert!(Args::try_parse_from(["raytracing", "--depth", "-1"]).is_err());
+        assert!(Args::try_parse_from(["raytracing", "--seed", "abc"]).is_err());
+    }
+}
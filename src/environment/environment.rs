@@ -0,0 +1,212 @@
+use crate::hittables::sphere::get_sphere_uv;
+use crate::textures::image_texture::ImageTexture;
+use crate::textures::textures::Texture;
+use crate::util::utils;
+use crate::vector::vector::{Color, Vec3};
+
+/// An `Environment` determines the color of the background, e.g. the light arriving from a
+/// `direction` when a ray does not hit anything in the world. Environments are owned by the
+/// `Camera` which is shared between the rendering threads, hence they must be `Send + Sync`.
+pub trait Environment: Send + Sync {
+    fn sample(&self, direction: Vec3) -> Color;
+    /// The `Sun` of the environment, if it has one. Its light is added at every diffuse hit by
+    /// casting a shadow ray towards it. By default there is no sun.
+    fn sun(&self) -> Option<Sun> {
+        return None;
+    }
+    /// The color of the background without the disk of the sun, which is what rays scattered by
+    /// diffuse surfaces see, since the light of the sun was already added at the diffuse hit. By
+    /// default this is the same as `sample`.
+    fn sample_without_sun(&self, direction: Vec3) -> Color {
+        return self.sample(direction);
+    }
+}
+
+/// A `Sun` is a distant light source that is seen as a small disk in the `direction` towards it,
+/// with an `angular_radius` in degrees. The `intensity` is the irradiance it causes on a surface
+/// that faces it, independent of the position of the surface, so it gives hard parallel shadows.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sun {
+    pub direction: Vec3,
+    pub intensity: Color,
+    pub angular_radius: f64,
+}
+
+impl Sun {
+    /// Create new instance of `Sun`, the `direction` is normalized.
+    pub fn new(direction: Vec3, intensity: Color, angular_radius: f64) -> Self {
+        Self {
+            direction: direction.unit_vector(),
+            intensity,
+            angular_radius,
+        }
+    }
+    /// Get the solid angle of the disk of the sun, which is $2 \pi (1 - \cos r)$ for an angular
+    /// radius $r$.
+    pub fn solid_angle(&self) -> f64 {
+        let cos_radius: f64 = utils::degrees_to_radians(self.angular_radius).cos();
+        return 2.0 * std::f64::consts::PI * (1.0 - cos_radius);
+    }
+    /// Get the radiance of the disk of the sun, which spreads the `intensity` over its solid
+    /// angle. The smaller the sun, the brighter its disk.
+    pub fn radiance(&self) -> Color {
+        return self.intensity / self.solid_angle();
+    }
+    /// Check if the given `direction` points at the disk of the sun.
+    pub fn covers(&self, direction: &Vec3) -> bool {
+        let cos_radius: f64 = utils::degrees_to_radians(self.angular_radius).cos();
+        return direction.unit_vector().dot(&self.direction) >= cos_radius;
+    }
+}
+
+/// A `GradientSky` blends linearly from the `bottom` color straight down to the `top` color
+/// straight up, depending on the vertical component of the direction. Setting both colors to
+/// black gives a dark background for scenes that are only lit by their light sources.
+#[derive(Clone, Copy, Debug)]
+pub struct GradientSky {
+    pub bottom: Color,
+    pub top: Color,
+}
+
+impl GradientSky {
+    /// Create new instance of `GradientSky`.
+    pub fn new(bottom: Color, top: Color) -> Self {
+        Self { bottom, top }
+    }
+}
+
+impl Default for GradientSky {
+    /// By default, the sky blends from white at the bottom to light blue at the top.
+    fn default() -> Self {
+        Self::new(Color::new(1.0, 1.0, 1.0), Color::new(0.5, 0.7, 1.0))
+    }
+}
+
+impl Environment for GradientSky {
+    /// Map the y-component of the unit direction from [-1,1] to [0,1] and use it to blend the
+    /// `bottom` and `top` colors.
+    fn sample(&self, direction: Vec3) -> Color {
+        let unit_direction = direction.unit_vector();
+        let a: f64 = (unit_direction.y + 1.0) * 0.5;
+        return self.bottom.lerp(&self.top, a);
+    }
+}
+
+/// A `SunSky` is a `GradientSky` with a `Sun` in it, for outdoor scenes. The sun is visible as a
+/// bright disk on top of the sky, and it lights diffuse surfaces directly, which gives hard
+/// shadows.
+#[derive(Clone, Copy, Debug)]
+pub struct SunSky {
+    pub sky: GradientSky,
+    pub sun: Sun,
+}
+
+impl SunSky {
+    /// Create new instance of `SunSky`.
+    pub fn new(sky: GradientSky, sun: Sun) -> Self {
+        Self { sky, sun }
+    }
+}
+
+impl Environment for SunSky {
+    /// Sample the sky and add the radiance of the sun if the direction points at its disk.
+    fn sample(&self, direction: Vec3) -> Color {
+        if self.sun.covers(&direction) {
+            return self.sky.sample(direction) + self.sun.radiance();
+        }
+        return self.sky.sample(direction);
+    }
+    /// Get the `Sun` of the sky.
+    fn sun(&self) -> Option<Sun> {
+        return Some(self.sun);
+    }
+    /// Sample only the sky.
+    fn sample_without_sun(&self, direction: Vec3) -> Color {
+        return self.sky.sample(direction);
+    }
+}
+
+/// An `ImageEnvironment` surrounds the world with an equirectangular image, typically a high
+/// dynamic range (HDR) image. The image is mapped onto an infinitely large sphere, in the same
+/// way as an `ImageTexture` is mapped onto a `Sphere`.
+#[derive(Clone, Debug)]
+pub struct ImageEnvironment {
+    pub image: ImageTexture,
+}
+
+impl ImageEnvironment {
+    /// Create new instance of `ImageEnvironment` by loading the image at `path`.
+    pub fn new(path: &str) -> Self {
+        Self {
+            image: ImageTexture::new(path),
+        }
+    }
+}
+
+impl Environment for ImageEnvironment {
+    /// Get the spherical coordinates of the unit direction and sample the image there.
+    fn sample(&self, direction: Vec3) -> Color {
+        let unit_direction: Vec3 = direction.unit_vector();
+        let (u, v) = get_sphere_uv(&unit_direction);
+        return self.image.value(u, v, &unit_direction);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera::camera::Camera;
+    use crate::hittables::hittables::Hittables;
+    use crate::raycaster::ray::Ray;
+    use crate::vector::vector::Point;
+
+    #[test]
+    fn default_gradient_sky_keeps_its_top_and_bottom_colors() {
+        let sky: GradientSky = GradientSky::default();
+        assert_eq!(
+            sky.sample(Vec3::new(0.0, 1.0, 0.0)),
+            Color::new(0.5, 0.7, 1.0)
+        );
+        assert_eq!(
+            sky.sample(Vec3::new(0.0, -3.0, 0.0)),
+            Color::new(1.0, 1.0, 1.0)
+        );
+        // Halfway up, the two colors are blended equally.
+        assert_eq!(
+            sky.sample(Vec3::new(1.0, 0.0, 0.0)),
+            Color::new(0.75, 0.85, 1.0)
+        );
+    }
+
+    #[test]
+    fn downward_ray_returns_the_sky_bottom() {
+        let bottom: Color = Color::new(0.2, 0.1, 0.0);
+        let top: Color = Color::new(0.0, 0.0, 0.0);
+        let camera: Camera = Camera::builder()
+            .environment(Box::new(GradientSky::new(bottom, top)))
+            .build();
+        let down: Ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        assert_eq!(down.ray_color(&Hittables::init(), 10, &camera), bottom);
+        let up: Ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), 0.0);
+        assert_eq!(up.ray_color(&Hittables::init(), 10, &camera), top);
+    }
+
+    #[test]
+    fn ray_at_the_sun_is_brighter_than_the_sky_beside_it() {
+        let sun_direction: Vec3 = Vec3::new(1.0, 1.0, 0.0);
+        let sun: Sun = Sun::new(sun_direction, Color::new(3.0, 3.0, 3.0), 0.5);
+        let camera: Camera = Camera::builder()
+            .environment(Box::new(SunSky::new(GradientSky::default(), sun)))
+            .build();
+        let origin: Point = Point::new(0.0, 0.0, 0.0);
+        let at_sun: Color =
+            Ray::new(origin, sun_direction, 0.0).ray_color(&Hittables::init(), 10, &camera);
+        // Two degrees next to the sun, which has a radius of half a degree.
+        let beside: Vec3 = sun_direction.unit_vector() + Vec3::new(0.0, 0.0, 0.035);
+        let beside_sun: Color =
+            Ray::new(origin, beside, 0.0).ray_color(&Hittables::init(), 10, &camera);
+        assert!(!sun.covers(&beside));
+        assert_eq!(beside_sun, GradientSky::default().sample(beside));
+        assert!(at_sun.x > beside_sun.x && at_sun.y > beside_sun.y && at_sun.z > beside_sun.z);
+    }
+}
@@ -0,0 +1,71 @@
+use crate::mesh::mesh::MeshParseError;
+#[cfg(feature = "serde")]
+use crate::scene::scene::SceneParseError;
+use std::fmt;
+
+/// Error of the raytracer. Writing an image or reading a scene or mesh file can fail with an I/O
+/// error, and the contents of a scene or mesh file can fail to parse. Scene files are only read
+/// with the `serde` feature. With the `preview` feature, the preview window can fail to open or
+/// update. The functions of the crate return these errors
+/// instead of exiting, so that only the binary decides how to handle them.
+#[derive(Debug)]
+pub enum RenderError {
+    Io(std::io::Error),
+    #[cfg(feature = "serde")]
+    SceneParse(SceneParseError),
+    MeshParse(MeshParseError),
+    #[cfg(feature = "preview")]
+    Preview(minifb::Error),
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenderError::Io(err) => write!(f, "I/O error: {err}"),
+            #[cfg(feature = "serde")]
+            RenderError::SceneParse(err) => write!(f, "{err}"),
+            RenderError::MeshParse(err) => write!(f, "{err}"),
+            #[cfg(feature = "preview")]
+            RenderError::Preview(err) => write!(f, "preview window error: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for RenderError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RenderError::Io(err) => Some(err),
+            #[cfg(feature = "serde")]
+            RenderError::SceneParse(err) => Some(err),
+            RenderError::MeshParse(err) => Some(err),
+            #[cfg(feature = "preview")]
+            RenderError::Preview(err) => Some(err),
+        }
+    }
+}
+
+impl From<std::io::Error> for RenderError {
+    fn from(err: std::io::Error) -> Self {
+        RenderError::Io(err)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<SceneParseError> for RenderError {
+    fn from(err: SceneParseError) -> Self {
+        RenderError::SceneParse(err)
+    }
+}
+
+impl From<MeshParseError> for RenderError {
+    fn from(err: MeshParseError) -> Self {
+        RenderError::MeshParse(err)
+    }
+}
+
+#[cfg(feature = "preview")]
+impl From<minifb::Error> for RenderError {
+    fn from(err: minifb::Error) -> Self {
+        RenderError::Preview(err)
+    }
+}
@@ -1,3 +1,16 @@
+pub mod aabb;
+pub mod boxprim;
+pub mod bvh;
+pub mod constant_medium;
+pub mod cylinder;
+pub mod disk;
 pub mod hittables;
+pub mod moving_sphere;
+pub mod plane;
+pub mod quad;
 pub mod record;
+pub mod rotate;
 pub mod sphere;
+pub mod transform;
+pub mod translate;
+pub mod triangle;
@@ -0,0 +1,139 @@
+use crate::raycaster::ray::Ray;
+use crate::util::utils::{Interval, EMPTY, UNIVERSE};
+use crate::vector::vector::{Point, Vec3};
+
+/// An axis-aligned bounding box (AABB) is defined by an `Interval` along each of the three
+/// coordinate axes. It is used to quickly reject rays that cannot hit the objects inside it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub x: Interval,
+    pub y: Interval,
+    pub z: Interval,
+}
+
+impl Aabb {
+    /// Create new `Aabb` instance from the intervals along each axis.
+    pub fn new(x: Interval, y: Interval, z: Interval) -> Self {
+        Self { x, y, z }
+    }
+    /// Create an empty `Aabb`, e.g. a box that contains no points and is hit by no ray.
+    pub fn empty() -> Self {
+        Self::new(EMPTY, EMPTY, EMPTY)
+    }
+    /// Create an `Aabb` that covers all of space.
+    pub fn universe() -> Self {
+        Self::new(UNIVERSE, UNIVERSE, UNIVERSE)
+    }
+    /// Create the smallest `Aabb` that contains both points `a` and `b`, which are treated as
+    /// opposite corners of the box. The box is padded so that it is never flat.
+    pub fn from_points(a: Point, b: Point) -> Self {
+        return Self {
+            x: Interval::new(a.x.min(b.x), a.x.max(b.x)),
+            y: Interval::new(a.y.min(b.y), a.y.max(b.y)),
+            z: Interval::new(a.z.min(b.z), a.z.max(b.z)),
+        }
+        .pad_to_minimums();
+    }
+    /// Expand every axis of the box that is thinner than a small minimum size. A flat box, such
+    /// as the box around an axis-aligned `Quad`, would otherwise never be hit by the slab test.
+    pub fn pad_to_minimums(&self) -> Self {
+        let delta: f64 = 0.0001;
+        let pad = |interval: Interval| {
+            if interval.size() < delta {
+                interval.expand(delta)
+            } else {
+                interval
+            }
+        };
+        return Self::new(pad(self.x), pad(self.y), pad(self.z));
+    }
+    /// Create the smallest `Aabb` that contains both boxes `a` and `b`.
+    pub fn surrounding(a: &Self, b: &Self) -> Self {
+        Self {
+            x: Interval::enclose(a.x, b.x),
+            y: Interval::enclose(a.y, b.y),
+            z: Interval::enclose(a.z, b.z),
+        }
+    }
+    /// Get the box displaced by `offset`.
+    pub fn translate(&self, offset: Vec3) -> Self {
+        Self {
+            x: Interval::new(self.x.min + offset.x, self.x.max + offset.x),
+            y: Interval::new(self.y.min + offset.y, self.y.max + offset.y),
+            z: Interval::new(self.z.min + offset.z, self.z.max + offset.z),
+        }
+    }
+    /// Get the `Interval` along the given axis, where 0, 1 and 2 correspond to x, y and z.
+    pub fn axis_interval(&self, axis: usize) -> Interval {
+        match axis {
+            0 => self.x,
+            1 => self.y,
+            _ => self.z,
+        }
+    }
+    /// Get the axis along which the box is the largest, where 0, 1 and 2 correspond to
+    /// x, y and z.
+    pub fn longest_axis(&self) -> usize {
+        if self.x.size() > self.y.size() {
+            if self.x.size() > self.z.size() {
+                return 0;
+            } else {
+                return 2;
+            }
+        } else if self.y.size() > self.z.size() {
+            return 1;
+        } else {
+            return 2;
+        }
+    }
+    /// Check if the `ray` hits the box for a ray parameter within `ray_parameter_interval`. This
+    /// is the slab method: along each axis the ray lies between the two bounding planes of the
+    /// box for an interval of ray parameters. The ray hits the box if these three intervals
+    /// overlap with each other and with `ray_parameter_interval`. An empty box is never hit.
+    pub fn hit(&self, ray: &Ray, ray_parameter_interval: Interval) -> bool {
+        let mut overlap: Interval = ray_parameter_interval;
+        for axis in 0..3 {
+            // Ray parameters where the ray crosses the two bounding planes of the slab.
+            let slab: Interval = self.axis_interval(axis);
+            // An empty slab contains no points, whatever the direction of the ray.
+            if slab.max < slab.min {
+                return false;
+            }
+            let inverse_direction: f64 = 1.0 / ray.direction[axis];
+            let t0: f64 = (slab.min - ray.origin[axis]) * inverse_direction;
+            let t1: f64 = (slab.max - ray.origin[axis]) * inverse_direction;
+            // Shrink the overlap to the part that lies inside the slab.
+            overlap.min = overlap.min.max(t0.min(t1));
+            overlap.max = overlap.max.min(t0.max(t1));
+            if overlap.max <= overlap.min {
+                return false;
+            }
+        }
+        return true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hittables::hittables::{Hittable, Hittables};
+    use crate::hittables::sphere::Sphere;
+    use crate::materials::arena::MaterialId;
+
+    #[test]
+    fn sphere_box_spans_center_plus_and_minus_radius() {
+        let sphere: Sphere = Sphere::new(Point::new(1.0, -2.0, 3.0), 0.5, MaterialId(0));
+        let bbox: Aabb = sphere.bounding_box();
+        assert_eq!(bbox.x, Interval::new(0.5, 1.5));
+        assert_eq!(bbox.y, Interval::new(-2.5, -1.5));
+        assert_eq!(bbox.z, Interval::new(2.5, 3.5));
+    }
+
+    #[test]
+    fn empty_hittables_have_an_empty_box() {
+        let bbox: Aabb = Hittables::init().bounding_box();
+        assert_eq!(bbox, Aabb::empty());
+        let ray: Ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0), 0.0);
+        assert!(!bbox.hit(&ray, UNIVERSE));
+    }
+}
@@ -0,0 +1,123 @@
+use super::aabb::Aabb;
+use super::hittables::{Hittable, Hittables};
+use super::quad::Quad;
+use super::record::HitRecord;
+use crate::materials::arena::MaterialId;
+use crate::raycaster::ray::Ray;
+use crate::util::utils::Interval;
+use crate::vector::vector::{Point, Vec3};
+use std::sync::Arc;
+
+/// A `BoxPrim` is an axis-aligned box, e.g. a rectangular cuboid whose faces are orthogonal to
+/// the coordinate axes. The box is stored as the six `Quad`s that make up its faces.
+pub struct BoxPrim {
+    pub sides: Hittables,
+}
+
+impl BoxPrim {
+    /// Create new `BoxPrim` instance from two opposite corners `a` and `b` of the box. The
+    /// corners do not need to be ordered, the minimum and maximum coordinates are determined per
+    /// axis. The faces are oriented such that their normals point out of the box.
+    pub fn new(a: Point, b: Point, material: MaterialId) -> Self {
+        // Get the corners with the minimum and maximum coordinates.
+        let min: Point = Point::new(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z));
+        let max: Point = Point::new(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z));
+        // Edge vectors along each of the axes.
+        let dx: Vec3 = Vec3::new(max.x - min.x, 0.0, 0.0);
+        let dy: Vec3 = Vec3::new(0.0, max.y - min.y, 0.0);
+        let dz: Vec3 = Vec3::new(0.0, 0.0, max.z - min.z);
+
+        // The edge vectors of each face are ordered such that `u x v` points outward.
+        let mut sides: Hittables = Hittables::init();
+        // Front face, normal along +z
+        sides.add(Arc::new(Quad::new(
+            Point::new(min.x, min.y, max.z),
+            dx,
+            dy,
+            material,
+        )));
+        // Right face, normal along +x
+        sides.add(Arc::new(Quad::new(
+            Point::new(max.x, min.y, max.z),
+            -dz,
+            dy,
+            material,
+        )));
+        // Back face, normal along -z
+        sides.add(Arc::new(Quad::new(
+            Point::new(max.x, min.y, min.z),
+            -dx,
+            dy,
+            material,
+        )));
+        // Left face, normal along -x
+        sides.add(Arc::new(Quad::new(
+            Point::new(min.x, min.y, min.z),
+            dz,
+            dy,
+            material,
+        )));
+        // Top face, normal along +y
+        sides.add(Arc::new(Quad::new(
+            Point::new(min.x, max.y, max.z),
+            dx,
+            -dz,
+            material,
+        )));
+        // Bottom face, normal along -y
+        sides.add(Arc::new(Quad::new(
+            Point::new(min.x, min.y, min.z),
+            dx,
+            dz,
+            material,
+        )));
+
+        Self { sides }
+    }
+}
+
+impl Hittable for BoxPrim {
+    /// A ray hits the box if it hits any of its faces, the closest face is returned.
+    fn ray_hit(&self, ray: &Ray, ray_parameter_interval: Interval) -> HitRecord {
+        return self.sides.ray_hit(ray, ray_parameter_interval);
+    }
+    /// The bounding box of the box is the bounding box of its faces.
+    fn bounding_box(&self) -> Aabb {
+        return self.sides.bounding_box();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::utils::POSITIVE_INFINITY;
+
+    #[test]
+    fn normal_of_entered_face_points_back_to_the_ray_origin() {
+        // The corners are given as "max" first, so the faces must be reoriented.
+        let boxprim: BoxPrim = BoxPrim::new(
+            Point::new(1.0, 2.0, 3.0),
+            Point::new(-1.0, -2.0, -3.0),
+            MaterialId(0),
+        );
+        let directions: [Vec3; 6] = [
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(-1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, -1.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(0.0, 0.0, -1.0),
+        ];
+        for direction in directions {
+            let origin: Point = direction * 10.0;
+            let ray: Ray = Ray::new(origin, -direction, 0.0);
+            let hit_record: HitRecord =
+                boxprim.ray_hit(&ray, Interval::new(0.001, POSITIVE_INFINITY));
+            assert!(hit_record.hit);
+            // The ray enters through the outside of the face, and the normal is the outward one.
+            assert!(hit_record.front_face);
+            assert_eq!(hit_record.normal, direction);
+            assert!(hit_record.normal.dot(&(origin - hit_record.point)) > 0.0);
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use super::aabb::Aabb;
+use super::hittables::{Hittable, Hittables};
+use super::record::HitRecord;
+use crate::raycaster::ray::Ray;
+use crate::util::utils::Interval;
+use std::sync::Arc;
+
+/// A bounding volume hierarchy (BVH) is a binary tree of `Hittable` objects, where every node
+/// stores the bounding box of all the objects below it. A ray that misses the bounding box of a
+/// node cannot hit any of its objects, so that entire branch of the tree can be skipped.
+pub struct BvhNode {
+    pub left: Arc<dyn Hittable>,
+    pub right: Arc<dyn Hittable>,
+    pub bbox: Aabb,
+}
+
+impl BvhNode {
+    /// Create new `BvhNode` instance by recursively partitioning the `objects`. At every level,
+    /// the objects are sorted along the longest axis of their combined bounding box, and split
+    /// into two halves.
+    pub fn new(mut objects: Vec<Arc<dyn Hittable>>) -> Self {
+        // Compute the bounding box of all the objects.
+        let bbox: Aabb = objects.iter().fold(Aabb::empty(), |bbox, object| {
+            Aabb::surrounding(&bbox, &object.bounding_box())
+        });
+
+        let (left, right): (Arc<dyn Hittable>, Arc<dyn Hittable>) = match objects.len() {
+            // An empty `Hittables` is never hit, so it can be used to fill up the node.
+            0 => (Arc::new(Hittables::init()), Arc::new(Hittables::init())),
+            1 => (objects.remove(0), Arc::new(Hittables::init())),
+            2 => {
+                let right = objects.remove(1);
+                (objects.remove(0), right)
+            }
+            _ => {
+                // Sort the objects along the longest axis and split them into two halves.
+                let axis: usize = bbox.longest_axis();
+                objects.sort_by(|a, b| {
+                    let a_min: f64 = a.bounding_box().axis_interval(axis).min;
+                    let b_min: f64 = b.bounding_box().axis_interval(axis).min;
+                    a_min.total_cmp(&b_min)
+                });
+                let second_half = objects.split_off(objects.len() / 2);
+                (
+                    Arc::new(BvhNode::new(objects)),
+                    Arc::new(BvhNode::new(second_half)),
+                )
+            }
+        };
+
+        Self { left, right, bbox }
+    }
+    /// Create new `BvhNode` instance from all the objects in `hittables`.
+    pub fn from_hittables(hittables: Hittables) -> Self {
+        Self::new(hittables.into_hittable_list())
+    }
+}
+
+impl Hittable for BvhNode {
+    /// If the ray hits the bounding box of the node, both children are checked for a hit. The
+    /// right child only needs to be checked up to the hit on the left child, if there was one,
+    /// so the closest hit is returned.
+    fn ray_hit(&self, ray: &Ray, ray_parameter_interval: Interval) -> HitRecord {
+        // Ray missed the bounding box, so it cannot hit any of the objects.
+        if !self.bbox.hit(ray, ray_parameter_interval) {
+            return HitRecord::default();
+        }
+
+        let left_hit_record: HitRecord = self.left.ray_hit(ray, ray_parameter_interval);
+        let right_interval: Interval = {
+            if left_hit_record.hit {
+                Interval::new(ray_parameter_interval.min, left_hit_record.ray_parameter)
+            } else {
+                ray_parameter_interval
+            }
+        };
+        let right_hit_record: HitRecord = self.right.ray_hit(ray, right_interval);
+
+        if right_hit_record.hit {
+            return right_hit_record;
+        } else {
+            return left_hit_record;
+        }
+    }
+    /// The bounding box of all the objects in the node.
+    fn bounding_box(&self) -> Aabb {
+        return self.bbox;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hittables::sphere::Sphere;
+    use crate::materials::arena::MaterialId;
+    use crate::util::utils::{get_random_in_range, seed_rng, POSITIVE_INFINITY};
+    use crate::vector::vector::{Point, Vec3};
+
+    #[test]
+    fn bvh_and_linear_list_give_the_same_closest_hit() {
+        seed_rng(13);
+        let mut spheres: Vec<Arc<dyn Hittable>> = Vec::new();
+        for index in 0..40 {
+            let center: Point = Point::new(
+                get_random_in_range(-5.0, 5.0),
+                get_random_in_range(-5.0, 5.0),
+                get_random_in_range(-5.0, 5.0),
+            );
+            let radius: f64 = get_random_in_range(0.2, 1.0);
+            spheres.push(Arc::new(Sphere::new(center, radius, MaterialId(index))));
+        }
+        let list: Hittables = Hittables::new(spheres.clone());
+        let bvh: BvhNode = BvhNode::new(spheres);
+
+        // Fixed rays along the axes through the scene, followed by random rays aimed
+        // into the scene.
+        let mut rays: Vec<Ray> = vec![
+            Ray::new(Point::new(-10.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.0),
+            Ray::new(Point::new(0.0, -10.0, 0.0), Vec3::new(0.0, 1.0, 0.0), 0.0),
+            Ray::new(Point::new(0.0, 0.0, 10.0), Vec3::new(0.0, 0.0, -1.0), 0.0),
+        ];
+        for _ in 0..500 {
+            let origin: Point = Point::new(
+                get_random_in_range(-8.0, 8.0),
+                get_random_in_range(-8.0, 8.0),
+                get_random_in_range(-8.0, 8.0),
+            );
+            let target: Point = Point::new(
+                get_random_in_range(-5.0, 5.0),
+                get_random_in_range(-5.0, 5.0),
+                get_random_in_range(-5.0, 5.0),
+            );
+            rays.push(Ray::new(origin, target - origin, 0.0));
+        }
+
+        let interval: Interval = Interval::new(0.001, POSITIVE_INFINITY);
+        let mut hits: usize = 0;
+        for ray in rays.iter() {
+            let expected: HitRecord = list.ray_hit(ray, interval);
+            assert_eq!(bvh.ray_hit(ray, interval), expected);
+            hits += expected.hit as usize;
+        }
+        // Make sure the comparison is not only between misses.
+        assert!(hits > 50);
+    }
+}
@@ -0,0 +1,117 @@
+use super::aabb::Aabb;
+use super::hittables::Hittable;
+use super::record::HitRecord;
+use crate::materials::arena::MaterialId;
+use crate::raycaster::ray::Ray;
+use crate::util::utils::{get_random, Interval, POSITIVE_INFINITY, UNIVERSE};
+use crate::vector::vector::{Point, Vec3};
+use std::sync::Arc;
+
+/// A `ConstantMedium` is a volume of constant density inside a `boundary`, such as fog or smoke.
+/// A ray passing through the volume can scatter at any point inside, with a probability that
+/// grows with the `density` and the distance travelled. When it does, the `phase_function`
+/// material decides the scattered direction. The boundary is assumed to be convex, e.g. a ray
+/// enters and leaves the volume at most once.
+pub struct ConstantMedium {
+    pub boundary: Arc<dyn Hittable>,
+    pub negative_inverse_density: f64,
+    pub phase_function: MaterialId,
+}
+
+impl ConstantMedium {
+    /// Create new `ConstantMedium` instance.
+    pub fn new(boundary: Arc<dyn Hittable>, density: f64, phase_function: MaterialId) -> Self {
+        Self {
+            boundary,
+            negative_inverse_density: -1.0 / density,
+            phase_function,
+        }
+    }
+}
+
+impl Hittable for ConstantMedium {
+    /// Find where the ray enters and leaves the boundary and sample the distance the ray travels
+    /// through the medium before it scatters, which is $-\frac{1}{\text{density}}\ln(\xi)$ for a
+    /// uniform random number $\xi$. If this distance is larger than the length of the segment
+    /// inside the boundary, the ray passes through the medium. Otherwise the ray hits the medium
+    /// at the sampled distance, with an arbitrary normal, since the medium has no surface.
+    fn ray_hit(&self, ray: &Ray, ray_parameter_interval: Interval) -> HitRecord {
+        // Find both crossings of the boundary along the whole line of the ray, so that rays that
+        // start inside the medium are handled as well.
+        let entry: HitRecord = self.boundary.ray_hit(ray, UNIVERSE);
+        if !entry.hit {
+            return HitRecord::default();
+        }
+        let exit: HitRecord = self.boundary.ray_hit(
+            ray,
+            Interval::new(entry.ray_parameter + 0.0001, POSITIVE_INFINITY),
+        );
+        if !exit.hit {
+            return HitRecord::default();
+        }
+
+        // Restrict the segment to the allowed interval and to the front of the ray.
+        let entry_parameter: f64 = entry.ray_parameter.max(ray_parameter_interval.min).max(0.0);
+        let exit_parameter: f64 = exit.ray_parameter.min(ray_parameter_interval.max);
+        if entry_parameter >= exit_parameter {
+            return HitRecord::default();
+        }
+
+        // Sample the distance at which the ray scatters.
+        let ray_length: f64 = ray.direction.length();
+        let distance_inside_boundary: f64 = (exit_parameter - entry_parameter) * ray_length;
+        let hit_distance: f64 = self.negative_inverse_density * get_random().ln();
+        if hit_distance > distance_inside_boundary {
+            return HitRecord::default();
+        }
+
+        let ray_parameter: f64 = entry_parameter + hit_distance / ray_length;
+        let point: Point = ray.at(ray_parameter);
+        return HitRecord::new(
+            true,
+            point,
+            Vec3::new(1.0, 0.0, 0.0),
+            true,
+            ray_parameter,
+            0.0,
+            0.0,
+            self.phase_function,
+        );
+    }
+    /// The bounding box of the boundary.
+    fn bounding_box(&self) -> Aabb {
+        return self.boundary.bounding_box();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hittables::sphere::Sphere;
+    use crate::util::utils::seed_rng;
+
+    #[test]
+    fn dense_medium_almost_always_scatters_a_ray_passing_through() {
+        seed_rng(32);
+        let boundary: Arc<dyn Hittable> =
+            Arc::new(Sphere::new(Point::new(0.0, 0.0, -5.0), 1.0, MaterialId(0)));
+        let interval: Interval = Interval::new(0.001, POSITIVE_INFINITY);
+        let ray: Ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+
+        let dense: ConstantMedium = ConstantMedium::new(boundary.clone(), 100.0, MaterialId(1));
+        let scattered: usize = (0..1000)
+            .filter(|_| dense.ray_hit(&ray, interval).hit)
+            .count();
+        assert!(scattered >= 995);
+        // The scatter points lie inside the boundary.
+        let hit_record: HitRecord = dense.ray_hit(&ray, interval);
+        assert!((4.0..=6.0).contains(&hit_record.ray_parameter));
+        assert_eq!(hit_record.material, Some(MaterialId(1)));
+
+        let thin: ConstantMedium = ConstantMedium::new(boundary, 0.01, MaterialId(1));
+        let scattered: usize = (0..1000)
+            .filter(|_| thin.ray_hit(&ray, interval).hit)
+            .count();
+        assert!(scattered < 100);
+    }
+}
@@ -0,0 +1,181 @@
+use super::aabb::Aabb;
+use super::hittables::Hittable;
+use super::record::{set_face_normal, HitRecord};
+use crate::materials::arena::MaterialId;
+use crate::raycaster::ray::Ray;
+use crate::util::utils::Interval;
+use crate::vector::vector::{Point, Vec3};
+
+/// A finite `Cylinder` is defined by the center of its base disk, the unit direction of its axis,
+/// its radius and its height along the axis. If `capped` is set, the cylinder is closed off by
+/// a disk at the bottom and at the top, otherwise it is an open tube.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cylinder {
+    pub base: Point,
+    pub axis: Vec3,
+    pub radius: f64,
+    pub height: f64,
+    pub capped: bool,
+    pub material: MaterialId,
+}
+
+impl Cylinder {
+    /// Create new `Cylinder` instance. The `axis` is normalised, so it does not need to be of
+    /// unit length.
+    pub fn new(
+        base: Point,
+        axis: Vec3,
+        radius: f64,
+        height: f64,
+        capped: bool,
+        material: MaterialId,
+    ) -> Self {
+        Self {
+            base,
+            axis: axis.unit_vector(),
+            radius,
+            height,
+            capped,
+            material,
+        }
+    }
+}
+
+impl Hittable for Cylinder {
+    /// The curved side is hit where the ray, projected onto the plane orthogonal to the axis,
+    /// lies at a distance `radius` from the axis. Writing $\vec{b}_\perp$ and $\vec{oc}_\perp$
+    /// for the projections of the ray direction and of the vector from the base to the ray
+    /// origin, this gives the quadratic
+    /// $$ |\vec{b}_\perp|^2 t^2 + 2 (\vec{b}_\perp \cdot \vec{oc}_\perp) t +
+    /// |\vec{oc}_\perp|^2 - r^2 = 0, $$
+    /// whose roots are only accepted if they lie within the height of the cylinder. The caps are
+    /// intersected as disks in the planes through the bottom and top centers. The closest of all
+    /// the accepted hits is returned.
+    fn ray_hit(&self, ray: &Ray, ray_parameter_interval: Interval) -> HitRecord {
+        let epsilon: f64 = 1e-8;
+        // Closest accepted ray parameter and the outward normal at that hit.
+        let mut closest_ray: f64 = ray_parameter_interval.max;
+        let mut outward_normal: Option<Vec3> = None;
+
+        // Curved side
+        let oc: Vec3 = ray.origin - self.base;
+        let direction_perp: Vec3 = ray.direction - self.axis * ray.direction.dot(&self.axis);
+        let oc_perp: Vec3 = oc - self.axis * oc.dot(&self.axis);
+        let a: f64 = direction_perp.length_squared();
+        let h: f64 = direction_perp.dot(&oc_perp);
+        let c: f64 = oc_perp.length_squared() - self.radius * self.radius;
+        let discriminant: f64 = h * h - a * c;
+        // If `a` is zero, the ray runs parallel to the axis and cannot hit the curved side.
+        if a > epsilon && discriminant >= 0.0 {
+            let sqrt_d: f64 = discriminant.sqrt();
+            let height_interval: Interval = Interval::new(0.0, self.height);
+            // Check the nearest root first, so the first accepted root is the closest.
+            for root in [(-h - sqrt_d) / a, (-h + sqrt_d) / a] {
+                if !Interval::new(ray_parameter_interval.min, closest_ray).surrounds(root) {
+                    continue;
+                }
+                let base_to_point: Vec3 = ray.at(root) - self.base;
+                let height: f64 = base_to_point.dot(&self.axis);
+                if height_interval.contains(height) {
+                    // The normal is the radial direction, orthogonal to the axis.
+                    closest_ray = root;
+                    outward_normal = Some((base_to_point - self.axis * height) / self.radius);
+                    break;
+                }
+            }
+        }
+
+        // Caps
+        let denominator: f64 = ray.direction.dot(&self.axis);
+        if self.capped && denominator.abs() > epsilon {
+            let top: Point = self.base + self.axis * self.height;
+            for (center, normal) in [(self.base, -self.axis), (top, self.axis)] {
+                let root: f64 = (center - ray.origin).dot(&self.axis) / denominator;
+                if !Interval::new(ray_parameter_interval.min, closest_ray).surrounds(root) {
+                    continue;
+                }
+                if (ray.at(root) - center).length_squared() <= self.radius * self.radius {
+                    closest_ray = root;
+                    outward_normal = Some(normal);
+                }
+            }
+        }
+
+        // Set the fields of the hit record if any part of the cylinder was hit. Surface
+        // coordinates are not computed for the cylinder, so they are set to zero.
+        match outward_normal {
+            Some(outward_normal) => {
+                let point = ray.at(closest_ray);
+                let (front_face, normal) = set_face_normal(ray, outward_normal);
+                return HitRecord::new(
+                    true,
+                    point,
+                    normal,
+                    front_face,
+                    closest_ray,
+                    0.0,
+                    0.0,
+                    self.material,
+                );
+            }
+            None => {
+                return HitRecord::default();
+            }
+        }
+    }
+    /// The cylinder lies within the bounding box of its bottom and top disks. A disk of radius
+    /// $r$ with unit normal $\vec{a}$ extends $r \sqrt{1 - a_i^2}$ from its center along axis $i$.
+    fn bounding_box(&self) -> Aabb {
+        let extent: Vec3 = Vec3::new(
+            self.radius * (1.0 - self.axis.x * self.axis.x).max(0.0).sqrt(),
+            self.radius * (1.0 - self.axis.y * self.axis.y).max(0.0).sqrt(),
+            self.radius * (1.0 - self.axis.z * self.axis.z).max(0.0).sqrt(),
+        );
+        let top: Point = self.base + self.axis * self.height;
+        return Aabb::surrounding(
+            &Aabb::from_points(self.base - extent, self.base + extent),
+            &Aabb::from_points(top - extent, top + extent),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::utils::POSITIVE_INFINITY;
+
+    fn cylinder(capped: bool) -> Cylinder {
+        return Cylinder::new(
+            Point::new(0.0, 0.0, -3.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            1.0,
+            2.0,
+            capped,
+            MaterialId(0),
+        );
+    }
+
+    #[test]
+    fn horizontal_ray_hits_the_curved_wall() {
+        let ray: Ray = Ray::new(Point::new(0.0, 1.0, 0.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let hit_record: HitRecord = cylinder(true).ray_hit(&ray, Interval::new(0.001, 100.0));
+        assert!(hit_record.hit);
+        assert!((hit_record.ray_parameter - 2.0).abs() < 1e-12);
+        // The side normal is radial, perpendicular to the axis.
+        assert!((hit_record.normal - Vec3::new(0.0, 0.0, 1.0)).length() < 1e-12);
+        assert!(hit_record.front_face);
+    }
+
+    #[test]
+    fn vertical_ray_hits_the_top_cap() {
+        let ray: Ray = Ray::new(Point::new(0.5, 5.0, -3.0), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        let interval: Interval = Interval::new(0.001, POSITIVE_INFINITY);
+        let hit_record: HitRecord = cylinder(true).ray_hit(&ray, interval);
+        assert!(hit_record.hit);
+        assert!((hit_record.ray_parameter - 3.0).abs() < 1e-12);
+        assert!((hit_record.normal - Vec3::new(0.0, 1.0, 0.0)).length() < 1e-12);
+
+        // Without caps, the ray runs down the inside of the tube without touching it.
+        assert!(!cylinder(false).ray_hit(&ray, interval).hit);
+    }
+}
@@ -0,0 +1,138 @@
+use super::aabb::Aabb;
+use super::hittables::Hittable;
+use super::record::{set_face_normal, HitRecord};
+use crate::materials::arena::MaterialId;
+use crate::raycaster::ray::Ray;
+use crate::util::utils::{get_random, Interval, POSITIVE_INFINITY};
+use crate::vector::vector::{Point, Vec3};
+
+/// A `Disk` is a flat circular surface, defined by its center, the unit normal of the plane it
+/// lies in and its radius.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Disk {
+    pub center: Point,
+    pub normal: Vec3,
+    pub radius: f64,
+    pub material: MaterialId,
+}
+
+impl Disk {
+    /// Create new `Disk` instance. The `normal` is normalised, so it does not need to be of unit
+    /// length.
+    pub fn new(center: Point, normal: Vec3, radius: f64, material: MaterialId) -> Self {
+        Self {
+            center,
+            normal: normal.unit_vector(),
+            radius,
+            material,
+        }
+    }
+}
+
+impl Hittable for Disk {
+    /// First the ray is intersected with the plane of the disk, in the same way as for a `Plane`.
+    /// The ray hits the disk if the hit point $\vec{P}$ lies within `radius` of the center
+    /// $\vec{C}$, i.e. $|\vec{P} - \vec{C}|^2 \leq r^2$. The surface coordinates are the polar
+    /// coordinates of the hit point in the plane, with $u$ the angle divided by $2 \pi$ and $v$
+    /// the distance to the center divided by the radius.
+    fn ray_hit(&self, ray: &Ray, ray_parameter_interval: Interval) -> HitRecord {
+        let epsilon: f64 = 1e-8;
+        let denominator: f64 = ray.direction.dot(&self.normal);
+
+        // Ray runs parallel to the disk, so it missed.
+        if denominator.abs() < epsilon {
+            return HitRecord::default();
+        }
+        // Check if the hit with the plane lies in the acceptable range.
+        let root: f64 = (self.center - ray.origin).dot(&self.normal) / denominator;
+        if !ray_parameter_interval.surrounds(root) {
+            return HitRecord::default();
+        }
+
+        // Check if the hit point lies within the radius of the disk.
+        let point: Point = ray.at(root);
+        let planar_point: Vec3 = point - self.center;
+        if planar_point.length_squared() > self.radius * self.radius {
+            return HitRecord::default();
+        }
+
+        // Set the fields of the hit record.
+        let (tangent, bitangent) = self.normal.orthonormal_basis();
+        let angle: f64 = planar_point
+            .dot(&bitangent)
+            .atan2(planar_point.dot(&tangent));
+        let u: f64 = (angle + std::f64::consts::PI) / (2.0 * std::f64::consts::PI);
+        let v: f64 = planar_point.length() / self.radius;
+        let (front_face, normal) = set_face_normal(ray, self.normal);
+        return HitRecord::new(true, point, normal, front_face, root, u, v, self.material);
+    }
+    /// Along an axis with unit vector $\vec{e}$, the disk extends $r \sqrt{1 - (\vec{n} \cdot
+    /// \vec{e})^2}$ from the center, which is zero along the normal and $r$ perpendicular to it.
+    fn bounding_box(&self) -> Aabb {
+        let extent = |n: f64| self.radius * (1.0 - n * n).max(0.0).sqrt();
+        let extent_vector: Vec3 = Vec3::new(
+            extent(self.normal.x),
+            extent(self.normal.y),
+            extent(self.normal.z),
+        );
+        return Aabb::from_points(self.center - extent_vector, self.center + extent_vector);
+    }
+    /// Sample a point uniformly on the disk, at angle $2 \pi \xi_1$ and distance
+    /// $r \sqrt{\xi_2}$ from the center, and return the direction from `origin` to it with its
+    /// density, see `pdf_value`. The square root spreads the points evenly over the area instead
+    /// of bunching them up near the center.
+    fn random_point_toward(&self, origin: &Point) -> (Vec3, f64) {
+        let (tangent, bitangent) = self.normal.orthonormal_basis();
+        let angle: f64 = 2.0 * std::f64::consts::PI * get_random();
+        let distance: f64 = self.radius * get_random().sqrt();
+        let point: Point =
+            self.center + (tangent * angle.cos() + bitangent * angle.sin()) * distance;
+        let direction: Vec3 = point - *origin;
+        return (direction, self.pdf_value(origin, &direction));
+    }
+    /// A uniformly sampled point on the disk with area $A = \pi r^2$, at distance $d$ from
+    /// `origin` and seen under an angle $\theta$ with the normal, has the solid angle density
+    /// $$ \text{pdf} = \frac{d^2}{|\cos \theta| A}. $$
+    /// Directions that do not hit the disk have a density of zero.
+    fn pdf_value(&self, origin: &Point, direction: &Vec3) -> f64 {
+        let ray: Ray = Ray::new(*origin, *direction, 0.0);
+        let hit_record: HitRecord = self.ray_hit(&ray, Interval::new(0.001, POSITIVE_INFINITY));
+        if !hit_record.hit {
+            return 0.0;
+        }
+        let area: f64 = std::f64::consts::PI * self.radius * self.radius;
+        let distance_squared: f64 =
+            hit_record.ray_parameter * hit_record.ray_parameter * direction.length_squared();
+        let cosine: f64 = (direction.dot(&self.normal) / direction.length()).abs();
+        return distance_squared / (cosine * area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ray_inside_the_radius_hits_and_outside_misses() {
+        let disk: Disk = Disk::new(
+            Point::new(1.0, 0.0, -3.0),
+            Vec3::new(0.0, 0.0, 2.0),
+            0.5,
+            MaterialId(0),
+        );
+        let down = |x: f64, y: f64| {
+            let ray: Ray = Ray::new(Point::new(x, y, 0.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+            return disk.ray_hit(&ray, Interval::new(0.001, POSITIVE_INFINITY));
+        };
+        let inside: HitRecord = down(1.3, 0.3);
+        assert!(inside.hit);
+        assert_eq!(inside.point, Point::new(1.3, 0.3, -3.0));
+        assert_eq!(inside.normal, Vec3::new(0.0, 0.0, 1.0));
+        assert!(inside.front_face);
+        assert!((inside.v - (0.18_f64).sqrt() / 0.5).abs() < 1e-12);
+
+        // The plane of the disk is hit, but 0.6 away from the center.
+        assert!(!down(1.0, 0.6).hit);
+        assert!(!down(1.4, 0.4).hit);
+    }
+}
@@ -1,24 +1,43 @@
+use super::aabb::Aabb;
 use super::record::HitRecord;
-use crate::materials::materials::{Lambertian, Material};
+use crate::materials::arena::{MaterialArena, MaterialId};
+use crate::materials::materials::Material;
 use crate::raycaster::ray::Ray;
-use crate::util::utils::Interval;
+use crate::util::utils::{get_random, Interval};
+use crate::vector::vector::{Point, Vec3};
+use std::sync::Arc;
 
 /// Hittable traits are able to implement the `ray_hit` method, meaning there is a way to determine
-/// if a ray hit the object. The function should return a `HitRecord`.
-pub trait Hittable {
-    fn ray_hit(
-        &self,
-        ray: &Ray,
-        ray_parameter_interval: Interval,
-    ) -> (HitRecord, Box<dyn Material>);
+/// if a ray hit the object. The function should return a `HitRecord`, which holds the material
+/// of the object on a hit. The `bounding_box` method
+/// returns an axis-aligned box that contains the entire object. Objects that can be sampled as
+/// area lights implement `random_point_toward`, which returns the direction from `origin` to a
+/// uniformly sampled point on the surface together with the solid angle probability density of
+/// that direction, and `pdf_value`, which returns that density for any `direction`. By default, an
+/// object cannot be sampled and the density is zero.
+/// Note: Hittables are shared between the rendering threads, hence they must be `Send + Sync`.
+pub trait Hittable: Send + Sync {
+    fn ray_hit(&self, ray: &Ray, ray_parameter_interval: Interval) -> HitRecord;
+    fn bounding_box(&self) -> Aabb;
+    fn random_point_toward(&self, _origin: &Point) -> (Vec3, f64) {
+        return (Vec3::new(1.0, 0.0, 0.0), 0.0);
+    }
+    fn pdf_value(&self, _origin: &Point, _direction: &Vec3) -> f64 {
+        return 0.0;
+    }
 }
 
 /// Create a struct that contains a vector of hittable objects. The hittable objects are those
 /// structs that implement the `Hittable` trait.
-/// Note: The elements of the vector must be contained in a `Box`, e.g. we need to surround each
-/// entry of such a vector by `Box::new(...)`.
+/// Note: The elements of the vector must be contained in an `Arc`, e.g. we need to surround each
+/// entry of such a vector by `Arc::new(...)`. Cloning an `Arc` only adds a reference to the same
+/// object, so a large object such as a mesh can be placed many times, for example in several
+/// `Translate` wrappers, without copying its data.
+/// The `Hittables` that is rendered as the world also holds the `MaterialArena` with the materials
+/// of all its objects, which is where the `MaterialId` of a `HitRecord` is looked up.
 pub struct Hittables {
-    hittable_list: Vec<Box<dyn Hittable>>,
+    hittable_list: Vec<Arc<dyn Hittable>>,
+    materials: MaterialArena,
 }
 
 impl Hittables {
@@ -26,50 +45,255 @@ impl Hittables {
     pub fn init() -> Self {
         Self {
             hittable_list: Vec::new(),
+            materials: MaterialArena::new(),
         }
     }
     /// Create new instance of `Hittables`
-    pub fn new(hittable_list: Vec<Box<dyn Hittable>>) -> Self {
-        Self { hittable_list }
+    pub fn new(hittable_list: Vec<Arc<dyn Hittable>>) -> Self {
+        Self {
+            hittable_list,
+            materials: MaterialArena::new(),
+        }
+    }
+    /// Create new instance of `Hittables` whose objects use the materials of `materials`.
+    pub fn with_materials(hittable_list: Vec<Arc<dyn Hittable>>, materials: MaterialArena) -> Self {
+        Self {
+            hittable_list,
+            materials,
+        }
+    }
+    /// Store the `material` in the `Hittables.materials` and return its id, which can be given to
+    /// any number of objects.
+    pub fn add_material<T: Material + 'static>(&mut self, material: T) -> MaterialId {
+        return self.materials.add(material);
+    }
+    /// Get the material with the given `id` from the `Hittables.materials`.
+    pub fn material(&self, id: MaterialId) -> Option<&dyn Material> {
+        return self.materials.get(id);
+    }
+    /// Take the `Hittables.materials` out, leaving an empty arena, e.g. to move them to a
+    /// `Hittables` that wraps this one.
+    pub fn take_materials(&mut self) -> MaterialArena {
+        return std::mem::take(&mut self.materials);
     }
     /// Add element to the `Hittables.hittable_list`
-    pub fn add(&mut self, hittable: Box<dyn Hittable>) {
+    pub fn add(&mut self, hittable: Arc<dyn Hittable>) {
         self.hittable_list.push(hittable);
     }
+    /// Remove the element at `index` from the `Hittables.hittable_list` and return it, or `None`
+    /// if there is no element at `index`.
+    pub fn remove(&mut self, index: usize) -> Option<Arc<dyn Hittable>> {
+        if index >= self.hittable_list.len() {
+            return None;
+        }
+        return Some(self.hittable_list.remove(index));
+    }
+    /// Remove all elements from the `Hittables.hittable_list`.
+    pub fn clear(&mut self) {
+        self.hittable_list.clear();
+    }
+    /// Get the number of elements in the `Hittables.hittable_list`.
+    pub fn len(&self) -> usize {
+        return self.hittable_list.len();
+    }
+    /// Check if the `Hittables.hittable_list` has no elements.
+    pub fn is_empty(&self) -> bool {
+        return self.hittable_list.is_empty();
+    }
+    /// Consume the `Hittables` and return the underlying `Hittables.hittable_list`.
+    pub fn into_hittable_list(self) -> Vec<Arc<dyn Hittable>> {
+        return self.hittable_list;
+    }
+}
+
+/// Collect shared hittable objects into `Hittables`, e.g.
+/// `let world: Hittables = spheres.into_iter().collect();`.
+impl FromIterator<Arc<dyn Hittable>> for Hittables {
+    fn from_iter<I: IntoIterator<Item = Arc<dyn Hittable>>>(iter: I) -> Self {
+        return Self::new(iter.into_iter().collect());
+    }
+}
+
+/// Add all shared hittable objects of an iterator to the `Hittables.hittable_list`.
+impl Extend<Arc<dyn Hittable>> for Hittables {
+    fn extend<I: IntoIterator<Item = Arc<dyn Hittable>>>(&mut self, iter: I) {
+        self.hittable_list.extend(iter);
+    }
 }
 
 impl Hittable for Hittables {
     /// Implement the `Hittable` trait for `Hittables`. We loop over all the elements and see if
     /// any of them hit. We can use the `Hittable` trait on all the elements as this is assumed to
-    /// be the case. If there are multiple hits, the closest hit is returned.
-    fn ray_hit(
-        &self,
-        ray: &Ray,
-        ray_parameter_interval: Interval,
-    ) -> (HitRecord, Box<dyn Material>) {
+    /// be the case. Every element is only asked for hits closer than the closest hit found so far,
+    /// so the closest hit is returned.
+    fn ray_hit(&self, ray: &Ray, ray_parameter_interval: Interval) -> HitRecord {
         // Get the default `HitRecord`
         let mut hit_record: HitRecord = HitRecord::default();
-        let mut material: Box<dyn Material> = Box::new(Lambertian::default());
         // Initialise the current closest hit to the maximum allowed ray parameter.
         let mut closest_ray: f64 = ray_parameter_interval.max;
 
         // Loop over all the hittables
         for hittable in &self.hittable_list {
-            // Get the hit record
-            let (current_hit_record, current_material) =
-                hittable.ray_hit(ray, ray_parameter_interval);
+            // Get the hit record of a hit closer than the current closest.
+            let current_hit_record: HitRecord =
+                hittable.ray_hit(ray, Interval::new(ray_parameter_interval.min, closest_ray));
             // Check if it was a hit
             if current_hit_record.hit {
-                // If so, check if the ray was closer than the current closest.
-                if current_hit_record.ray_parameter <= closest_ray {
-                    // If it was closer, update the closest ray and set the new hit record.
-                    hit_record = current_hit_record;
-                    material = current_material;
-                    closest_ray = current_hit_record.ray_parameter;
-                }
+                // If so, update the closest ray and set the new hit record.
+                closest_ray = current_hit_record.ray_parameter;
+                hit_record = current_hit_record;
             }
         }
         // Return the closest hit.
-        return (hit_record, material);
+        return hit_record;
+    }
+    /// The bounding box of `Hittables` is the smallest box that contains the bounding boxes of
+    /// all the elements. If there are no elements, the box is empty.
+    fn bounding_box(&self) -> Aabb {
+        return self
+            .hittable_list
+            .iter()
+            .fold(Aabb::empty(), |bbox, hittable| {
+                Aabb::surrounding(&bbox, &hittable.bounding_box())
+            });
+    }
+    /// Sample the direction toward a uniformly chosen element. The density of the direction is
+    /// the average of the densities of all the elements, see `pdf_value`.
+    fn random_point_toward(&self, origin: &Point) -> (Vec3, f64) {
+        if self.hittable_list.is_empty() {
+            return (Vec3::new(1.0, 0.0, 0.0), 0.0);
+        }
+        let index: usize = ((get_random() * self.len() as f64) as usize).min(self.len() - 1);
+        let (direction, _) = self.hittable_list[index].random_point_toward(origin);
+        return (direction, self.pdf_value(origin, &direction));
+    }
+    /// Every element is chosen with the same probability, so the density of a direction is the
+    /// average of the densities of the elements.
+    fn pdf_value(&self, origin: &Point, direction: &Vec3) -> f64 {
+        if self.hittable_list.is_empty() {
+            return 0.0;
+        }
+        let total: f64 = self
+            .hittable_list
+            .iter()
+            .map(|hittable| hittable.pdf_value(origin, direction))
+            .sum();
+        return total / self.hittable_list.len() as f64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hittables::sphere::Sphere;
+    use crate::hittables::translate::Translate;
+    use crate::materials::materials::{Lambertian, Metal, Scatter};
+    use crate::util::utils::POSITIVE_INFINITY;
+    use crate::vector::vector::Color;
+
+    fn sphere(x: f64) -> Arc<dyn Hittable> {
+        return Arc::new(Sphere::new(Point::new(x, 0.0, -2.0), 0.5, MaterialId(0)));
+    }
+
+    #[test]
+    fn add_len_remove_and_clear() {
+        let mut world: Hittables = Hittables::init();
+        assert!(world.is_empty());
+        let first: Arc<dyn Hittable> = sphere(-1.0);
+        let second: Arc<dyn Hittable> = sphere(1.0);
+        world.add(first.clone());
+        world.add(second.clone());
+        assert_eq!(world.len(), 2);
+        assert!(!world.is_empty());
+
+        // Removing shifts the later elements down.
+        assert!(world.remove(2).is_none());
+        let removed: Arc<dyn Hittable> = world.remove(0).unwrap();
+        assert!(Arc::ptr_eq(&removed, &first));
+        assert_eq!(world.len(), 1);
+        assert!(Arc::ptr_eq(&world.remove(0).unwrap(), &second));
+        assert!(world.is_empty());
+
+        world.add(first);
+        world.add(second);
+        world.clear();
+        assert_eq!(world.len(), 0);
+        assert!(world.is_empty());
+    }
+
+    #[test]
+    fn collect_and_extend_from_iterators() {
+        let mut world: Hittables = [-1.0, 0.0, 1.0].into_iter().map(sphere).collect();
+        assert_eq!(world.len(), 3);
+        world.extend([2.0, 3.0].into_iter().map(sphere));
+        assert_eq!(world.len(), 5);
+    }
+
+    #[test]
+    fn nearer_of_two_overlapping_spheres_is_hit() {
+        // The far sphere is added first, so the nearer hit has to replace it.
+        let far: Arc<dyn Hittable> =
+            Arc::new(Sphere::new(Point::new(0.0, 0.0, -3.0), 1.0, MaterialId(1)));
+        let near: Arc<dyn Hittable> =
+            Arc::new(Sphere::new(Point::new(0.0, 0.0, -2.5), 1.0, MaterialId(2)));
+        let world: Hittables = Hittables::new(vec![far, near.clone()]);
+        let ray: Ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let interval: Interval = Interval::new(0.001, POSITIVE_INFINITY);
+
+        let hit_record: HitRecord = world.ray_hit(&ray, interval);
+        assert_eq!(hit_record, near.ray_hit(&ray, interval));
+        assert_eq!(hit_record.material, Some(MaterialId(2)));
+        assert_eq!(hit_record.ray_parameter, 1.5);
+    }
+
+    #[test]
+    fn two_translates_share_one_sphere() {
+        let sphere: Arc<dyn Hittable> = sphere(0.0);
+        let left: Translate = Translate::new(sphere.clone(), Vec3::new(-2.0, 0.0, 0.0));
+        let right: Translate = Translate::new(sphere.clone(), Vec3::new(2.0, 0.0, 0.0));
+        assert!(Arc::ptr_eq(&left.object, &right.object));
+        assert_eq!(Arc::strong_count(&sphere), 3);
+
+        let world: Hittables = [left, right]
+            .into_iter()
+            .map(|instance| Arc::new(instance) as Arc<dyn Hittable>)
+            .collect();
+        assert_eq!(Arc::strong_count(&sphere), 3);
+        for x in [-2.0, 2.0] {
+            let ray: Ray = Ray::new(Point::new(x, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+            let hit_record: HitRecord =
+                world.ray_hit(&ray, Interval::new(0.001, POSITIVE_INFINITY));
+            assert!(hit_record.hit);
+            assert_eq!(hit_record.point, Point::new(x, 0.0, -1.5));
+        }
+    }
+
+    #[test]
+    fn hit_on_a_metal_sphere_reports_the_metal() {
+        let mut world: Hittables = Hittables::init();
+        let matte: MaterialId = world.add_material(Lambertian::new(Color::new(0.1, 0.1, 0.1)));
+        let gold: Color = Color::new(0.9, 0.7, 0.2);
+        let metal: MaterialId = world.add_material(Metal::new(gold, 0.0));
+        world.add(Arc::new(Sphere::new(
+            Point::new(0.0, 0.0, -2.0),
+            0.5,
+            metal,
+        )));
+        world.add(Arc::new(Sphere::new(
+            Point::new(0.0, 0.0, -5.0),
+            0.5,
+            matte,
+        )));
+
+        let ray: Ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let hit_record: HitRecord = world.ray_hit(&ray, Interval::new(0.001, POSITIVE_INFINITY));
+        assert_eq!(hit_record.material, Some(metal));
+        let material: &dyn Material = world.material(metal).unwrap();
+        assert_eq!(material.base_color(&hit_record), gold);
+        assert!(!material.is_diffuse());
+        // A metal without fuzz mirrors the ray straight back.
+        let scatter: Scatter = material.scatter(&ray, &hit_record);
+        assert!(scatter.did_scatter);
+        assert_eq!(scatter.ray.direction, Vec3::new(0.0, 0.0, 1.0));
     }
 }
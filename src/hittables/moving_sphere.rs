@@ -0,0 +1,111 @@
+use super::aabb::Aabb;
+use super::hittables::Hittable;
+use super::record::HitRecord;
+use super::sphere::Sphere;
+use crate::materials::arena::MaterialId;
+use crate::raycaster::ray::Ray;
+use crate::util::utils::Interval;
+use crate::vector::vector::Point;
+
+/// A `MovingSphere` is a `Sphere` whose center moves in a straight line from `center0` at time
+/// `time0` to `center1` at time `time1`. Rays cast at different times see the sphere at different
+/// positions, which results in motion blur.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MovingSphere {
+    pub center0: Point,
+    pub center1: Point,
+    pub time0: f64,
+    pub time1: f64,
+    pub radius: f64,
+    pub material: MaterialId,
+}
+
+impl MovingSphere {
+    /// Create new `MovingSphere` instance.
+    pub fn new(
+        center0: Point,
+        center1: Point,
+        time0: f64,
+        time1: f64,
+        radius: f64,
+        material: MaterialId,
+    ) -> Self {
+        Self {
+            center0,
+            center1,
+            time0,
+            time1,
+            radius,
+            material,
+        }
+    }
+    /// Get the center of the sphere at the given `time` by linearly interpolating between the
+    /// two centers. Outside of [time0, time1], the sphere stays at the closest of the two centers.
+    pub fn center(&self, time: f64) -> Point {
+        if self.time1 == self.time0 {
+            return self.center0;
+        }
+        let fraction: f64 =
+            Interval::new(0.0, 1.0).clamp((time - self.time0) / (self.time1 - self.time0));
+        return self.center0.lerp(&self.center1, fraction);
+    }
+    /// Get the `Sphere` with the radius and material of the moving sphere at the given `center`.
+    pub fn sphere_at(&self, center: Point) -> Sphere {
+        return Sphere {
+            center,
+            radius: self.radius,
+            material: self.material,
+        };
+    }
+}
+
+impl Hittable for MovingSphere {
+    /// The ray hits the moving sphere if it hits the `Sphere` at the position of the center at
+    /// the time the ray was cast.
+    fn ray_hit(&self, ray: &Ray, ray_parameter_interval: Interval) -> HitRecord {
+        return self
+            .sphere_at(self.center(ray.time))
+            .ray_hit(ray, ray_parameter_interval);
+    }
+    /// The bounding box of a `MovingSphere` contains the sphere at both of its end positions,
+    /// and hence along its entire path.
+    fn bounding_box(&self) -> Aabb {
+        let sphere0: Sphere = self.sphere_at(self.center0);
+        let sphere1: Sphere = self.sphere_at(self.center1);
+        return Aabb::surrounding(&sphere0.bounding_box(), &sphere1.bounding_box());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::utils::POSITIVE_INFINITY;
+    use crate::vector::vector::Vec3;
+
+    #[test]
+    fn rays_at_time_zero_and_one_see_the_two_positions() {
+        let sphere: MovingSphere = MovingSphere::new(
+            Point::new(0.0, 0.0, -3.0),
+            Point::new(2.0, 0.0, -3.0),
+            0.0,
+            1.0,
+            0.5,
+            MaterialId(0),
+        );
+        let interval: Interval = Interval::new(0.001, POSITIVE_INFINITY);
+        let shoot = |x: f64, time: f64| {
+            let ray: Ray = Ray::new(Point::new(x, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0), time);
+            return sphere.ray_hit(&ray, interval);
+        };
+
+        let start: HitRecord = shoot(0.0, 0.0);
+        assert!(start.hit);
+        assert_eq!(start.point, Point::new(0.0, 0.0, -2.5));
+        assert!(!shoot(2.0, 0.0).hit);
+
+        let end: HitRecord = shoot(2.0, 1.0);
+        assert!(end.hit);
+        assert_eq!(end.point, Point::new(2.0, 0.0, -2.5));
+        assert!(!shoot(0.0, 1.0).hit);
+    }
+}
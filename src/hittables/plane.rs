@@ -0,0 +1,99 @@
+use super::aabb::Aabb;
+use super::hittables::Hittable;
+use super::record::{set_face_normal, HitRecord};
+use crate::materials::arena::MaterialId;
+use crate::raycaster::ray::Ray;
+use crate::util::utils::Interval;
+use crate::vector::vector::{Point, Vec3};
+
+/// A `Plane` is an infinite flat surface, defined by a point that lies on the plane and the
+/// unit normal of the plane.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Plane {
+    pub point: Point,
+    pub normal: Vec3,
+    pub material: MaterialId,
+}
+
+impl Plane {
+    /// Create new `Plane` instance. The `normal` is normalised, so it does not need to be of unit
+    /// length.
+    pub fn new(point: Point, normal: Vec3, material: MaterialId) -> Self {
+        Self {
+            point,
+            normal: normal.unit_vector(),
+            material,
+        }
+    }
+}
+
+impl Hittable for Plane {
+    /// A point $\vec{P}$ lies on the plane if $(\vec{P} - \vec{Q}) \cdot \vec{n} = 0$, with
+    /// $\vec{Q}$ the point on the plane and $\vec{n}$ the normal. Filling in the ray
+    /// $\vec{P}(t) = \vec{A} + t \vec{b}$ gives
+    /// $$ t = \frac{(\vec{Q} - \vec{A}) \cdot \vec{n}}{\vec{b} \cdot \vec{n}}. $$
+    /// If the denominator is (close to) zero, the ray runs parallel to the plane and does not hit.
+    fn ray_hit(&self, ray: &Ray, ray_parameter_interval: Interval) -> HitRecord {
+        let epsilon: f64 = 1e-8;
+        let denominator: f64 = ray.direction.dot(&self.normal);
+
+        // Ray runs parallel to the plane, so it missed.
+        if denominator.abs() < epsilon {
+            return HitRecord::default();
+        }
+        // Check if the hit lies in the acceptable range.
+        let root: f64 = (self.point - ray.origin).dot(&self.normal) / denominator;
+        if !ray_parameter_interval.surrounds(root) {
+            return HitRecord::default();
+        }
+
+        // Set the fields of the hit record. An infinite plane has no natural surface
+        // coordinates, so they are set to zero.
+        let point = ray.at(root);
+        let (front_face, normal) = set_face_normal(ray, self.normal);
+        return HitRecord::new(
+            true,
+            point,
+            normal,
+            front_face,
+            root,
+            0.0,
+            0.0,
+            self.material,
+        );
+    }
+    /// A `Plane` is infinite, so its bounding box covers all of space.
+    fn bounding_box(&self) -> Aabb {
+        return Aabb::universe();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::utils::POSITIVE_INFINITY;
+
+    #[test]
+    fn ray_straight_down_hits_horizontal_plane_below_origin() {
+        let plane: Plane = Plane::new(
+            Point::new(0.0, -1.0, 0.0),
+            Vec3::new(0.0, 2.0, 0.0),
+            MaterialId(0),
+        );
+        let ray: Ray = Ray::new(Point::new(0.5, 3.0, -2.0), Vec3::new(0.0, -2.0, 0.0), 0.0);
+        let hit_record: HitRecord = plane.ray_hit(&ray, Interval::new(0.001, POSITIVE_INFINITY));
+        assert!(hit_record.hit);
+        assert_eq!(hit_record.ray_parameter, 2.0);
+        assert_eq!(hit_record.point, Point::new(0.5, -1.0, -2.0));
+        assert_eq!(hit_record.normal, Vec3::new(0.0, 1.0, 0.0));
+        assert!(hit_record.front_face);
+
+        // Going up, the plane lies behind the ray.
+        let ray: Ray = Ray::new(Point::new(0.5, 3.0, -2.0), Vec3::new(0.0, 1.0, 0.0), 0.0);
+        assert!(
+            !plane
+                .ray_hit(&ray, Interval::new(0.001, POSITIVE_INFINITY))
+                .hit
+        );
+    }
+}
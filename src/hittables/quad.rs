@@ -0,0 +1,159 @@
+use super::aabb::Aabb;
+use super::hittables::Hittable;
+use super::record::{set_face_normal, HitRecord};
+use crate::materials::arena::MaterialId;
+use crate::raycaster::ray::Ray;
+use crate::util::utils::{get_random, Interval, POSITIVE_INFINITY};
+use crate::vector::vector::{Point, Vec3};
+
+/// A `Quad` is a parallelogram defined by a corner point `q` and the two edge vectors `u` and `v`
+/// starting at that corner. The outward normal is given by $\vec{u} \times \vec{v}$.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quad {
+    pub q: Point,
+    pub u: Vec3,
+    pub v: Vec3,
+    pub material: MaterialId,
+}
+
+impl Quad {
+    /// Create new `Quad` instance.
+    pub fn new(q: Point, u: Vec3, v: Vec3, material: MaterialId) -> Self {
+        Self { q, u, v, material }
+    }
+}
+
+impl Hittable for Quad {
+    /// First the ray is intersected with the plane spanned by `u` and `v`, in the same way as
+    /// for a `Plane`. The hit point $\vec{P}$ is then written in the planar coordinates
+    /// $\vec{P} = \vec{Q} + \alpha \vec{u} + \beta \vec{v}$, and the ray hits the quad if both
+    /// $\alpha$ and $\beta$ lie in $[0, 1]$. With $\vec{n} = \vec{u} \times \vec{v}$ and
+    /// $\vec{p} = \vec{P} - \vec{Q}$, these coordinates are
+    /// $$ \alpha = \frac{\vec{n} \cdot (\vec{p} \times \vec{v})}{\vec{n} \cdot \vec{n}}, \quad
+    /// \beta = \frac{\vec{n} \cdot (\vec{u} \times \vec{p})}{\vec{n} \cdot \vec{n}}. $$
+    fn ray_hit(&self, ray: &Ray, ray_parameter_interval: Interval) -> HitRecord {
+        let epsilon: f64 = 1e-8;
+        let n: Vec3 = self.u.cross(&self.v);
+        let outward_normal: Vec3 = n.unit_vector();
+        let denominator: f64 = ray.direction.dot(&outward_normal);
+
+        // Ray runs parallel to the quad, so it missed.
+        if denominator.abs() < epsilon {
+            return HitRecord::default();
+        }
+        // Check if the hit with the plane lies in the acceptable range.
+        let root: f64 = (self.q - ray.origin).dot(&outward_normal) / denominator;
+        if !ray_parameter_interval.surrounds(root) {
+            return HitRecord::default();
+        }
+
+        // Check if the hit point lies within the quad using its planar coordinates.
+        let point: Point = ray.at(root);
+        let planar_point: Vec3 = point - self.q;
+        let w: Vec3 = n / n.length_squared();
+        let alpha: f64 = w.dot(&planar_point.cross(&self.v));
+        let beta: f64 = w.dot(&self.u.cross(&planar_point));
+        let unit_interval: Interval = Interval::new(0.0, 1.0);
+        if !unit_interval.contains(alpha) || !unit_interval.contains(beta) {
+            return HitRecord::default();
+        }
+
+        // Set the fields of the hit record, the planar coordinates are the surface coordinates.
+        let (front_face, normal) = set_face_normal(ray, outward_normal);
+        return HitRecord::new(
+            true,
+            point,
+            normal,
+            front_face,
+            root,
+            alpha,
+            beta,
+            self.material,
+        );
+    }
+    /// The bounding box of a `Quad` is the smallest box containing its four corners.
+    fn bounding_box(&self) -> Aabb {
+        return Aabb::surrounding(
+            &Aabb::from_points(self.q, self.q + self.u + self.v),
+            &Aabb::from_points(self.q + self.u, self.q + self.v),
+        );
+    }
+    /// Sample a point uniformly on the quad, $\vec{P} = \vec{Q} + \xi_1 \vec{u} + \xi_2 \vec{v}$,
+    /// and return the direction from `origin` to it with its density, see `pdf_value`.
+    fn random_point_toward(&self, origin: &Point) -> (Vec3, f64) {
+        let point: Point = self.q + self.u * get_random() + self.v * get_random();
+        let direction: Vec3 = point - *origin;
+        return (direction, self.pdf_value(origin, &direction));
+    }
+    /// A uniformly sampled point on the quad with area $A$, at distance $d$ from `origin` and seen
+    /// under an angle $\theta$ with the normal, has the solid angle density
+    /// $$ \text{pdf} = \frac{d^2}{|\cos \theta| A}. $$
+    /// Directions that do not hit the quad have a density of zero.
+    fn pdf_value(&self, origin: &Point, direction: &Vec3) -> f64 {
+        let ray: Ray = Ray::new(*origin, *direction, 0.0);
+        let hit_record: HitRecord = self.ray_hit(&ray, Interval::new(0.001, POSITIVE_INFINITY));
+        if !hit_record.hit {
+            return 0.0;
+        }
+        let n: Vec3 = self.u.cross(&self.v);
+        let area: f64 = n.length();
+        let distance_squared: f64 =
+            hit_record.ray_parameter * hit_record.ray_parameter * direction.length_squared();
+        let cosine: f64 = (direction.dot(&n) / (direction.length() * area)).abs();
+        return distance_squared / (cosine * area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::utils::seed_rng;
+
+    #[test]
+    fn ray_through_center_hits_and_just_outside_an_edge_misses() {
+        let quad: Quad = Quad::new(
+            Point::new(-1.0, -1.0, -2.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(0.0, 2.0, 0.0),
+            MaterialId(0),
+        );
+        let interval: Interval = Interval::new(0.001, POSITIVE_INFINITY);
+        let origin: Point = Point::new(0.0, 0.0, 0.0);
+
+        let center: Ray = Ray::new(origin, Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let hit_record: HitRecord = quad.ray_hit(&center, interval);
+        assert!(hit_record.hit);
+        assert_eq!(hit_record.point, Point::new(0.0, 0.0, -2.0));
+        assert_eq!(hit_record.normal, Vec3::new(0.0, 0.0, 1.0));
+
+        // Aim at a point just beyond the right edge at x = 1.
+        let outside: Ray = Ray::new(origin, Vec3::new(1.0 + 1e-6, 0.0, -2.0), 0.0);
+        assert!(!quad.ray_hit(&outside, interval).hit);
+        let inside: Ray = Ray::new(origin, Vec3::new(1.0 - 1e-6, 0.0, -2.0), 0.0);
+        assert!(quad.ray_hit(&inside, interval).hit);
+    }
+
+    #[test]
+    fn pdf_integrates_to_one_over_all_directions() {
+        seed_rng(62);
+        let quad: Quad = Quad::new(
+            Point::new(-1.0, -1.0, -1.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(0.0, 2.0, 0.5),
+            MaterialId(0),
+        );
+        let origin: Point = Point::new(0.2, 0.1, 0.0);
+        // Monte Carlo estimate with uniformly distributed directions, whose density is 1 / 4 pi.
+        let count: usize = 100_000;
+        let total: f64 = (0..count)
+            .map(|_| quad.pdf_value(&origin, &Vec3::get_random_unit_vector()))
+            .sum();
+        let integral: f64 = total / count as f64 * 4.0 * std::f64::consts::PI;
+        assert!((integral - 1.0).abs() < 0.05);
+
+        // Sampled directions point at the quad and have the density of `pdf_value`.
+        let (direction, pdf) = quad.random_point_toward(&origin);
+        assert!(pdf > 0.0);
+        assert_eq!(pdf, quad.pdf_value(&origin, &direction));
+    }
+}
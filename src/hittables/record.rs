@@ -1,9 +1,12 @@
+use crate::materials::arena::MaterialId;
 use crate::raycaster::ray::Ray;
 use crate::vector::vector::{Point, Vec3};
 
 /// Structure that stores the information when a hit occurs, such as the
-/// point that was registered as a hit, the normal vector of that point and
-/// the parameter for that point along the ray.
+/// point that was registered as a hit, the normal vector of that point,
+/// the parameter for that point along the ray, the surface coordinates (u,v)
+/// of that point, which are used for texture mapping, and the id of the material of the surface
+/// that was hit in the `MaterialArena` of the world. If there was no hit, there is no material.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct HitRecord {
     pub hit: bool,
@@ -11,10 +14,14 @@ pub struct HitRecord {
     pub normal: Vec3,
     pub front_face: bool,
     pub ray_parameter: f64,
+    pub u: f64,
+    pub v: f64,
+    pub material: Option<MaterialId>,
 }
 
 impl Default for HitRecord {
-    /// By default, everything that can be zero is set to zero and hit is set to `false`.
+    /// By default, everything that can be zero is set to zero, hit is set to `false` and there is
+    /// no material.
     fn default() -> Self {
         Self {
             hit: false,
@@ -22,6 +29,9 @@ impl Default for HitRecord {
             normal: Vec3::new(0.0, 0.0, 0.0),
             front_face: false,
             ray_parameter: 0.0,
+            u: 0.0,
+            v: 0.0,
+            material: None,
         }
     }
 }
@@ -34,6 +44,9 @@ impl HitRecord {
         normal: Vec3,
         front_face: bool,
         ray_parameter: f64,
+        u: f64,
+        v: f64,
+        material: MaterialId,
     ) -> Self {
         Self {
             hit,
@@ -41,6 +54,9 @@ impl HitRecord {
             normal,
             front_face,
             ray_parameter,
+            u,
+            v,
+            material: Some(material),
         }
     }
 }
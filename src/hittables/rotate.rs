@@ -0,0 +1,106 @@
+use super::aabb::Aabb;
+use super::hittables::Hittable;
+use super::record::HitRecord;
+use crate::raycaster::ray::Ray;
+use crate::util::utils;
+use crate::util::utils::Interval;
+use crate::vector::vector::{Point, Vec3};
+use std::sync::Arc;
+
+/// A `RotateY` instance places the wrapped `object` rotated about the y-axis by an angle, given
+/// in degrees in the constructor. Instead of rotating the object, the incoming rays are rotated
+/// in the opposite direction. The sine and cosine of the angle and the bounding box are computed
+/// once in the constructor.
+pub struct RotateY {
+    pub object: Arc<dyn Hittable>,
+    pub sin_theta: f64,
+    pub cos_theta: f64,
+    pub bbox: Aabb,
+}
+
+impl RotateY {
+    /// Create new `RotateY` instance, rotating `object` by `angle` degrees about the y-axis.
+    pub fn new(object: Arc<dyn Hittable>, angle: f64) -> Self {
+        let theta: f64 = utils::degrees_to_radians(angle);
+        let sin_theta: f64 = theta.sin();
+        let cos_theta: f64 = theta.cos();
+
+        // The bounding box is the box around the eight rotated corners of the object's box.
+        let object_bbox: Aabb = object.bounding_box();
+        let mut bbox: Aabb = Aabb::empty();
+        for x in [object_bbox.x.min, object_bbox.x.max] {
+            for y in [object_bbox.y.min, object_bbox.y.max] {
+                for z in [object_bbox.z.min, object_bbox.z.max] {
+                    let corner: Point = rotate_y(Point::new(x, y, z), sin_theta, cos_theta);
+                    bbox = Aabb::surrounding(&bbox, &Aabb::from_points(corner, corner));
+                }
+            }
+        }
+
+        Self {
+            object,
+            sin_theta,
+            cos_theta,
+            bbox,
+        }
+    }
+}
+
+/// Rotate `vector` about the y-axis by the angle with the given sine and cosine.
+fn rotate_y(vector: Vec3, sin_theta: f64, cos_theta: f64) -> Vec3 {
+    return Vec3::new(
+        cos_theta * vector.x + sin_theta * vector.z,
+        vector.y,
+        -sin_theta * vector.x + cos_theta * vector.z,
+    );
+}
+
+impl Hittable for RotateY {
+    /// Rotate the ray origin and direction by the inverse rotation into the frame of the object,
+    /// check for a hit there, and rotate the hit point and normal back into the world.
+    fn ray_hit(&self, ray: &Ray, ray_parameter_interval: Interval) -> HitRecord {
+        // The inverse rotation is the rotation by the opposite angle, which flips the sine.
+        let rotated_ray: Ray = Ray::new(
+            rotate_y(ray.origin, -self.sin_theta, self.cos_theta),
+            rotate_y(ray.direction, -self.sin_theta, self.cos_theta),
+            ray.time,
+        );
+        let mut hit_record: HitRecord = self.object.ray_hit(&rotated_ray, ray_parameter_interval);
+        if hit_record.hit {
+            hit_record.point = rotate_y(hit_record.point, self.sin_theta, self.cos_theta);
+            hit_record.normal = rotate_y(hit_record.normal, self.sin_theta, self.cos_theta);
+        }
+        return hit_record;
+    }
+    /// The bounding box around the rotated bounding box of the object.
+    fn bounding_box(&self) -> Aabb {
+        return self.bbox;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hittables::sphere::Sphere;
+    use crate::materials::arena::MaterialId;
+    use crate::util::utils::POSITIVE_INFINITY;
+
+    #[test]
+    fn off_center_sphere_rotated_a_quarter_turn_is_hit_at_its_new_position() {
+        let sphere: Arc<dyn Hittable> =
+            Arc::new(Sphere::new(Point::new(3.0, 0.0, 0.0), 1.0, MaterialId(0)));
+        let rotated: RotateY = RotateY::new(sphere, 90.0);
+        let interval: Interval = Interval::new(0.001, POSITIVE_INFINITY);
+
+        // A quarter turn about the y-axis moves the center from +x to -z.
+        let from_above: Ray = Ray::new(Point::new(0.0, 10.0, -3.0), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        let hit_record: HitRecord = rotated.ray_hit(&from_above, interval);
+        assert!(hit_record.hit);
+        assert!((hit_record.point - Point::new(0.0, 1.0, -3.0)).length() < 1e-12);
+        assert!((hit_record.normal - Vec3::new(0.0, 1.0, 0.0)).length() < 1e-12);
+
+        let old: Ray = Ray::new(Point::new(3.0, 10.0, 0.0), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        assert!(!rotated.ray_hit(&old, interval).hit);
+        assert!(rotated.bounding_box().z.contains(-3.9));
+    }
+}
@@ -1,21 +1,30 @@
+use super::aabb::Aabb;
 use super::hittables::Hittable;
 use super::record::{set_face_normal, HitRecord};
-use crate::materials::materials::{Lambertian, Material};
+use crate::materials::arena::MaterialId;
 use crate::raycaster::ray::Ray;
 use crate::util::utils::Interval;
 use crate::vector::vector::{Point, Vec3};
 
-/// A `Sphere` is defined by the location of its center in 3D space, and the radius of it.
+/// A `Sphere` is defined by the location of its center in 3D space, and the radius of it. A
+/// negative radius gives the same surface, but with the normals pointing inward. Inside a larger
+/// `Dielectric` sphere, a slightly smaller sphere of the same material with a negative radius then
+/// makes the glass hollow, as rays entering the small sphere are treated as leaving the glass.
 #[derive(Debug, Clone, Copy, PartialEq)]
-pub struct Sphere<T: Material + Clone + Copy> {
+pub struct Sphere {
     pub center: Point,
     pub radius: f64,
-    pub material: T,
+    pub material: MaterialId,
 }
 
-impl<T: Material + Clone + Copy> Sphere<T> {
-    /// Create new `Sphere` instance.
-    pub fn new(center: Point, radius: f64, material: T) -> Self {
+impl Sphere {
+    /// Create new `Sphere` instance. The `radius` may be negative to flip the normals inward, see
+    /// `Sphere`. A sphere with a zero or non-finite radius can never be hit, which is logged as
+    /// a warning.
+    pub fn new(center: Point, radius: f64, material: MaterialId) -> Self {
+        if radius == 0.0 || !radius.is_finite() {
+            log::warn!("Sphere at {center:?} has radius {radius}, it cannot be hit");
+        }
         Self {
             center,
             radius,
@@ -24,16 +33,12 @@ impl<T: Material + Clone + Copy> Sphere<T> {
     }
 }
 
-impl<T: Material + Clone + Copy + 'static> Hittable for Sphere<T> {
+impl Hittable for Sphere {
     /// Given a sphere and a line in 3D, one can perform some math to find the conditions for that
     /// line to intersect the sphere. This method simply implements that math and returns if the
     /// line intersects or not. By replacing `b = -2h` in the quadratic formula, the implementation
     /// becomes even simpler.
-    fn ray_hit(
-        &self,
-        ray: &Ray,
-        ray_parameter_interval: Interval,
-    ) -> (HitRecord, Box<dyn Material>) {
+    fn ray_hit(&self, ray: &Ray, ray_parameter_interval: Interval) -> HitRecord {
         let oc: Vec3 = self.center - ray.origin;
         let a: f64 = ray.direction.length_squared();
         let h: f64 = ray.direction.dot(&oc);
@@ -42,7 +47,7 @@ impl<T: Material + Clone + Copy + 'static> Hittable for Sphere<T> {
 
         // No solution to quadratic, so ray missed.
         if discriminant < 0.0 {
-            return (HitRecord::default(), Box::new(Lambertian::default()));
+            return HitRecord::default();
         }
         let sqrt_d: f64 = discriminant.sqrt();
         // Find nearest root in the acceptable range.
@@ -54,7 +59,7 @@ impl<T: Material + Clone + Copy + 'static> Hittable for Sphere<T> {
                 if !ray_parameter_interval.surrounds(root_plus) {
                     // If we get here, the plus root also did not lie in the acceptable range,
                     // so the ray did not hit.
-                    return (HitRecord::default(), Box::new(Lambertian::default()));
+                    return HitRecord::default();
                 } else {
                     // If we get here, the plus root did lie in the acceptable range, and the
                     // minus root has already been ruled out, so root takes the value of root_plus.
@@ -67,13 +72,80 @@ impl<T: Material + Clone + Copy + 'static> Hittable for Sphere<T> {
             }
         };
 
-        // Set the fields of the hit record.
+        // Set the fields of the hit record. Dividing by the signed radius makes the normal of a
+        // sphere with a negative radius point inward.
         let point = ray.at(root);
         let outward_normal = (point - self.center) / self.radius;
         let (front_face, normal) = set_face_normal(ray, outward_normal);
-        return (
-            HitRecord::new(true, point, normal, front_face, root),
-            Box::new(self.material),
-        );
+        let (u, v) = get_sphere_uv(&((point - self.center) / self.radius.abs()));
+        return HitRecord::new(true, point, normal, front_face, root, u, v, self.material);
+    }
+    /// The bounding box of a `Sphere` extends `radius` from the center along every axis, for a
+    /// negative radius as well.
+    fn bounding_box(&self) -> Aabb {
+        let radius: f64 = self.radius.abs();
+        let radius_vector: Vec3 = Vec3::new(radius, radius, radius);
+        return Aabb::from_points(self.center - radius_vector, self.center + radius_vector);
+    }
+}
+
+/// Given a `point` on the unit sphere centered at the origin, compute its surface coordinates
+/// (u,v) in [0,1]. With $\theta$ the angle up from $-y$ and $\phi$ the angle around the y-axis
+/// from $-x$, the coordinates are
+/// $$ u = \frac{\phi}{2 \pi}, \quad v = \frac{\theta}{\pi}. $$
+pub fn get_sphere_uv(point: &Point) -> (f64, f64) {
+    let theta: f64 = (-point.y).acos();
+    let phi: f64 = (-point.z).atan2(point.x) + std::f64::consts::PI;
+    let u: f64 = phi / (2.0 * std::f64::consts::PI);
+    let v: f64 = theta / std::f64::consts::PI;
+    return (u, v);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::utils::POSITIVE_INFINITY;
+
+    fn hit_from(origin: Point, sphere: &Sphere) -> HitRecord {
+        let ray: Ray = Ray::new(origin, sphere.center - origin, 0.0);
+        return sphere.ray_hit(&ray, Interval::new(0.001, POSITIVE_INFINITY));
+    }
+
+    #[test]
+    fn north_pole_and_plus_x_equator_have_known_uv() {
+        let sphere: Sphere = Sphere::new(Point::new(1.0, 2.0, 3.0), 2.0, MaterialId(0));
+
+        let pole: HitRecord = hit_from(Point::new(1.0, 10.0, 3.0), &sphere);
+        assert!(pole.hit);
+        assert!((pole.v - 1.0).abs() < 1e-12);
+
+        let equator: HitRecord = hit_from(Point::new(10.0, 2.0, 3.0), &sphere);
+        assert!(equator.hit);
+        assert!((equator.u - 0.5).abs() < 1e-12);
+        assert!((equator.v - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn negative_radius_sphere_has_inward_normals() {
+        let center: Point = Point::new(0.0, 0.0, -3.0);
+        let hollow: Sphere = Sphere::new(center, -1.0, MaterialId(0));
+        let origin: Point = Point::new(0.0, 0.0, 0.0);
+
+        // Seen from outside, the ray hits the back of the surface, whose outward normal points
+        // toward the center.
+        let hit_record: HitRecord = hit_from(origin, &hollow);
+        assert!(hit_record.hit);
+        assert!(!hit_record.front_face);
+        assert_eq!(hit_record.point, Point::new(0.0, 0.0, -2.0));
+        let outward_normal: Vec3 = -hit_record.normal;
+        assert_eq!(outward_normal, Vec3::new(0.0, 0.0, -1.0));
+        assert!(outward_normal.dot(&(center - hit_record.point)) > 0.0);
+
+        // The same sphere with a positive radius is hit on the front.
+        let solid: Sphere = Sphere::new(center, 1.0, MaterialId(0));
+        let hit_record: HitRecord = hit_from(origin, &solid);
+        assert!(hit_record.front_face);
+        assert_eq!(hit_record.normal, Vec3::new(0.0, 0.0, 1.0));
+        assert_eq!(hollow.bounding_box(), solid.bounding_box());
     }
 }
@@ -0,0 +1,81 @@
+use super::aabb::Aabb;
+use super::hittables::Hittable;
+use super::record::HitRecord;
+use crate::raycaster::ray::Ray;
+use crate::transform::transform::Mat4;
+use crate::util::utils::Interval;
+use crate::vector::vector::Point;
+use std::sync::Arc;
+
+/// A `Transform` instance places the wrapped `object` transformed by an affine `matrix`, which
+/// can combine translations, rotations and scalings. Instead of transforming the object, the
+/// incoming rays are transformed by the `inverse`. Normals are transformed by the inverse
+/// transpose, the `normal_matrix`, so they stay orthogonal to the surface under non-uniform
+/// scaling. The derived matrices and the bounding box are computed once in the constructor.
+pub struct Transform {
+    pub object: Arc<dyn Hittable>,
+    pub matrix: Mat4,
+    pub inverse: Mat4,
+    pub normal_matrix: Mat4,
+    pub bbox: Aabb,
+}
+
+impl Transform {
+    /// Create new `Transform` instance. If `matrix` is singular, it cannot be undone, so the
+    /// error is logged and the object is left untransformed.
+    pub fn new(object: Arc<dyn Hittable>, matrix: Mat4) -> Self {
+        let (matrix, inverse) = match matrix.inverse() {
+            Some(inverse) => (matrix, inverse),
+            None => {
+                log::error!("Transform matrix {matrix:?} is singular, it is ignored");
+                (Mat4::identity(), Mat4::identity())
+            }
+        };
+
+        // The bounding box is the box around the eight transformed corners of the object's box.
+        let object_bbox: Aabb = object.bounding_box();
+        let mut bbox: Aabb = Aabb::empty();
+        for x in [object_bbox.x.min, object_bbox.x.max] {
+            for y in [object_bbox.y.min, object_bbox.y.max] {
+                for z in [object_bbox.z.min, object_bbox.z.max] {
+                    let corner: Point = matrix.mul_point(Point::new(x, y, z));
+                    bbox = Aabb::surrounding(&bbox, &Aabb::from_points(corner, corner));
+                }
+            }
+        }
+
+        Self {
+            object,
+            matrix,
+            inverse,
+            normal_matrix: inverse.transpose(),
+            bbox,
+        }
+    }
+}
+
+impl Hittable for Transform {
+    /// Transform the ray into the frame of the object with the inverse matrix, check for a hit
+    /// there, and transform the hit point and normal back into the world. Since the ray direction
+    /// is not normalised, the ray parameter of the hit is the same in both frames.
+    fn ray_hit(&self, ray: &Ray, ray_parameter_interval: Interval) -> HitRecord {
+        let object_ray: Ray = Ray::new(
+            self.inverse.mul_point(ray.origin),
+            self.inverse.mul_vector(ray.direction),
+            ray.time,
+        );
+        let mut hit_record: HitRecord = self.object.ray_hit(&object_ray, ray_parameter_interval);
+        if hit_record.hit {
+            hit_record.point = self.matrix.mul_point(hit_record.point);
+            hit_record.normal = self
+                .normal_matrix
+                .mul_vector(hit_record.normal)
+                .unit_vector();
+        }
+        return hit_record;
+    }
+    /// The bounding box around the transformed bounding box of the object.
+    fn bounding_box(&self) -> Aabb {
+        return self.bbox;
+    }
+}
@@ -0,0 +1,67 @@
+use super::aabb::Aabb;
+use super::hittables::Hittable;
+use super::record::HitRecord;
+use crate::raycaster::ray::Ray;
+use crate::util::utils::Interval;
+use crate::vector::vector::Vec3;
+use std::sync::Arc;
+
+/// A `Translate` instance places the wrapped `object` displaced by `offset`. Instead of moving
+/// the object, the incoming rays are moved in the opposite direction.
+pub struct Translate {
+    pub object: Arc<dyn Hittable>,
+    pub offset: Vec3,
+}
+
+impl Translate {
+    /// Create new `Translate` instance.
+    pub fn new(object: Arc<dyn Hittable>, offset: Vec3) -> Self {
+        Self { object, offset }
+    }
+}
+
+impl Hittable for Translate {
+    /// Move the ray origin by `-offset` into the frame of the object, check for a hit there, and
+    /// move the hit point back by `offset`. A translation does not change the normal.
+    fn ray_hit(&self, ray: &Ray, ray_parameter_interval: Interval) -> HitRecord {
+        let offset_ray: Ray = Ray::new(ray.origin - self.offset, ray.direction, ray.time);
+        let mut hit_record: HitRecord = self.object.ray_hit(&offset_ray, ray_parameter_interval);
+        if hit_record.hit {
+            hit_record.point += self.offset;
+        }
+        return hit_record;
+    }
+    /// The bounding box of the object, displaced by `offset`.
+    fn bounding_box(&self) -> Aabb {
+        return self.object.bounding_box().translate(self.offset);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hittables::sphere::Sphere;
+    use crate::materials::arena::MaterialId;
+    use crate::util::utils::POSITIVE_INFINITY;
+    use crate::vector::vector::Point;
+
+    #[test]
+    fn translated_sphere_is_hit_at_its_new_position() {
+        let sphere: Arc<dyn Hittable> =
+            Arc::new(Sphere::new(Point::new(0.0, 0.0, 0.0), 1.0, MaterialId(0)));
+        let translated: Translate = Translate::new(sphere, Vec3::new(5.0, 0.0, 0.0));
+        let interval: Interval = Interval::new(0.001, POSITIVE_INFINITY);
+
+        let origin: Point = Point::new(5.0, 0.0, 10.0);
+        let aimed: Ray = Ray::new(origin, Point::new(5.0, 0.0, 0.0) - origin, 0.0);
+        let hit_record: HitRecord = translated.ray_hit(&aimed, interval);
+        assert!(hit_record.hit);
+        assert!((hit_record.point - Point::new(5.0, 0.0, 1.0)).length() < 1e-12);
+        assert!((hit_record.normal - Vec3::new(0.0, 0.0, 1.0)).length() < 1e-12);
+
+        // The original position is empty now.
+        let old: Ray = Ray::new(Point::new(0.0, 0.0, 10.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        assert!(!translated.ray_hit(&old, interval).hit);
+        assert_eq!(translated.bounding_box().x, Interval::new(4.0, 6.0));
+    }
+}
@@ -0,0 +1,199 @@
+use super::aabb::Aabb;
+use super::hittables::Hittable;
+use super::record::{set_face_normal, HitRecord};
+use crate::materials::arena::MaterialId;
+use crate::raycaster::ray::Ray;
+use crate::util::utils::Interval;
+use crate::vector::vector::{Point, Vec3};
+
+/// A `Triangle` is defined by its three vertices in 3D space. The orientation of the vertices
+/// determines the outward normal through the right hand rule. Triangles of a smooth mesh can
+/// have a unit `normals` vector at every vertex, which are interpolated over the triangle to hide
+/// the facets. Without vertex normals, the geometric normal of the triangle is used.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Triangle {
+    pub v0: Point,
+    pub v1: Point,
+    pub v2: Point,
+    pub normals: Option<[Vec3; 3]>,
+    pub material: MaterialId,
+}
+
+impl Triangle {
+    /// Create new `Triangle` instance.
+    pub fn new(v0: Point, v1: Point, v2: Point, material: MaterialId) -> Self {
+        Self {
+            v0,
+            v1,
+            v2,
+            normals: None,
+            material,
+        }
+    }
+    /// Create new `Triangle` instance with the given unit `normals` at the vertices `v0`, `v1`
+    /// and `v2`, in that order.
+    pub fn with_normals(
+        v0: Point,
+        v1: Point,
+        v2: Point,
+        normals: [Vec3; 3],
+        material: MaterialId,
+    ) -> Self {
+        Self {
+            v0,
+            v1,
+            v2,
+            normals: Some(normals),
+            material,
+        }
+    }
+    /// Get the outward normal at the point with barycentric coordinates (u,v). With vertex normals,
+    /// this is the interpolation $(1 - u - v) \vec{n}_0 + u \vec{n}_1 + v \vec{n}_2$, normalised.
+    /// Otherwise, it is the geometric normal $\vec{e}_1 \times \vec{e}_2$, normalised.
+    pub fn normal_at(&self, u: f64, v: f64) -> Vec3 {
+        match self.normals {
+            Some([n0, n1, n2]) => {
+                return (n0 * (1.0 - u - v) + n1 * u + n2 * v).unit_vector();
+            }
+            None => {
+                return (self.v1 - self.v0)
+                    .cross(&(self.v2 - self.v0))
+                    .unit_vector();
+            }
+        }
+    }
+}
+
+impl Hittable for Triangle {
+    /// Implementation of the Möller–Trumbore algorithm. A point in the triangle can be written
+    /// in barycentric coordinates as $\vec{v}_0 + u \vec{e}_1 + v \vec{e}_2$, with
+    /// $\vec{e}_1 = \vec{v}_1 - \vec{v}_0$ and $\vec{e}_2 = \vec{v}_2 - \vec{v}_0$. Setting this
+    /// equal to the ray gives a linear system in $(t, u, v)$, which is solved with Cramer's rule.
+    /// The point lies inside the triangle if $u, v \geq 0$ and $u + v \leq 1$.
+    /// See: <https://en.wikipedia.org/wiki/M%C3%B6ller%E2%80%93Trumbore_intersection_algorithm>
+    fn ray_hit(&self, ray: &Ray, ray_parameter_interval: Interval) -> HitRecord {
+        let epsilon: f64 = 1e-8;
+        let edge_1: Vec3 = self.v1 - self.v0;
+        let edge_2: Vec3 = self.v2 - self.v0;
+
+        // If the determinant is (close to) zero, the ray runs parallel to the triangle.
+        let ray_cross_edge_2: Vec3 = ray.direction.cross(&edge_2);
+        let determinant: f64 = edge_1.dot(&ray_cross_edge_2);
+        if determinant.abs() < epsilon {
+            return HitRecord::default();
+        }
+        let inverse_determinant: f64 = 1.0 / determinant;
+
+        // Compute the barycentric coordinates and check they lie within the triangle.
+        let barycentric_interval: Interval = Interval::new(0.0, 1.0);
+        let origin_offset: Vec3 = ray.origin - self.v0;
+        let u: f64 = inverse_determinant * origin_offset.dot(&ray_cross_edge_2);
+        if !barycentric_interval.contains(u) {
+            return HitRecord::default();
+        }
+        let offset_cross_edge_1: Vec3 = origin_offset.cross(&edge_1);
+        let v: f64 = inverse_determinant * ray.direction.dot(&offset_cross_edge_1);
+        if !barycentric_interval.contains(v) || !barycentric_interval.contains(u + v) {
+            return HitRecord::default();
+        }
+
+        // Check if the hit lies in the acceptable range.
+        let root: f64 = inverse_determinant * edge_2.dot(&offset_cross_edge_1);
+        if !ray_parameter_interval.surrounds(root) {
+            return HitRecord::default();
+        }
+
+        // Set the fields of the hit record, with the interpolated normal for smooth triangles.
+        let point = ray.at(root);
+        let outward_normal = self.normal_at(u, v);
+        let (front_face, normal) = set_face_normal(ray, outward_normal);
+        return HitRecord::new(true, point, normal, front_face, root, u, v, self.material);
+    }
+    /// The bounding box of a `Triangle` is the smallest box containing its three vertices.
+    fn bounding_box(&self) -> Aabb {
+        return Aabb::surrounding(
+            &Aabb::from_points(self.v0, self.v1),
+            &Aabb::from_points(self.v0, self.v2),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::utils::POSITIVE_INFINITY;
+
+    fn triangle() -> Triangle {
+        return Triangle::new(
+            Point::new(0.0, 0.0, -1.0),
+            Point::new(1.0, 0.0, -1.0),
+            Point::new(0.0, 1.0, -1.0),
+            MaterialId(0),
+        );
+    }
+
+    fn shoot(origin: Point, direction: Vec3) -> HitRecord {
+        let ray: Ray = Ray::new(origin, direction, 0.0);
+        return triangle().ray_hit(&ray, Interval::new(0.001, POSITIVE_INFINITY));
+    }
+
+    #[test]
+    fn ray_through_interior_hits() {
+        let hit_record: HitRecord = shoot(Point::new(0.25, 0.25, 0.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(hit_record.hit);
+        assert!((hit_record.ray_parameter - 1.0).abs() < 1e-12);
+        assert!((hit_record.u - 0.25).abs() < 1e-12);
+        assert!((hit_record.v - 0.25).abs() < 1e-12);
+        assert_eq!(hit_record.normal, Vec3::new(0.0, 0.0, 1.0));
+        assert!(hit_record.front_face);
+    }
+
+    #[test]
+    fn ray_grazing_past_an_edge_misses() {
+        // Just beyond the hypotenuse, where u + v is slightly above one.
+        let hit_record: HitRecord = shoot(
+            Point::new(0.5 + 1e-9, 0.5 + 1e-9, 0.0),
+            Vec3::new(0.0, 0.0, -1.0),
+        );
+        assert!(!hit_record.hit);
+        // Just beyond the edge along the y-axis, where u is slightly below zero.
+        let hit_record: HitRecord = shoot(Point::new(-1e-9, 0.5, 0.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(!hit_record.hit);
+    }
+
+    #[test]
+    fn ray_parallel_to_the_triangle_misses() {
+        let hit_record: HitRecord = shoot(Point::new(-1.0, 0.25, -1.0), Vec3::new(1.0, 0.0, 0.0));
+        assert!(!hit_record.hit);
+    }
+
+    #[test]
+    fn centroid_normal_is_the_average_of_the_vertex_normals() {
+        let normals: [Vec3; 3] = [
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(1.0, 0.0, 1.0).unit_vector(),
+            Vec3::new(0.0, 1.0, 1.0).unit_vector(),
+        ];
+        let smooth: Triangle = Triangle::with_normals(
+            Point::new(0.0, 0.0, -1.0),
+            Point::new(1.0, 0.0, -1.0),
+            Point::new(0.0, 1.0, -1.0),
+            normals,
+            MaterialId(0),
+        );
+        let centroid: Point = Point::new(1.0 / 3.0, 1.0 / 3.0, -1.0);
+        let ray: Ray = Ray::new(
+            centroid + Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(0.0, 0.0, -1.0),
+            0.0,
+        );
+        let hit_record: HitRecord = smooth.ray_hit(&ray, Interval::new(0.001, POSITIVE_INFINITY));
+        assert!(hit_record.hit);
+        let expected: Vec3 = ((normals[0] + normals[1] + normals[2]) / 3.0).unit_vector();
+        assert!((hit_record.normal - expected).length() < 1e-12);
+        assert_ne!(
+            hit_record.normal,
+            triangle().normal_at(1.0 / 3.0, 1.0 / 3.0)
+        );
+    }
+}
@@ -0,0 +1,26 @@
+// The explicit `return` statements, the `foo::foo` module layout and the long `Camera`
+// constructor are deliberate style choices of this crate.
+#![allow(clippy::needless_return)]
+#![allow(clippy::module_inception)]
+#![allow(clippy::too_many_arguments)]
+
+pub mod animation;
+pub mod camera;
+pub mod checkpoint;
+pub mod cli;
+pub mod environment;
+pub mod error;
+pub mod hittables;
+pub mod lights;
+pub mod logger;
+pub mod materials;
+pub mod mesh;
+pub mod postprocess;
+pub mod raycaster;
+#[cfg(feature = "serde")]
+pub mod scene;
+pub mod scenes;
+pub mod textures;
+pub mod transform;
+pub mod util;
+pub mod vector;
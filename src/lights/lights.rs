@@ -0,0 +1,83 @@
+use crate::vector::vector::{Color, Point};
+
+/// A `Light` is a light source that is not part of the world, so rays never hit it. Instead, the
+/// light arriving from it is added at every diffuse hit by casting a shadow ray towards its
+/// `position`, which is known as next-event estimation. Lights are owned by the `Camera` which is
+/// shared between the rendering threads, hence they must be `Send + Sync`.
+pub trait Light: Send + Sync {
+    fn position(&self) -> Point;
+    fn intensity_toward(&self, point: &Point) -> Color;
+}
+
+/// A `PointLight` emits light of the same `intensity` in all directions from a single `position`.
+/// The irradiance it causes falls off with the square of the distance. Small bright light sources
+/// converge much faster as a `PointLight` than as a small sphere of `DiffuseLight`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PointLight {
+    pub position: Point,
+    pub intensity: Color,
+}
+
+impl PointLight {
+    /// Create new instance of `PointLight`.
+    pub fn new(position: Point, intensity: Color) -> Self {
+        Self {
+            position,
+            intensity,
+        }
+    }
+}
+
+impl Light for PointLight {
+    /// Get the position of the light.
+    fn position(&self) -> Point {
+        return self.position;
+    }
+    /// A `PointLight` emits its `intensity` towards every point.
+    fn intensity_toward(&self, _point: &Point) -> Color {
+        return self.intensity;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera::camera::Camera;
+    use crate::environment::environment::GradientSky;
+    use crate::hittables::hittables::Hittables;
+    use crate::hittables::plane::Plane;
+    use crate::materials::materials::Lambertian;
+    use crate::raycaster::ray::Ray;
+    use crate::vector::vector::Vec3;
+    use std::sync::Arc;
+
+    #[test]
+    fn unoccluded_point_light_above_a_surface_brightens_it() {
+        let black: Color = Color::new(0.0, 0.0, 0.0);
+        let mut world: Hittables = Hittables::init();
+        let gray = world.add_material(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        world.add(Arc::new(Plane::new(
+            Point::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            gray,
+        )));
+        let ray: Ray = Ray::new(Point::new(0.0, 3.0, 0.0), Vec3::new(0.0, -1.0, 0.0), 0.0);
+
+        let unlit: Camera = Camera::builder()
+            .environment(Box::new(GradientSky::new(black, black)))
+            .build();
+        assert_eq!(ray.ray_color(&world, 10, &unlit), black);
+
+        let light: PointLight =
+            PointLight::new(Point::new(0.0, 2.0, 0.0), Color::new(4.0, 4.0, 4.0));
+        let lit: Camera = Camera::builder()
+            .environment(Box::new(GradientSky::new(black, black)))
+            .light(Box::new(light))
+            .build();
+        let color: Color = ray.ray_color(&world, 10, &lit);
+        // Straight below the light at distance 2, the irradiance is 4 / 2^2 = 1, of which the
+        // surface reflects its albedo divided by pi toward the ray. Scattered rays see black.
+        let expected: f64 = 0.5 / std::f64::consts::PI;
+        assert!((color - Color::new(expected, expected, expected)).length() < 1e-12);
+    }
+}
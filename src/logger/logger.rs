@@ -1,22 +1,37 @@
-use env_logger::Builder;
+use env_logger::{Builder, Env, Target};
+use log::LevelFilter;
 use std::io::Write;
 
 const PKG_NAME: &str = env!("CARGO_PKG_NAME");
 
-/// Create log file and set up the logger.
-pub fn init_logging() {
-    // Create and open log file.
-    let log_file =
-        std::fs::File::create(format!("{}.log", PKG_NAME)).expect("Error creating log file");
-    // Only one thread allowed.
-    let log_file = std::sync::Mutex::new(log_file);
+/// Destination of the log records. `File` writes to `{pkg}.log` in the working directory,
+/// `Stderr` writes to the standard error stream, which avoids a stray log file in CI, and `Pipe`
+/// writes to any other writer.
+pub enum LogSink {
+    File,
+    Stderr,
+    Pipe(Box<dyn Write + Send + 'static>),
+}
+
+/// Set up the logger to write all records of at least `level` to `sink`. If the `RUST_LOG`
+/// environment variable is set, it takes precedence over `level`, e.g. `RUST_LOG=warn`.
+/// See: <https://docs.rs/env_logger/latest/env_logger/#enabling-logging>
+pub fn init_logging(level: LevelFilter, sink: LogSink) -> std::io::Result<()> {
+    // Create and open log file, if needed.
+    let target: Target = match sink {
+        LogSink::File => Target::Pipe(Box::new(std::fs::File::create(format!(
+            "{}.log",
+            PKG_NAME
+        ))?)),
+        LogSink::Stderr => Target::Stderr,
+        LogSink::Pipe(writer) => Target::Pipe(writer),
+    };
 
     // Set up the logger
     Builder::new()
-        .format(move |_buf, record| {
-            let mut log_file = log_file.lock().unwrap();
+        .format(|buf, record| {
             writeln!(
-                log_file,
+                buf,
                 "{} [{}] - {}:{} - {}",
                 chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
                 record.level(),
@@ -25,6 +40,45 @@ pub fn init_logging() {
                 record.args()
             )
         })
-        .filter(None, log::LevelFilter::Info)
+        .filter(None, level)
+        .parse_env(Env::default())
+        .target(target)
         .init();
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// A sink that keeps the written log in memory, shared with the test.
+    #[derive(Clone)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            return Ok(buf.len());
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            return Ok(());
+        }
+    }
+
+    // The logger is global, so this is the only test that sets it up.
+    #[test]
+    fn warn_level_suppresses_info_records() {
+        let buffer: SharedBuffer = SharedBuffer(Arc::new(Mutex::new(Vec::new())));
+        init_logging(LevelFilter::Warn, LogSink::Pipe(Box::new(buffer.clone()))).unwrap();
+
+        log::info!("informational record from the logger test");
+        log::warn!("warning record from the logger test");
+        log::logger().flush();
+
+        let log: String = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        assert!(!log.contains("informational record from the logger test"));
+        assert!(log.contains("[WARN]"));
+        assert!(log.contains("warning record from the logger test"));
+    }
 }
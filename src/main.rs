@@ -1,170 +1,221 @@
-pub mod camera;
-pub mod hittables;
-pub mod logger;
-pub mod materials;
-pub mod raycaster;
-pub mod util;
-pub mod vector;
+// The explicit `return` statements are a deliberate style choice of this crate, see `lib.rs`.
+#![allow(clippy::needless_return)]
 
 // Internal files
-use camera::camera::Camera;
-use hittables::hittables::Hittables;
-use hittables::sphere::Sphere;
-use logger::logger::init_logging;
-use materials::materials::{Dielectric, Lambertian, Metal};
-use util::utils;
-use vector::vector::{Color, Point, Vec3};
+use raytracing::camera::camera::Camera;
+use raytracing::cli::cli::Args;
+use raytracing::error::error::RenderError;
+use raytracing::hittables::bvh::BvhNode;
+use raytracing::hittables::hittables::Hittables;
+use raytracing::logger::logger::{init_logging, LogSink};
+use raytracing::materials::arena::MaterialArena;
+#[cfg(feature = "serde")]
+use raytracing::scene::scene::{load_scene, load_scene_toml};
+use raytracing::scenes::scenes::random_spheres;
+use raytracing::util::utils;
+use raytracing::util::utils::PpmFormat;
+#[cfg(feature = "preview")]
+use raytracing::vector::vector::Color;
+use raytracing::vector::vector::{Point, Vec3};
 // Standard library
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+// External crates
+use clap::Parser;
+use signal_hook::consts::SIGINT;
 
 fn main() {
+    // Parse command line arguments, this exits with an error message on invalid arguments.
+    let args: Args = Args::parse();
+
     // Initialise logger
-    init_logging();
+    let log_sink: LogSink = if args.log_stderr {
+        LogSink::Stderr
+    } else {
+        LogSink::File
+    };
+    if let Err(err) = init_logging(args.log_level, log_sink) {
+        eprintln!("Error creating log file: {err}");
+        std::process::exit(1);
+    }
 
-    // Create result directory if it doesn't exist.
-    utils::create_result_dir();
+    // Render, only here it is decided that an error ends the program.
+    if let Err(err) = run(&args) {
+        log::error!("Error rendering: {err}");
+        std::process::exit(1);
+    }
+}
 
-    // Create and open file
-    let mut file = match std::fs::File::create("result/image.ppm") {
-        Ok(f) => f,
-        Err(err) => {
-            log::error!("Error creating or opening `result/image.ppm` file: {err}");
-            std::process::exit(1);
-        }
+/// Load the camera and the world from the scene file of the `args`, if one is given. Files with
+/// the `toml` extension are read as TOML, all others as JSON.
+#[cfg(feature = "serde")]
+fn load_scene_file(args: &Args) -> Result<Option<(Camera, Hittables)>, RenderError> {
+    let Some(path) = &args.scene else {
+        return Ok(None);
     };
+    let is_toml: bool = path
+        .extension()
+        .is_some_and(|extension| extension == "toml");
+    if is_toml {
+        return Ok(Some(load_scene_toml(path)?));
+    } else {
+        return Ok(Some(load_scene(path)?));
+    }
+}
 
-    // Define aspect ratio, which is defined as the width/height.
-    let aspect_ratio: f64 = 16.0 / 9.0;
-    // Define image width
-    // 480p resolution (854 x 480) with 16:9 aspect ratio
-    //let image_width = 854;
-    // 1080p resolution (1080 x 1920) with 16:9 aspect ratio
-    //let image_width = 1920;
-    let image_width = 400;
-    // Define center of camera
-    let camera_center: Point = Point::new(13.0, 2.0, 3.0);
-    // Define the amount of samples per pixel
-    let samples_per_pixel: i32 = 100;
-    // Define maximum amount of bounces the ray can do.
-    let max_depth: i32 = 50;
-    // Define vfov (vertical field of view)
-    let vfov: f64 = 20.0;
-    // Define the point we are looking at (center if the canvas)
-    let look_at: Point = Point::new(0.0, 0.0, 0.0);
-    // Define vup, the Camera relative up direction
-    let vup: Vec3 = Vec3::new(0.0, 1.0, 0.0);
-    // Define defocus parameters
-    let defocus_angle: f64 = 0.6;
-    let focus_dist: f64 = 10.0;
-
-    // Define Camera instance
-    let camera: Camera = Camera::initialize(
-        aspect_ratio,
-        image_width,
-        camera_center,
-        samples_per_pixel,
-        max_depth,
-        vfov,
-        look_at,
-        vup,
-        defocus_angle,
-        focus_dist,
-    );
-
-    // Define the world -- cover image
-    let mut world: Hittables = Hittables::init();
-
-    let material_ground = Lambertian::new(Color::new(0.5, 0.5, 0.5));
-    world.add(Box::new(Sphere::new(
-        Point::new(0.0, -1000.0, 0.0),
-        1000.0,
-        material_ground,
-    )));
-
-    for a in -11..11 {
-        for b in -11..11 {
-            let choose_mat: f64 = utils::get_random();
-            let sphere_center: Point = Point::new(
-                a as f64 + 0.9 * utils::get_random(),
-                0.2,
-                b as f64 + 0.9 * utils::get_random(),
-            );
-
-            if (sphere_center - Point::new(4.0, 0.2, 0.0)).length() > 0.9 {
-                if choose_mat < 0.8 {
-                    // Diffuse
-                    let albedo = Color::get_random_vector() * Color::get_random_vector();
-                    let sphere_material = Lambertian::new(albedo);
-                    world.add(Box::new(Sphere::new(sphere_center, 0.2, sphere_material)));
-                } else if choose_mat < 0.95 {
-                    // Metal
-                    let albedo = Color::get_random_vector_in_range(0.5, 1.0);
-                    let fuzz = utils::get_random_in_range(0.5, 1.0);
-                    let sphere_material = Metal::new(albedo, fuzz);
-                    world.add(Box::new(Sphere::new(sphere_center, 0.2, sphere_material)));
-                } else {
-                    // Glass
-                    let sphere_material = Dielectric::new(Color::new(1.0, 1.0, 1.0), 1.5);
-                    world.add(Box::new(Sphere::new(sphere_center, 0.2, sphere_material)));
-                }
-            }
+/// Without the `serde` feature, scene files cannot be read and the cover image is rendered.
+#[cfg(not(feature = "serde"))]
+fn load_scene_file(_args: &Args) -> Result<Option<(Camera, Hittables)>, RenderError> {
+    return Ok(None);
+}
+
+/// Render the scene described by the command line `args`.
+fn run(args: &Args) -> Result<(), RenderError> {
+    // Load the camera and the world from the scene file, or create the cover image.
+    let (mut camera, mut world): (Camera, Hittables) = match load_scene_file(args)? {
+        Some(scene) => scene,
+        // Without a seed, the cover scene is different every time.
+        None => {
+            let seed: u64 = args.seed.unwrap_or_else(rand::random);
+            (cover_camera(args), random_spheres(seed))
         }
-    }
+    };
+    camera.seed = args.seed;
+    camera.threads = args.threads;
 
-    let material_1 = Dielectric::new(Color::new(1.0, 1.0, 1.0), 1.5);
-    world.add(Box::new(Sphere::new(
-        Point::new(0.0, 1.0, 0.0),
-        1.0,
-        material_1,
-    )));
-
-    let material_2 = Lambertian::new(Color::new(0.4, 0.2, 0.1));
-    world.add(Box::new(Sphere::new(
-        Point::new(-4.0, 1.0, 0.0),
-        1.0,
-        material_2,
-    )));
-
-    let material_3 = Metal::new(Color::new(0.7, 0.6, 0.5), 0.0);
-    world.add(Box::new(Sphere::new(
-        Point::new(4.0, 1.0, 0.0),
-        1.0,
-        material_3,
-    )));
+    // Organise the world in a bounding volume hierarchy to speed up finding the closest hit, the
+    // materials stay with the top level of the world.
+    let materials: MaterialArena = world.take_materials();
+    let world: Hittables =
+        Hittables::with_materials(vec![Arc::new(BvhNode::from_hittables(world))], materials);
 
     // Define the world -- Ground ball, Glass ball, Matt ball and Metal ball.
-    //let material_ground = Lambertian::new(Color::new(0.8, 0.8, 0.0));
-    //let material_center = Lambertian::new(Color::new(0.1, 0.2, 0.5));
-    //let material_left = Dielectric::new(Color::new(1.0, 1.0, 1.0), 1.50);
-    //let material_bubble = Dielectric::new(Color::new(1.0, 1.0, 1.0), 1.0 / 1.50);
-    //let material_right = Metal::new(Color::new(0.8, 0.6, 0.2), 1.0);
-    //
     //let mut world: Hittables = Hittables::init();
-    //world.add(Box::new(Sphere::new(
+    //
+    //let material_ground = world.add_material(Lambertian::new(Color::new(0.8, 0.8, 0.0)));
+    //let material_center = world.add_material(Lambertian::new(Color::new(0.1, 0.2, 0.5)));
+    //let material_left = world.add_material(Dielectric::new(Color::new(1.0, 1.0, 1.0), 1.50));
+    //let material_bubble =
+    //    world.add_material(Dielectric::new(Color::new(1.0, 1.0, 1.0), 1.0 / 1.50));
+    //let material_right = world.add_material(Metal::new(Color::new(0.8, 0.6, 0.2), 1.0));
+    //
+    //world.add(Arc::new(Sphere::new(
     //    Point::new(0.0, -100.5, -1.0),
     //    100.0,
     //    material_ground,
     //)));
-    //world.add(Box::new(Sphere::new(
+    //world.add(Arc::new(Sphere::new(
     //    Point::new(0.0, 0.0, -1.2),
     //    0.5,
     //    material_center,
     //)));
-    //world.add(Box::new(Sphere::new(
+    //world.add(Arc::new(Sphere::new(
     //    Point::new(-1.0, 0.0, -1.0),
     //    0.5,
     //    material_left,
     //)));
-    //world.add(Box::new(Sphere::new(
+    //world.add(Arc::new(Sphere::new(
     //    Point::new(-1.0, 0.0, -1.0),
     //    0.4,
     //    material_bubble,
     //)));
-    //world.add(Box::new(Sphere::new(
+    //world.add(Arc::new(Sphere::new(
     //    Point::new(1.0, 0.0, -1.0),
     //    0.5,
     //    material_right,
     //)));
     //
+    // Create output directory if it doesn't exist.
+    utils::create_parent_dir(&args.output)?;
+
+    // Create and open file
+    let mut writer = create_output(&args.output)?;
+
+    // Ctrl-C stops the render early and the partial image is still written. A second Ctrl-C,
+    // while the flag is already set, ends the program right away.
+    let stop: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+    signal_hook::flag::register_conditional_shutdown(SIGINT, 1, stop.clone())?;
+    signal_hook::flag::register(SIGINT, stop.clone())?;
+
     // Render image
-    camera.render(&mut file, &world);
+    #[cfg(feature = "preview")]
+    if args.preview {
+        let buffer: Vec<Color> = camera.render_preview(&world)?;
+        camera.write_ppm(&mut writer, &buffer, PpmFormat::Binary)?;
+    } else {
+        camera.render_cancellable(&mut writer, &world, &stop, PpmFormat::Binary)?;
+    }
+    #[cfg(not(feature = "preview"))]
+    camera.render_cancellable(&mut writer, &world, &stop, PpmFormat::Binary)?;
+    flush_output(writer, &args.output)?;
+    if stop.load(Ordering::Relaxed) {
+        log::warn!("Render interrupted, skipping the depth and albedo passes");
+        return Ok(());
+    }
+
+    // Render the depth and albedo passes next to the image, if requested.
+    if let Some(max_distance) = args.depth {
+        let depth_path: PathBuf = args.output.with_file_name("depth.ppm");
+        let mut depth_writer = create_output(&depth_path)?;
+        camera.render_depth(&mut depth_writer, &world, max_distance, PpmFormat::Binary)?;
+        flush_output(depth_writer, &depth_path)?;
+    }
+    if args.albedo {
+        let albedo_path: PathBuf = args.output.with_file_name("albedo.ppm");
+        let mut albedo_writer = create_output(&albedo_path)?;
+        camera.render_albedo(&mut albedo_writer, &world, PpmFormat::Binary)?;
+        flush_output(albedo_writer, &albedo_path)?;
+    }
+    return Ok(());
+}
+
+/// Create the file at `path` and buffer the writes to it, the colors are written one pixel at a
+/// time. The path `-` writes to stdout instead, e.g. to pipe the image into another program. The
+/// progress bar and the log are written to stderr or a file, so they do not end up in the image.
+fn create_output(path: &Path) -> Result<BufWriter<Box<dyn Write>>, RenderError> {
+    if path == Path::new("-") {
+        return Ok(BufWriter::new(Box::new(std::io::stdout().lock())));
+    }
+    let file: File = File::create(path).map_err(|err| with_path(err, path))?;
+    return Ok(BufWriter::new(Box::new(file)));
+}
+
+/// Flush the remaining buffered writes of `writer` to the file at `path`.
+fn flush_output(mut writer: BufWriter<Box<dyn Write>>, path: &Path) -> Result<(), RenderError> {
+    writer.flush().map_err(|err| with_path(err, path))?;
+    return Ok(());
+}
+
+/// Add the `path` of the file to an I/O error, so that the error message says which file failed.
+fn with_path(err: std::io::Error, path: &Path) -> std::io::Error {
+    return std::io::Error::new(err.kind(), format!("`{}`: {err}", path.display()));
+}
+
+/// Create the camera of the cover image, with the resolution and quality of the command line
+/// `args`.
+fn cover_camera(args: &Args) -> Camera {
+    return Camera::builder()
+        // Aspect ratio, which is defined as the width/height.
+        .aspect_ratio(16.0 / 9.0)
+        // 480p resolution (854 x 480) with 16:9 aspect ratio: --width 854
+        // 1080p resolution (1080 x 1920) with 16:9 aspect ratio: --width 1920
+        .width(args.width)
+        .samples(args.samples)
+        .max_depth(args.max_depth)
+        // Camera position, the point it looks at (center of the canvas) and the camera relative
+        // up direction.
+        .center(Point::new(13.0, 2.0, 3.0))
+        .look_at(Point::new(0.0, 0.0, 0.0))
+        .vup(Vec3::new(0.0, 1.0, 0.0))
+        // Vertical field of view
+        .vfov(20.0)
+        // Defocus parameters
+        .defocus_angle(0.6)
+        .focus_dist(10.0)
+        // If the shutter opens and closes at the same time there is no motion blur.
+        .shutter(0.0, 0.0)
+        .build();
 }
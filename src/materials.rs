@@ -1 +1,2 @@
+pub mod arena;
 pub mod materials;
@@ -0,0 +1,82 @@
+use super::materials::Material;
+use std::sync::Arc;
+
+/// Index of a material in a `MaterialArena`. Hittables store the index of their material instead
+/// of the material itself, so objects with the same material share a single copy of it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MaterialId(pub usize);
+
+/// Structure that stores every material of a scene once. Adding a material returns its
+/// `MaterialId`, which can be given to as many hittables as needed, and a `HitRecord` carries the
+/// id of the material that was hit, which is looked up here when the ray is colored.
+#[derive(Clone, Default)]
+pub struct MaterialArena {
+    materials: Vec<Arc<dyn Material>>,
+}
+
+impl MaterialArena {
+    /// Initialise empty instance of `MaterialArena`.
+    pub fn new() -> Self {
+        Self {
+            materials: Vec::new(),
+        }
+    }
+    /// Store the `material` in the arena and return its id.
+    pub fn add<T: Material + 'static>(&mut self, material: T) -> MaterialId {
+        self.materials.push(Arc::new(material));
+        return MaterialId(self.materials.len() - 1);
+    }
+    /// Get the material with the given `id`, or `None` if the id does not belong to this arena.
+    pub fn get(&self, id: MaterialId) -> Option<&dyn Material> {
+        return self.materials.get(id.0).map(|material| material.as_ref());
+    }
+    /// Get the number of materials in the arena.
+    pub fn len(&self) -> usize {
+        return self.materials.len();
+    }
+    /// Check if the arena has no materials.
+    pub fn is_empty(&self) -> bool {
+        return self.materials.is_empty();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hittables::hittables::{Hittable, Hittables};
+    use crate::hittables::record::HitRecord;
+    use crate::hittables::sphere::Sphere;
+    use crate::materials::materials::Lambertian;
+    use crate::raycaster::ray::Ray;
+    use crate::util::utils::{Interval, POSITIVE_INFINITY};
+    use crate::vector::vector::{Color, Point, Vec3};
+
+    #[test]
+    fn two_spheres_with_one_material_resolve_to_the_same_entry() {
+        let mut world: Hittables = Hittables::init();
+        let red: MaterialId = world.add_material(Lambertian::new(Color::new(0.8, 0.1, 0.1)));
+        world.add(Arc::new(Sphere::new(Point::new(-1.0, 0.0, -2.0), 0.5, red)));
+        world.add(Arc::new(Sphere::new(Point::new(1.0, 0.0, -2.0), 0.5, red)));
+
+        let materials: Vec<*const dyn Material> = [-1.0, 1.0]
+            .iter()
+            .map(|x| {
+                let ray: Ray = Ray::new(Point::new(*x, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+                let hit_record: HitRecord =
+                    world.ray_hit(&ray, Interval::new(0.001, POSITIVE_INFINITY));
+                let id: MaterialId = hit_record.material.unwrap();
+                assert_eq!(id, red);
+                return world.material(id).unwrap() as *const dyn Material;
+            })
+            .collect();
+        assert!(std::ptr::addr_eq(materials[0], materials[1]));
+
+        // Every added material gets an entry of its own.
+        let mut arena: MaterialArena = MaterialArena::new();
+        let first: MaterialId = arena.add(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        let second: MaterialId = arena.add(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+        assert_ne!(first, second);
+        assert_eq!(arena.len(), 2);
+        assert!(arena.get(MaterialId(2)).is_none());
+    }
+}
@@ -1,6 +1,8 @@
 use crate::hittables::record::HitRecord;
 use crate::raycaster::ray::Ray;
-use crate::util::utils::get_random;
+use crate::textures::textures::{SolidColor, Texture};
+use crate::util::utils;
+use crate::util::utils::{get_random, Interval};
 use crate::vector::vector::{Color, Vec3};
 use std::ops::Neg;
 
@@ -25,59 +27,85 @@ impl Scatter {
 }
 
 /// Any `Material` should implement what it means for a `Ray` to scatter on
-/// that material.
-pub trait Material {
+/// that material, and its `base_color` at a hit, e.g. its color without any lighting, which is
+/// used for the albedo pass. Materials that emit light implement `emitted`, which gets the ray
+/// that hit the material so that the emission may depend on the direction, by default a material
+/// emits no light. Diffuse materials return `true` from `is_diffuse`, so that the light of the
+/// `Light` sources is added at their hits. Materials are stored in the `MaterialArena` of the
+/// world which is shared between the rendering threads, hence they must be `Send + Sync`.
+pub trait Material: Send + Sync {
     fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Scatter;
+    fn base_color(&self, hit_record: &HitRecord) -> Color;
+    fn emitted(&self, _ray_in: &Ray, _hit_record: &HitRecord) -> Color {
+        return Color::new(0.0, 0.0, 0.0);
+    }
+    fn is_diffuse(&self) -> bool {
+        return false;
+    }
 }
 
 /// A Lambertian material is essentially a diffuse material. The material scatters light
-/// randomly according to a Lambertian distribution and attenuates according to the `albedo`
-/// color. Albedo is Latin for whiteness and in this context defines the fractional
-/// reflectance.
+/// randomly according to a Lambertian distribution and attenuates according to the albedo
+/// color given by the `texture` at the hit. Albedo is Latin for whiteness and in this context
+/// defines the fractional reflectance.
 #[derive(Clone, Copy, Debug)]
-pub struct Lambertian {
-    pub albedo: Color,
+pub struct Lambertian<T: Texture + Clone = SolidColor> {
+    pub texture: T,
 }
 
-impl Lambertian {
-    /// Create new instance of `Lambertian`
+impl Lambertian<SolidColor> {
+    /// Create new instance of `Lambertian` with the same `albedo` everywhere.
     pub fn new(albedo: Color) -> Self {
-        Self { albedo }
+        Self {
+            texture: SolidColor::new(albedo),
+        }
+    }
+}
+
+impl<T: Texture + Clone> Lambertian<T> {
+    /// Create new instance of `Lambertian` with the albedo given by `texture`.
+    pub fn from_texture(texture: T) -> Self {
+        Self { texture }
     }
 }
 
-impl Default for Lambertian {
+impl Default for Lambertian<SolidColor> {
     fn default() -> Self {
-        Self {
-            albedo: Color::new(0.0, 0.0, 0.0),
-        }
+        Self::new(Color::new(0.0, 0.0, 0.0))
     }
 }
 
-impl Material for Lambertian {
+impl<T: Texture + Clone> Material for Lambertian<T> {
     /// A `Lambertian` material scatters light back in a random direction following
-    /// a Lambertian distribution. We assume constant attenuation.
-    fn scatter(&self, _ray_in: &Ray, hit_record: &HitRecord) -> Scatter {
-        let scattering_direction = {
-            let tmp = hit_record.normal + Vec3::get_random_unit_vector();
-
-            // Catch the case where the normal vector and random vector happen to cancel
-            //each other out resulting a zero vector. In that case, the scattering
-            // direction should be the normal
-            if tmp.near_zero() {
-                hit_record.normal
-            } else {
-                tmp
-            }
-        };
+    /// a Lambertian distribution. We assume constant attenuation. The direction is importance
+    /// sampled with the cosine-weighted probability density $\cos \theta / \pi$, which cancels
+    /// the cosine and the $1 / \pi$ of the Lambertian BRDF, so the attenuation is just the albedo.
+    /// The classic direction, the normal plus a random unit vector, is zero when the random vector
+    /// is opposite to the normal, which had to be caught with `Vec3::near_zero` to avoid NaNs
+    /// further on. The cosine-weighted direction is a unit vector, so it needs no such check.
+    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Scatter {
+        let scattering_direction: Vec3 = Vec3::get_random_cosine_direction(hit_record.normal);
 
-        let scattered_ray = Ray::new(hit_record.point, scattering_direction);
+        let scattered_ray = Ray::new(hit_record.point, scattering_direction, ray_in.time);
+        let attenuation: Color = self
+            .texture
+            .value(hit_record.u, hit_record.v, &hit_record.point);
         return Scatter {
             did_scatter: true,
             ray: scattered_ray,
-            attenuation: self.albedo,
+            attenuation,
         };
     }
+    /// The base color of a `Lambertian` material is the albedo given by the `texture`.
+    fn base_color(&self, hit_record: &HitRecord) -> Color {
+        return self
+            .texture
+            .value(hit_record.u, hit_record.v, &hit_record.point);
+    }
+    /// A `Lambertian` material is diffuse.
+    fn is_diffuse(&self) -> bool {
+        return true;
+    }
 }
 
 /// A `Metal` material is defined by the fact that it reflects light. The color of the
@@ -102,7 +130,7 @@ impl Material for Metal {
     fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Scatter {
         let scattered_direction: Vec3 = ray_in.direction.reflect(hit_record.normal)
             + Vec3::get_random_unit_vector() * self.fuzz;
-        let scattered_ray: Ray = Ray::new(hit_record.point, scattered_direction);
+        let scattered_ray: Ray = Ray::new(hit_record.point, scattered_direction, ray_in.time);
         // Check if the scattered ray is going into the material, e.g. the
         // dot product with the normal is negative. If so, the ray is absorbed and
         // hence not scattered.
@@ -113,29 +141,115 @@ impl Material for Metal {
             attenuation: self.albedo,
         };
     }
+    /// The base color of a `Metal` material is its `albedo`.
+    fn base_color(&self, _hit_record: &HitRecord) -> Color {
+        return self.albedo;
+    }
+}
+
+/// A `RoughMetal` material reflects light about microfacet normals that are sampled from the
+/// GGX (Trowbridge-Reitz) distribution, which gives more physically plausible highlights than the
+/// `fuzz` of `Metal`. The `roughness` is assumed to be a value in [0, 1], where zero is a perfect
+/// mirror. Reflections that end up below the surface are absorbed. The light is attenuated by the
+/// `albedo`, the Fresnel and shadowing terms of the full microfacet model are not taken into
+/// account.
+/// See: <https://www.cs.cornell.edu/~srm/publications/EGSR07-btdf.pdf>
+#[derive(Clone, Copy, Debug)]
+pub struct RoughMetal {
+    pub albedo: Color,
+    pub roughness: f64,
+}
+
+impl RoughMetal {
+    /// Create new instance of `RoughMetal`.
+    pub fn new(albedo: Color, roughness: f64) -> Self {
+        Self { albedo, roughness }
+    }
+    /// Sample a microfacet normal around the surface `normal` from the GGX distribution weighted
+    /// by the cosine with the surface normal. With $\alpha$ the square of the roughness, the
+    /// angle $\theta$ of the microfacet normal with the surface normal follows from two uniform
+    /// random numbers $\xi_1, \xi_2$ as
+    /// $$ \tan^2 \theta = \frac{\alpha^2 \xi_1}{1 - \xi_1}, \quad \phi = 2 \pi \xi_2. $$
+    pub fn sample_microfacet_normal(&self, normal: Vec3) -> Vec3 {
+        let alpha: f64 = self.roughness * self.roughness;
+        let xi: f64 = get_random();
+        let tan2_theta: f64 = alpha * alpha * xi / (1.0 - xi);
+        let cos_theta: f64 = 1.0 / (1.0 + tan2_theta).sqrt();
+        let sin_theta: f64 = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi: f64 = 2.0 * std::f64::consts::PI * get_random();
+        let (tangent, bitangent) = normal.orthonormal_basis();
+        return tangent * (sin_theta * phi.cos())
+            + bitangent * (sin_theta * phi.sin())
+            + normal * cos_theta;
+    }
+}
+
+impl Material for RoughMetal {
+    /// A `RoughMetal` material scatters light by reflection with respect to a sampled microfacet
+    /// normal. We assume constant attenuation.
+    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Scatter {
+        let microfacet_normal: Vec3 = self.sample_microfacet_normal(hit_record.normal);
+        let scattered_direction: Vec3 = ray_in.direction.reflect(microfacet_normal);
+        let scattered_ray: Ray = Ray::new(hit_record.point, scattered_direction, ray_in.time);
+        // Rays reflected into the material are absorbed.
+        let did_scatter: bool = scattered_direction.dot(&hit_record.normal) > 0.0;
+        return Scatter {
+            did_scatter,
+            ray: scattered_ray,
+            attenuation: self.albedo,
+        };
+    }
+    /// The base color of a `RoughMetal` material is its `albedo`.
+    fn base_color(&self, _hit_record: &HitRecord) -> Color {
+        return self.albedo;
+    }
 }
 
 /// A `Dielectric` is a material like glass or water which both refracts and reflects light. The
 /// refraction is handled with Snell's law and requires the material to have a refractive index.
+/// Colored glass absorbs light along its path through the interior according to the Beer-Lambert
+/// law, with the `absorption` coefficient per color channel and per unit of distance.
 #[derive(Clone, Copy, Debug)]
 pub struct Dielectric {
     pub albedo: Color,
     pub refractive_index: f64,
+    pub absorption: Color,
 }
 
 impl Dielectric {
-    /// Create a new instance of `Dielectric`.
+    /// Create a new instance of `Dielectric` that does not absorb any light in its interior.
     pub fn new(albedo: Color, refractive_index: f64) -> Self {
         Self {
             albedo,
             refractive_index,
+            absorption: Color::new(0.0, 0.0, 0.0),
+        }
+    }
+    /// Create a new instance of `Dielectric` that absorbs light in its interior with the given
+    /// `absorption` coefficient per color channel.
+    pub fn with_absorption(albedo: Color, refractive_index: f64, absorption: Color) -> Self {
+        Self {
+            albedo,
+            refractive_index,
+            absorption,
         }
     }
+    /// Get the fraction of the light per color channel that remains after travelling the given
+    /// `distance` through the interior, which is $e^{-\sigma_a d}$ according to the Beer-Lambert
+    /// law.
+    pub fn transmittance(&self, distance: f64) -> Color {
+        return Color::new(
+            (-self.absorption.x * distance).exp(),
+            (-self.absorption.y * distance).exp(),
+            (-self.absorption.z * distance).exp(),
+        );
+    }
 }
 
 impl Material for Dielectric {
-    /// A `Dielectric` material both reflects and refracts the incoming light. Currently only the
-    /// refracted part is implemented.
+    /// A `Dielectric` material both reflects and refracts the incoming light. A ray that hits the
+    /// surface from the inside has travelled through the interior since the previous hit, so it is
+    /// attenuated by the absorption along that distance as well.
     fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Scatter {
         let ri = {
             if hit_record.front_face {
@@ -162,13 +276,170 @@ impl Material for Dielectric {
                 ray_in_direction_unit.refract(hit_record.normal, ri)
             }
         };
-        let scattered_ray = Ray::new(hit_record.point, scattered_direction);
+        let scattered_ray = Ray::new(hit_record.point, scattered_direction, ray_in.time);
+        let attenuation: Color = {
+            if hit_record.front_face {
+                self.albedo
+            } else {
+                let distance: f64 = hit_record.ray_parameter * ray_in.direction.length();
+                self.albedo * self.transmittance(distance)
+            }
+        };
         return Scatter {
             did_scatter: true,
             ray: scattered_ray,
-            attenuation: self.albedo,
+            attenuation,
         };
     }
+    /// A `Dielectric` material is transparent, so its base color is white.
+    fn base_color(&self, _hit_record: &HitRecord) -> Color {
+        return Color::new(1.0, 1.0, 1.0);
+    }
+}
+
+/// A `DiffuseLight` is a material that emits light of color `emission` in all directions. It does
+/// not scatter any incoming light. The components of `emission` may be larger than one for bright
+/// light sources.
+#[derive(Clone, Copy, Debug)]
+pub struct DiffuseLight {
+    pub emission: Color,
+}
+
+impl DiffuseLight {
+    /// Create new instance of `DiffuseLight`.
+    pub fn new(emission: Color) -> Self {
+        Self { emission }
+    }
+}
+
+impl Material for DiffuseLight {
+    /// A `DiffuseLight` absorbs all the incoming light, it only emits.
+    fn scatter(&self, ray_in: &Ray, _hit_record: &HitRecord) -> Scatter {
+        return Scatter {
+            did_scatter: false,
+            ray: *ray_in,
+            attenuation: Color::new(0.0, 0.0, 0.0),
+        };
+    }
+    /// A `DiffuseLight` emits its `emission` color.
+    fn emitted(&self, _ray_in: &Ray, _hit_record: &HitRecord) -> Color {
+        return self.emission;
+    }
+    /// The base color of a `DiffuseLight` is its `emission` color, clamped to [0,1].
+    fn base_color(&self, _hit_record: &HitRecord) -> Color {
+        let unit: Interval = Interval::new(0.0, 1.0);
+        return Color::new(
+            unit.clamp(self.emission.x),
+            unit.clamp(self.emission.y),
+            unit.clamp(self.emission.z),
+        );
+    }
+}
+
+/// A `SpotLight` is a `DiffuseLight` that only emits within a cone around `direction`, e.g. for a
+/// focused stage light on a `Disk` or `Quad`. Within the inner cone the full `emission` is
+/// emitted, outside the outer cone nothing, and in between the light fades out smoothly. The
+/// cones are stored as the cosines of their half angles.
+#[derive(Clone, Copy, Debug)]
+pub struct SpotLight {
+    pub emission: Color,
+    pub direction: Vec3,
+    pub cos_inner: f64,
+    pub cos_outer: f64,
+}
+
+impl SpotLight {
+    /// Create new instance of `SpotLight` shining along `direction`, which is normalised. The half
+    /// angles `inner_angle` and `outer_angle` of the cones are given in degrees, with the inner
+    /// angle no larger than the outer one.
+    pub fn new(emission: Color, direction: Vec3, inner_angle: f64, outer_angle: f64) -> Self {
+        if inner_angle > outer_angle {
+            log::warn!("SpotLight inner angle {inner_angle} is larger than outer {outer_angle}");
+        }
+        Self {
+            emission,
+            direction: direction.unit_vector(),
+            cos_inner: utils::degrees_to_radians(inner_angle).cos(),
+            cos_outer: utils::degrees_to_radians(outer_angle).cos(),
+        }
+    }
+}
+
+impl Material for SpotLight {
+    /// A `SpotLight` absorbs all the incoming light, it only emits.
+    fn scatter(&self, ray_in: &Ray, _hit_record: &HitRecord) -> Scatter {
+        return Scatter {
+            did_scatter: false,
+            ray: *ray_in,
+            attenuation: Color::new(0.0, 0.0, 0.0),
+        };
+    }
+    /// The light travels back along `ray_in`, so the emission is scaled by the smoothstep of the
+    /// cosine of the angle between $-\vec{b}$ and `direction`, going from zero at the outer cone
+    /// to one at the inner cone.
+    fn emitted(&self, ray_in: &Ray, _hit_record: &HitRecord) -> Color {
+        let cosine: f64 = ray_in.direction.unit_vector().neg().dot(&self.direction);
+        return self.emission * utils::smoothstep(self.cos_outer, self.cos_inner, cosine);
+    }
+    /// The base color of a `SpotLight` is its `emission` color, clamped to [0,1].
+    fn base_color(&self, _hit_record: &HitRecord) -> Color {
+        let unit: Interval = Interval::new(0.0, 1.0);
+        return Color::new(
+            unit.clamp(self.emission.x),
+            unit.clamp(self.emission.y),
+            unit.clamp(self.emission.z),
+        );
+    }
+}
+
+/// An `Isotropic` material scatters light uniformly in all directions, attenuated by the albedo
+/// color given by the `texture` at the hit. It never absorbs a ray. It is the phase function of
+/// participating media such as `ConstantMedium`.
+#[derive(Clone, Copy, Debug)]
+pub struct Isotropic<T: Texture + Clone = SolidColor> {
+    pub texture: T,
+}
+
+impl Isotropic<SolidColor> {
+    /// Create new instance of `Isotropic` with the same `albedo` everywhere.
+    pub fn new(albedo: Color) -> Self {
+        Self {
+            texture: SolidColor::new(albedo),
+        }
+    }
+}
+
+impl<T: Texture + Clone> Isotropic<T> {
+    /// Create new instance of `Isotropic` with the albedo given by `texture`.
+    pub fn from_texture(texture: T) -> Self {
+        Self { texture }
+    }
+}
+
+impl<T: Texture + Clone> Material for Isotropic<T> {
+    /// An `Isotropic` material scatters light from the hit point in a direction sampled uniformly
+    /// over the unit sphere.
+    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Scatter {
+        let scattered_ray = Ray::new(
+            hit_record.point,
+            Vec3::get_random_unit_vector(),
+            ray_in.time,
+        );
+        let attenuation: Color = self
+            .texture
+            .value(hit_record.u, hit_record.v, &hit_record.point);
+        return Scatter {
+            did_scatter: true,
+            ray: scattered_ray,
+            attenuation,
+        };
+    }
+    /// The base color of an `Isotropic` material is the albedo given by the `texture`.
+    fn base_color(&self, hit_record: &HitRecord) -> Color {
+        return self
+            .texture
+            .value(hit_record.u, hit_record.v, &hit_record.point);
+    }
 }
 
 /// Slick's approximation for reflectance.
@@ -178,3 +449,141 @@ pub fn reflectance(cosine: f64, refrative_index: f64) -> f64 {
     let power = (1.0 - cosine).powf(5.0);
     return r0 + (1.0 - r0) * power;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera::camera::Camera;
+    use crate::environment::environment::GradientSky;
+    use crate::hittables::hittables::Hittables;
+    use crate::hittables::sphere::Sphere;
+    use crate::vector::vector::Point;
+    use std::sync::Arc;
+
+    #[test]
+    fn ray_hitting_emissive_sphere_returns_the_emission() {
+        let black: Color = Color::new(0.0, 0.0, 0.0);
+        let camera: Camera = Camera::builder()
+            .environment(Box::new(GradientSky::new(black, black)))
+            .build();
+        let emission: Color = Color::new(4.0, 2.0, 1.0);
+        let mut world: Hittables = Hittables::init();
+        let light = world.add_material(DiffuseLight::new(emission));
+        world.add(Arc::new(Sphere::new(
+            Point::new(0.0, 0.0, -3.0),
+            1.0,
+            light,
+        )));
+
+        let origin: Point = Point::new(0.0, 0.0, 0.0);
+        let hit: Ray = Ray::new(origin, Vec3::new(0.0, 0.0, -1.0), 0.0);
+        assert_eq!(hit.ray_color(&world, 10, &camera), emission);
+        let miss: Ray = Ray::new(origin, Vec3::new(0.0, 0.0, 1.0), 0.0);
+        assert_eq!(miss.ray_color(&world, 10, &camera), black);
+    }
+
+    #[test]
+    fn isotropic_scatter_directions_average_to_zero() {
+        utils::seed_rng(33);
+        let albedo: Color = Color::new(0.5, 0.6, 0.7);
+        let isotropic: Isotropic = Isotropic::new(albedo);
+        let ray_in: Ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.0);
+        let hit_record: HitRecord = HitRecord {
+            hit: true,
+            point: Point::new(1.0, 0.0, 0.0),
+            ..HitRecord::default()
+        };
+
+        let count: usize = 20_000;
+        let mut sum: Vec3 = Vec3::new(0.0, 0.0, 0.0);
+        for _ in 0..count {
+            let scatter: Scatter = isotropic.scatter(&ray_in, &hit_record);
+            assert!(scatter.did_scatter);
+            assert_eq!(scatter.attenuation, albedo);
+            assert_eq!(scatter.ray.origin, hit_record.point);
+            sum += scatter.ray.direction.unit_vector();
+        }
+        // The mean of uniform unit vectors is zero, with a standard error of about 0.004.
+        assert!((sum / count as f64).length() < 0.03);
+    }
+
+    #[test]
+    fn thick_glass_tints_transmitted_light_more_than_thin_glass() {
+        utils::seed_rng(57);
+        let white: Color = Color::new(1.0, 1.0, 1.0);
+        let camera: Camera = Camera::builder()
+            .environment(Box::new(GradientSky::new(white, white)))
+            .build();
+        // Absorbs blue strongly, green less and red not at all.
+        let glass: Dielectric = Dielectric::with_absorption(white, 1.5, Color::new(0.0, 0.5, 1.0));
+        let transmitted = |radius: f64| {
+            let mut world: Hittables = Hittables::init();
+            let material = world.add_material(glass);
+            world.add(Arc::new(Sphere::new(
+                Point::new(0.0, 0.0, -5.0),
+                radius,
+                material,
+            )));
+            let ray: Ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+            let count: usize = 2000;
+            let mut total: Color = Color::new(0.0, 0.0, 0.0);
+            for _ in 0..count {
+                total += ray.ray_color(&world, 20, &camera);
+            }
+            return total / count as f64;
+        };
+        let thin: Color = transmitted(0.25);
+        let thick: Color = transmitted(2.0);
+        assert!((thin.x - 1.0).abs() < 1e-12);
+        assert!((thick.x - 1.0).abs() < 1e-12);
+        assert!(thick.y < thin.y);
+        assert!(thick.z < thin.z);
+        assert!(thick.z < thick.y);
+    }
+
+    #[test]
+    fn rough_metal_without_roughness_is_a_mirror() {
+        utils::seed_rng(59);
+        let normal: Vec3 = Vec3::new(0.0, 1.0, 0.0);
+        let hit_record: HitRecord = HitRecord {
+            hit: true,
+            normal,
+            front_face: true,
+            ..HitRecord::default()
+        };
+        let ray_in: Ray = Ray::new(Point::new(-1.0, 1.0, 0.0), Vec3::new(1.0, -1.0, 0.5), 0.0);
+        let mirrored: Vec3 = Vec3::new(1.0, 1.0, 0.5);
+
+        let mirror: RoughMetal = RoughMetal::new(Color::new(0.9, 0.9, 0.9), 0.0);
+        for _ in 0..100 {
+            let scatter: Scatter = mirror.scatter(&ray_in, &hit_record);
+            assert!(scatter.did_scatter);
+            assert!((scatter.ray.direction - mirrored).length() < 1e-12);
+        }
+        // With roughness, the reflections spread around the mirror direction.
+        let rough: RoughMetal = RoughMetal::new(Color::new(0.9, 0.9, 0.9), 0.5);
+        let spread: bool = (0..100).any(|_| {
+            let scatter: Scatter = rough.scatter(&ray_in, &hit_record);
+            (scatter.ray.direction - mirrored).length() > 1e-3
+        });
+        assert!(spread);
+    }
+
+    #[test]
+    fn spotlight_is_full_inside_the_inner_cone_and_dark_outside_the_outer() {
+        let emission: Color = Color::new(4.0, 4.0, 2.0);
+        let spot: SpotLight = SpotLight::new(emission, Vec3::new(0.0, -3.0, 0.0), 20.0, 40.0);
+        // A ray coming up to the light at `angle` degrees from the axis of the cone.
+        let emitted = |angle: f64| {
+            let (sin, cos) = utils::degrees_to_radians(angle).sin_cos();
+            let ray: Ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vec3::new(sin, cos, 0.0), 0.0);
+            return spot.emitted(&ray, &HitRecord::default());
+        };
+        assert_eq!(emitted(0.0), emission);
+        assert_eq!(emitted(15.0), emission);
+        assert_eq!(emitted(45.0), Color::new(0.0, 0.0, 0.0));
+        assert_eq!(emitted(90.0), Color::new(0.0, 0.0, 0.0));
+        let between: Color = emitted(30.0);
+        assert!(between.x > 0.0 && between.x < emission.x);
+    }
+}
@@ -0,0 +1,176 @@
+use crate::error::error::RenderError;
+use crate::hittables::hittables::Hittables;
+use crate::hittables::triangle::Triangle;
+use crate::materials::arena::MaterialId;
+use crate::vector::vector::{Point, Vec3};
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Error that occurs when a line of an OBJ file cannot be parsed. The `line` number starts at one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MeshParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for MeshParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid OBJ file at line {}: {}",
+            self.line, self.message
+        )
+    }
+}
+
+impl std::error::Error for MeshParseError {}
+
+/// Parse the `contents` of a Wavefront OBJ file into a list of `Triangle` hittables with the
+/// given `material`. Only the vertex positions (`v` lines), the vertex normals (`vn` lines) and
+/// the faces (`f` lines) are read, all other lines such as texture coordinates and groups are
+/// ignored. Faces with more than three vertices are split into a fan of triangles around their
+/// first vertex. If every vertex of a face refers to a normal, e.g. `f 1//1 2//2 3//3`, the
+/// triangles are smoothly shaded with these normals. Indices start at one, negative indices count
+/// back from the last vertex or normal read so far.
+/// See: <https://en.wikipedia.org/wiki/Wavefront_.obj_file>
+pub fn parse_obj(contents: &str, material: MaterialId) -> Result<Hittables, MeshParseError> {
+    let mut vertices: Vec<Point> = Vec::new();
+    let mut normals: Vec<Vec3> = Vec::new();
+    let mut triangles: Hittables = Hittables::init();
+    for (line_index, line) in contents.lines().enumerate() {
+        let error = |message: String| MeshParseError {
+            line: line_index + 1,
+            message,
+        };
+        // Strip comments
+        let line: &str = line.split('#').next().unwrap_or("");
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some(keyword @ ("v" | "vn")) => {
+                let coordinates: Vec<f64> = tokens
+                    .take(3)
+                    .map(|token| {
+                        token
+                            .parse::<f64>()
+                            .map_err(|err| error(format!("invalid coordinate `{token}`: {err}")))
+                    })
+                    .collect::<Result<Vec<f64>, MeshParseError>>()?;
+                if coordinates.len() < 3 {
+                    return Err(error(format!("`{keyword}` needs three coordinates")));
+                }
+                let vector: Vec3 = Vec3::new(coordinates[0], coordinates[1], coordinates[2]);
+                if keyword == "v" {
+                    vertices.push(vector);
+                } else {
+                    normals.push(vector.unit_vector());
+                }
+            }
+            Some("f") => {
+                // Every vertex of the face is a position with an optional normal.
+                let face: Vec<(Point, Option<Vec3>)> = tokens
+                    .map(|token| {
+                        let invalid = || error(format!("invalid face vertex `{token}`"));
+                        let mut indices = token.split('/');
+                        let position: &str = indices.next().unwrap_or("");
+                        let position: Point =
+                            vertices[parse_index(position, vertices.len()).ok_or_else(invalid)?];
+                        let normal: Option<Vec3> = match indices.nth(1) {
+                            Some(index) if !index.is_empty() => Some(
+                                normals[parse_index(index, normals.len()).ok_or_else(invalid)?],
+                            ),
+                            _ => None,
+                        };
+                        return Ok((position, normal));
+                    })
+                    .collect::<Result<Vec<(Point, Option<Vec3>)>, MeshParseError>>()?;
+                if face.len() < 3 {
+                    return Err(error("a face needs at least three vertices".to_string()));
+                }
+                // Triangulate the polygon with a fan around its first vertex.
+                for k in 1..face.len() - 1 {
+                    let (v0, v1, v2) = (face[0], face[k], face[k + 1]);
+                    match (v0.1, v1.1, v2.1) {
+                        (Some(n0), Some(n1), Some(n2)) => {
+                            triangles.add(Arc::new(Triangle::with_normals(
+                                v0.0,
+                                v1.0,
+                                v2.0,
+                                [n0, n1, n2],
+                                material,
+                            )));
+                        }
+                        _ => {
+                            triangles.add(Arc::new(Triangle::new(v0.0, v1.0, v2.0, material)));
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    return Ok(triangles);
+}
+
+/// Parse an `index` of a face element into an index into the `count` vertices or normals read so
+/// far. Returns `None` if the index is not a number or does not refer to an existing element.
+fn parse_index(index: &str, count: usize) -> Option<usize> {
+    let index: i64 = index.parse().ok()?;
+    let index: i64 = {
+        if index > 0 {
+            index - 1
+        } else {
+            count as i64 + index
+        }
+    };
+    if index < 0 || index >= count as i64 {
+        return None;
+    }
+    return Some(index as usize);
+}
+
+/// Read the Wavefront OBJ file at `path` into a list of `Triangle` hittables with the given
+/// `material`, see `parse_obj`. The path is added to the message of I/O errors.
+pub fn load_obj(path: &Path, material: MaterialId) -> Result<Hittables, RenderError> {
+    let contents: String = std::fs::read_to_string(path).map_err(|err| {
+        let message: String = format!("could not read OBJ file `{}`: {err}", path.display());
+        RenderError::Io(std::io::Error::new(err.kind(), message))
+    })?;
+    return Ok(parse_obj(&contents, material)?);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hittables::hittables::Hittable;
+    use crate::raycaster::ray::Ray;
+    use crate::util::utils::{Interval, POSITIVE_INFINITY};
+
+    #[test]
+    fn quad_face_is_split_into_two_triangles() {
+        let contents: &str = "# unit square\n\
+                              v 0 0 0\n\
+                              v 1 0 0\n\
+                              v 1 1 0\n\
+                              v 0 1 0\n\
+                              vt 0 0\n\
+                              f 1 2 3 4\n";
+        let mesh: Hittables = parse_obj(contents, MaterialId(0)).unwrap();
+        assert_eq!(mesh.len(), 2);
+        // Both halves of the square are covered.
+        for (x, y) in [(0.75, 0.25), (0.25, 0.75)] {
+            let ray: Ray = Ray::new(Point::new(x, y, 1.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+            assert!(
+                mesh.ray_hit(&ray, Interval::new(0.001, POSITIVE_INFINITY))
+                    .hit
+            );
+        }
+
+        // A face referring to vertices that do not exist is an error on its line.
+        match parse_obj("v 0 0 0\nf 1 2 3\n", MaterialId(0)) {
+            Err(error) => assert_eq!(error.line, 2),
+            Ok(_) => panic!("face with missing vertices was accepted"),
+        }
+        assert!(load_obj(Path::new("does/not/exist.obj"), MaterialId(0)).is_err());
+    }
+}
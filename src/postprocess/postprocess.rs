@@ -0,0 +1,152 @@
+use crate::util::utils;
+use crate::vector::vector::Color;
+
+/// A `PostProcess` is an effect that is applied to the rendered image `buffer` of the given
+/// dimensions, in scanline order, after rendering and before the image is written. The buffer
+/// holds linear colors, so post-processes run before exposure, tone mapping and gamma encoding.
+/// Post-processes are owned by the `Camera` which is shared between the rendering threads, hence
+/// they must be `Send + Sync`.
+pub trait PostProcess: Send + Sync {
+    fn apply(&self, buffer: &mut [Color], width: i32, height: i32);
+}
+
+/// A `Vignette` darkens the image towards the corners with the given `strength`, see
+/// `utils::vignette`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Vignette {
+    pub strength: f64,
+}
+
+impl Vignette {
+    /// Create new instance of `Vignette`.
+    pub fn new(strength: f64) -> Self {
+        Self { strength }
+    }
+}
+
+impl PostProcess for Vignette {
+    /// Apply `utils::vignette` with the `strength` of the vignette.
+    fn apply(&self, buffer: &mut [Color], width: i32, height: i32) {
+        utils::vignette(buffer, width, height, self.strength);
+    }
+}
+
+/// `Bloom` makes bright parts of the image glow, like light that scatters in a real lens. The
+/// pixels whose luminance is above the `threshold` are blurred with a Gaussian blur that reaches
+/// `radius` pixels far, and the blurred image is added to the image, scaled by the `intensity`.
+/// Since the buffer holds linear colors, emissive objects are typically far brighter than one,
+/// so a `threshold` of one only lets light sources and their highlights glow.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bloom {
+    pub threshold: f64,
+    pub radius: i32,
+    pub intensity: f64,
+}
+
+impl Bloom {
+    /// Create new instance of `Bloom`.
+    pub fn new(threshold: f64, radius: i32, intensity: f64) -> Self {
+        Self {
+            threshold,
+            radius,
+            intensity,
+        }
+    }
+    /// Get the normalized weights of the Gaussian blur for the offsets from `-radius` to `radius`.
+    /// The standard deviation is a third of the radius, so the weights beyond the radius are
+    /// negligible.
+    pub fn kernel(&self) -> Vec<f64> {
+        let radius: i32 = self.radius.max(0);
+        let sigma: f64 = (radius as f64 / 3.0).max(f64::EPSILON);
+        let weights: Vec<f64> = (-radius..=radius)
+            .map(|offset| (-0.5 * (offset as f64 / sigma).powi(2)).exp())
+            .collect();
+        let total: f64 = weights.iter().sum();
+        return weights.iter().map(|weight| weight / total).collect();
+    }
+}
+
+/// Get the relative luminance of a linear `color`, with the Rec. 709 weights of its channels.
+/// See: <https://en.wikipedia.org/wiki/Relative_luminance>
+pub fn luminance(color: &Color) -> f64 {
+    return 0.2126 * color.x + 0.7152 * color.y + 0.0722 * color.z;
+}
+
+/// Blur the `image` of the given dimensions along its rows if `horizontal` is true, or along its
+/// columns otherwise, with the `kernel`, which holds the weights for the offsets from `-radius`
+/// to `radius`. Pixels outside the image are black.
+fn blur_axis(
+    image: &[Color],
+    width: i32,
+    height: i32,
+    kernel: &[f64],
+    horizontal: bool,
+) -> Vec<Color> {
+    let radius: i32 = (kernel.len() / 2) as i32;
+    let mut blurred: Vec<Color> = vec![Color::new(0.0, 0.0, 0.0); image.len()];
+    for j in 0..height {
+        for i in 0..width {
+            let mut color: Color = Color::new(0.0, 0.0, 0.0);
+            for (k, weight) in kernel.iter().enumerate() {
+                let offset: i32 = k as i32 - radius;
+                let (x, y) = if horizontal {
+                    (i + offset, j)
+                } else {
+                    (i, j + offset)
+                };
+                if x >= 0 && x < width && y >= 0 && y < height {
+                    color += image[(y * width + x) as usize] * *weight;
+                }
+            }
+            blurred[(j * width + i) as usize] = color;
+        }
+    }
+    return blurred;
+}
+
+impl PostProcess for Bloom {
+    /// Extract the bright pixels, blur them with a separable Gaussian blur, first along the rows
+    /// and then along the columns, and add the result scaled by the `intensity`.
+    fn apply(&self, buffer: &mut [Color], width: i32, height: i32) {
+        let bright: Vec<Color> = buffer
+            .iter()
+            .map(|color| {
+                if luminance(color) > self.threshold {
+                    *color
+                } else {
+                    Color::new(0.0, 0.0, 0.0)
+                }
+            })
+            .collect();
+        let kernel: Vec<f64> = self.kernel();
+        let blurred: Vec<Color> = blur_axis(&bright, width, height, &kernel, true);
+        let blurred: Vec<Color> = blur_axis(&blurred, width, height, &kernel, false);
+        for (color, glow) in buffer.iter_mut().zip(blurred) {
+            *color += glow * self.intensity;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bright_pixel_glows_into_its_neighbors_but_a_dim_one_does_not() {
+        let dim: Color = Color::new(0.5, 0.5, 0.5);
+        let black: Color = Color::new(0.0, 0.0, 0.0);
+        // A 7x1 image with a bright pixel at the left and a dim one at the right.
+        let mut buffer: Vec<Color> = vec![black; 7];
+        buffer[1] = Color::new(50.0, 50.0, 50.0);
+        buffer[5] = dim;
+        let bloom: Bloom = Bloom::new(1.0, 1, 1.0);
+        bloom.apply(&mut buffer, 7, 1);
+        assert!(buffer[0].x > 0.0);
+        assert!(buffer[2].x > 0.0);
+        // The glow reaches `radius` pixels far.
+        assert_eq!(buffer[3], black);
+        assert_eq!(buffer[4], black);
+        assert_eq!(buffer[5], dim);
+        assert_eq!(buffer[6], black);
+    }
+}
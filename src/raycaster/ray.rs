@@ -1,24 +1,34 @@
-use crate::camera::camera::Camera;
+use crate::camera::camera::{Camera, Projection, RenderMode};
 use crate::hittables::hittables::Hittable;
 use crate::hittables::hittables::Hittables;
+use crate::hittables::record::HitRecord;
 use crate::materials::materials::Scatter;
-use crate::util::utils::sample_square;
-use crate::util::utils::Interval;
+use crate::util::utils;
 use crate::util::utils::POSITIVE_INFINITY;
+use crate::util::utils::{get_random, Interval};
 use crate::vector::vector::{Color, Point, Vec3};
+use std::ops::Neg;
+use std::sync::atomic::Ordering;
 
 /// A `Ray` is defined is effectively a line in 3D. This line can be fully defined by a
 /// point (the origin) and a vector from that point (the direction). Effectively it is a function
+/// The `time` at which the ray is cast determines where moving objects are, which is used for
+/// motion blur.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Ray {
     pub origin: Point,
     pub direction: Vec3,
+    pub time: f64,
 }
 
 impl Ray {
     /// Create new `Ray` instance.
-    pub fn new(origin: Point, direction: Vec3) -> Self {
-        Self { origin, direction }
+    pub fn new(origin: Point, direction: Vec3, time: f64) -> Self {
+        Self {
+            origin,
+            direction,
+            time,
+        }
     }
     /// The `Ray` structure defines the constants needed to define a parametrization of a
     /// line in 3D. To actually obtain a point along this line the parameter value, here called
@@ -30,48 +40,452 @@ impl Ray {
         return self.origin + self.direction * scalar;
     }
     /// Send the given `Ray` out into the `world`, if it hits a `Hittable` object, do something
-    /// with the colors. If it does not hit anything, do the default coloring.
-    pub fn ray_color(&self, world: &Hittables, depth: i32) -> Color {
+    /// with the colors. If it does not hit anything, the color is given by the environment of the
+    /// `camera`. Hits closer than the `shadow_epsilon` of the `camera` along the ray are ignored,
+    /// for the shadow rays towards the lights as well.
+    pub fn ray_color(&self, world: &Hittables, depth: i32, camera: &Camera) -> Color {
+        return self.path_color(world, depth, camera, false);
+    }
+    /// Get the color of the ray as in `ray_color`. If `sun_sampled` is true, the ray was scattered
+    /// by a diffuse surface where the light of the sun of the environment was already added by
+    /// `direct_light`, so the disk of the sun is left out of the environment to not count it twice.
+    pub fn path_color(
+        &self,
+        world: &Hittables,
+        depth: i32,
+        camera: &Camera,
+        sun_sampled: bool,
+    ) -> Color {
+        // Count the ray for the render statistics, path traced rays beyond the maximum depth are
+        // not traced.
+        if depth > 0 || camera.render_mode != RenderMode::PathTrace {
+            camera.rays_traced.fetch_add(1, Ordering::Relaxed);
+        }
+        // Debug render modes only look at the first hit.
+        match camera.render_mode {
+            RenderMode::PathTrace => {}
+            RenderMode::Normals => return self.normal_color(world, camera),
+            RenderMode::Depth { max_distance } => {
+                return self.depth_color(world, max_distance, camera)
+            }
+            RenderMode::Albedo => return self.albedo_color(world, camera),
+        }
         // If we have reached the maximum depth, return black.
         if depth <= 0 {
             return Color::new(0.0, 0.0, 0.0);
         }
         // Making the lower bound of the valid interval slightly bigger than zero avoids shadow
         // acne.
-        let (hit_record, material) = world.ray_hit(self, Interval::new(0.001, POSITIVE_INFINITY));
+        let hit_record: HitRecord = world.ray_hit(
+            self,
+            Interval::new(camera.shadow_epsilon, POSITIVE_INFINITY),
+        );
 
-        if hit_record.hit {
+        // Look up the material that was hit in the materials of the world.
+        if let Some(material) = hit_record.material.and_then(|id| world.material(id)) {
+            // Get the light emitted by the material.
+            let emitted: Color = material.emitted(self, &hit_record);
             // Get the scattered ray based on the material.
             let scatter: Scatter = material.scatter(self, &hit_record);
             // Check if the ray scatterd
             if scatter.did_scatter {
-                // Run `ray_color` on the scattered ray with the attenuated color
-                return scatter.ray.ray_color(world, depth - 1) * scatter.attenuation;
+                // Add the light arriving directly from the light sources on diffuse surfaces.
+                let direct: Color = {
+                    if material.is_diffuse() {
+                        self.direct_light(world, camera, &hit_record) * scatter.attenuation
+                    } else {
+                        Color::new(0.0, 0.0, 0.0)
+                    }
+                };
+                // Sample the light surfaces directly as well on diffuse surfaces.
+                if material.is_diffuse() && !camera.light_surfaces.is_empty() {
+                    return emitted
+                        + direct
+                        + self.light_sampled_color(
+                            world,
+                            depth,
+                            camera,
+                            &hit_record,
+                            scatter.attenuation,
+                        );
+                }
+                // Run `path_color` on the scattered ray with the attenuated color
+                return emitted
+                    + direct
+                    + scatter
+                        .ray
+                        .path_color(world, depth - 1, camera, material.is_diffuse())
+                        * scatter.attenuation;
             } else {
-                // If it did not scatter, it was completely absorbed, e.g. the color was black.
-                return Color::new(0.0, 0.0, 0.0);
+                // If it did not scatter, it was completely absorbed, e.g. only the emitted light
+                // remains.
+                return emitted;
             }
         }
 
-        let unit_direction = self.direction.unit_vector();
-        let a: f64 = (unit_direction.y + 1.0) * 0.5;
-        return Color::new(1.0, 1.0, 1.0) * (1.0 - a) + Color::new(0.5, 0.7, 1.0) * a;
+        if sun_sampled {
+            return camera.environment.sample_without_sun(self.direction);
+        }
+        return camera.environment.sample(self.direction);
     }
-    /// Given a pixel location (i,j), shoot a ray from the `Camera` to a random
-    /// location within the pixel square.
-    pub fn get_ray(i: i32, j: i32, camera: &Camera) -> Self {
-        let offset: Vec3 = sample_square();
+    /// Get the light arriving directly from the `Light` sources of the `camera` at the diffuse
+    /// surface of the `hit_record`, reflected by a white Lambertian BRDF $1 / \pi$. For every
+    /// light, a shadow ray is cast towards it, and if nothing in the `world` lies in between, its
+    /// intensity is added, scaled by $\max(0, n \cdot l) / d^2$ with $l$ the unit direction
+    /// towards the light and $d$ the distance to it. The `Sun` of the environment, if any, is added
+    /// in the same way, but without the falloff with the distance.
+    pub fn direct_light(
+        &self,
+        world: &Hittables,
+        camera: &Camera,
+        hit_record: &HitRecord,
+    ) -> Color {
+        let mut light: Color = Color::new(0.0, 0.0, 0.0);
+        for source in camera.lights.iter() {
+            // The light lies at ray parameter one along the shadow ray.
+            let to_light: Vec3 = source.position() - hit_record.point;
+            let cos_theta: f64 = hit_record.normal.dot(&to_light.unit_vector());
+            if cos_theta <= 0.0 {
+                continue;
+            }
+            let shadow_ray: Ray = Ray::new(hit_record.point, to_light, self.time);
+            let shadow_hit: HitRecord =
+                world.ray_hit(&shadow_ray, Interval::new(camera.shadow_epsilon, 1.0));
+            if shadow_hit.hit {
+                continue;
+            }
+            light += source.intensity_toward(&hit_record.point) * cos_theta
+                / (to_light.length_squared() * std::f64::consts::PI);
+        }
+        // The sun is infinitely far away, so its light does not fall off with the distance.
+        if let Some(sun) = camera.environment.sun() {
+            let cos_theta: f64 = hit_record.normal.dot(&sun.direction);
+            if cos_theta > 0.0 {
+                let shadow_ray: Ray = Ray::new(hit_record.point, sun.direction, self.time);
+                let shadow_hit: HitRecord = world.ray_hit(
+                    &shadow_ray,
+                    Interval::new(camera.shadow_epsilon, POSITIVE_INFINITY),
+                );
+                if !shadow_hit.hit {
+                    light += sun.intensity * cos_theta / std::f64::consts::PI;
+                }
+            }
+        }
+        return light;
+    }
+    /// Get the light scattered by the diffuse surface of the `hit_record` with the given `albedo`,
+    /// by sending out a ray in a direction that is sampled toward the light surfaces of the
+    /// `camera` or from the cosine-weighted hemisphere, each with probability one half. The
+    /// result is weighted by the Lambertian BRDF $\text{albedo} / \pi$ and the cosine with the
+    /// normal, and divided by the mixed density of both strategies. Sampling the lights directly
+    /// makes the render converge much faster for small light sources.
+    pub fn light_sampled_color(
+        &self,
+        world: &Hittables,
+        depth: i32,
+        camera: &Camera,
+        hit_record: &HitRecord,
+        albedo: Color,
+    ) -> Color {
+        let direction: Vec3 = {
+            if get_random() < 0.5 {
+                camera
+                    .light_surfaces
+                    .random_point_toward(&hit_record.point)
+                    .0
+            } else {
+                Vec3::get_random_cosine_direction(hit_record.normal)
+            }
+        };
+        // Directions below the surface do not contribute.
+        let cos_theta: f64 = hit_record.normal.dot(&direction.unit_vector());
+        if cos_theta <= 0.0 {
+            return Color::new(0.0, 0.0, 0.0);
+        }
+        let pdf: f64 = 0.5
+            * camera
+                .light_surfaces
+                .pdf_value(&hit_record.point, &direction)
+            + 0.5 * cos_theta / std::f64::consts::PI;
+        let scattered_ray: Ray = Ray::new(hit_record.point, direction, self.time);
+        return scattered_ray.path_color(world, depth - 1, camera, true)
+            * albedo
+            * (cos_theta / (std::f64::consts::PI * pdf));
+    }
+    /// Get the color of the normal of the first surface the ray hits in the `world`, with every
+    /// component mapped from [-1,1] to [0,1]. If it does not hit anything, the color is given by
+    /// the environment of the `camera`.
+    pub fn normal_color(&self, world: &Hittables, camera: &Camera) -> Color {
+        let hit_record: HitRecord = world.ray_hit(
+            self,
+            Interval::new(camera.shadow_epsilon, POSITIVE_INFINITY),
+        );
+        if hit_record.hit {
+            return (hit_record.normal + Vec3::new(1.0, 1.0, 1.0)) * 0.5;
+        }
+        return camera.environment.sample(self.direction);
+    }
+    /// Get the distance along the ray to the first surface it hits in the `world` as a gray value,
+    /// relative to `max_distance`. Surfaces at `max_distance` or further are white, and so are
+    /// rays that do not hit anything, which are taken to be at `max_distance`.
+    pub fn depth_color(&self, world: &Hittables, max_distance: f64, camera: &Camera) -> Color {
+        let distance: f64 = self.hit_distance(world, camera).min(max_distance);
+        let gray: f64 = Interval::new(0.0, 1.0).clamp(distance / max_distance);
+        return Color::new(gray, gray, gray);
+    }
+    /// Get the distance along the ray to the first surface it hits in the `world`, or
+    /// `POSITIVE_INFINITY` if it does not hit anything. Hits closer than the `shadow_epsilon` of
+    /// the `camera` are ignored.
+    pub fn hit_distance(&self, world: &Hittables, camera: &Camera) -> f64 {
+        let hit_record: HitRecord = world.ray_hit(
+            self,
+            Interval::new(camera.shadow_epsilon, POSITIVE_INFINITY),
+        );
+        if hit_record.hit {
+            return hit_record.ray_parameter * self.direction.length();
+        }
+        return POSITIVE_INFINITY;
+    }
+    /// Get the base color of the material of the first surface the ray hits in the `world`. If it
+    /// does not hit anything, the color is given by the environment of the `camera`.
+    pub fn albedo_color(&self, world: &Hittables, camera: &Camera) -> Color {
+        let hit_record: HitRecord = world.ray_hit(
+            self,
+            Interval::new(camera.shadow_epsilon, POSITIVE_INFINITY),
+        );
+        if let Some(material) = hit_record.material.and_then(|id| world.material(id)) {
+            return material.base_color(&hit_record);
+        }
+        return camera.environment.sample(self.direction);
+    }
+    /// Given a pixel location (i,j), shoot a ray from the `Camera` to a random location around
+    /// the pixel center, distributed according to the pixel filter, at a random time while the
+    /// shutter is open. The location is made from the numbers of the camera's sampler for the
+    /// given `sample` of the pixel, every pixel uses its own range of the sampler's sequence.
+    /// With an orthographic projection, the ray goes in the viewing direction from right behind
+    /// the sampled location instead, and with an equirectangular projection, the ray goes from the
+    /// camera center in the direction with the longitude and latitude of the sampled location. With
+    /// a fisheye projection, the ray goes from the camera center in the direction given by
+    /// `Camera::fisheye_direction` for the sampled location. See `Projection`.
+    pub fn get_ray(i: i32, j: i32, sample: i32, camera: &Camera) -> Self {
+        let pixel_index: u64 = (j as u64) * (camera.image_width as u64) + (i as u64);
+        let sample_index: u64 = pixel_index * (camera.samples_per_pixel as u64) + (sample as u64);
+        let (uniform1, uniform2) = camera.sampler.sample_2d(sample_index);
+        let offset: Vec3 = camera.pixel_filter.offset(uniform1, uniform2);
         let pixel_sample = camera.pixel_upper_left_center
             + (camera.pixel_delta_u * (i as f64 + offset.x))
             + (camera.pixel_delta_v * (j as f64 + offset.y));
-        let ray_origin: Point = {
-            if camera.defocus_angle <= 0.0 {
-                camera.center
-            } else {
-                camera.defocus_disk_sample()
+        let (ray_origin, ray_direction): (Point, Vec3) = match camera.projection {
+            Projection::Perspective => {
+                let ray_origin: Point = {
+                    if camera.defocus_angle <= 0.0 {
+                        camera.center
+                    } else {
+                        camera.defocus_disk_sample(sample_index)
+                    }
+                };
+                (ray_origin, pixel_sample - ray_origin)
+            }
+            // Move the sample back from the viewport to the plane of the camera center.
+            Projection::Orthographic => {
+                (pixel_sample + camera.w * camera.focus_dist, camera.w.neg())
+            }
+            // The longitude runs from -pi to pi from left to right, and the latitude from pi/2 to
+            // -pi/2 from top to bottom.
+            Projection::Equirectangular => {
+                let (longitude, latitude) = utils::equirectangular_angles(
+                    i as f64 + 0.5 + offset.x,
+                    j as f64 + 0.5 + offset.y,
+                    camera.image_width,
+                    camera.image_height,
+                );
+                let direction: Vec3 = camera.u * (latitude.cos() * longitude.sin())
+                    + camera.v * latitude.sin()
+                    - camera.w * (latitude.cos() * longitude.cos());
+                (camera.center, direction)
+            }
+            Projection::Fisheye { fov_degrees } => {
+                let direction: Vec3 = camera.fisheye_direction(
+                    i as f64 + 0.5 + offset.x,
+                    j as f64 + 0.5 + offset.y,
+                    fov_degrees,
+                );
+                (camera.center, direction)
             }
         };
-        let ray_direction: Vec3 = pixel_sample - ray_origin;
-        return Self::new(ray_origin, ray_direction);
+        let ray_time: f64 = camera.shutter_sample();
+        return Self::new(ray_origin, ray_direction, ray_time);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::environment::environment::GradientSky;
+    use crate::hittables::sphere::Sphere;
+    use crate::materials::arena::MaterialId;
+    use crate::materials::materials::Lambertian;
+    use std::sync::Arc;
+
+    #[test]
+    fn camera_ray_times_lie_within_the_shutter() {
+        let camera: Camera = Camera::builder()
+            .width(8)
+            .samples(16)
+            .shutter(0.25, 0.75)
+            .seed(17)
+            .build();
+        let mut times: Vec<f64> = Vec::new();
+        for j in 0..camera.image_height {
+            for i in 0..camera.image_width {
+                for sample in 0..camera.samples_per_pixel {
+                    times.push(Ray::get_ray(i, j, sample, &camera).time);
+                }
+            }
+        }
+        assert!(times.iter().all(|&time| (0.25..=0.75).contains(&time)));
+        // The times are spread over the whole shutter interval.
+        assert!(times.iter().any(|&time| time < 0.3));
+        assert!(times.iter().any(|&time| time > 0.7));
+
+        // With the shutter open for an instant, every ray has that time.
+        let camera: Camera = Camera::builder()
+            .width(8)
+            .samples(4)
+            .shutter(0.5, 0.5)
+            .build();
+        assert!((0..4).all(|sample| Ray::get_ray(3, 2, sample, &camera).time == 0.5));
+    }
+
+    /// A world with a red diffuse sphere of radius one at (0,0,-`distance`).
+    fn sphere_ahead(distance: f64) -> Hittables {
+        let mut world: Hittables = Hittables::init();
+        let red = world.add_material(Lambertian::new(Color::new(1.0, 0.0, 0.0)));
+        world.add(Arc::new(Sphere::new(
+            Point::new(0.0, 0.0, -distance),
+            1.0,
+            red,
+        )));
+        return world;
+    }
+
+    #[test]
+    fn normals_mode_shows_a_sphere_facing_plus_z_as_blue() {
+        let camera: Camera = Camera::builder().render_mode(RenderMode::Normals).build();
+        let ray: Ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let color: Color = ray.ray_color(&sphere_ahead(3.0), 10, &camera);
+        assert_eq!(color, Color::new(0.5, 0.5, 1.0));
+        assert!(color.z > color.x && color.z > color.y);
+    }
+
+    #[test]
+    fn closer_sphere_has_a_darker_depth() {
+        let camera: Camera = Camera::builder().build();
+        let ray: Ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let near: Color = ray.depth_color(&sphere_ahead(3.0), 10.0, &camera);
+        let far: Color = ray.depth_color(&sphere_ahead(6.0), 10.0, &camera);
+        assert!((near.x - 0.2).abs() < 1e-12);
+        assert!((far.x - 0.5).abs() < 1e-12);
+        assert!(near.x < far.x);
+        // A miss is at the maximum distance, which is white.
+        let miss: Ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0), 0.0);
+        assert_eq!(
+            miss.depth_color(&sphere_ahead(3.0), 10.0, &camera),
+            Color::new(1.0, 1.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn albedo_pass_of_a_red_sphere_is_red_without_any_light() {
+        let black: Color = Color::new(0.0, 0.0, 0.0);
+        let camera: Camera = Camera::builder()
+            .aspect_ratio(1.0)
+            .width(9)
+            .samples(4)
+            .environment(Box::new(GradientSky::new(black, black)))
+            .render_mode(RenderMode::Albedo)
+            .build();
+        let world: Hittables = sphere_ahead(3.0);
+        let buffer: Vec<Color> = camera.render_to_buffer(&world);
+        assert_eq!(buffer[4 * 9 + 4], Color::new(1.0, 0.0, 0.0));
+
+        // The path traced image of the unlit scene is black.
+        let camera: Camera = Camera::builder()
+            .aspect_ratio(1.0)
+            .width(9)
+            .samples(4)
+            .environment(Box::new(GradientSky::new(black, black)))
+            .build();
+        assert_eq!(camera.render_to_buffer(&world)[4 * 9 + 4], black);
+    }
+
+    #[test]
+    fn orthographic_rays_share_the_direction_but_not_the_origin() {
+        let camera: Camera = Camera::builder()
+            .width(16)
+            .samples(1)
+            .center(Point::new(1.0, 2.0, 3.0))
+            .look_at(Point::new(0.0, 0.0, 0.0))
+            .projection(Projection::Orthographic)
+            .build();
+        let first: Ray = Ray::get_ray(0, 0, 0, &camera);
+        let second: Ray = Ray::get_ray(11, 5, 0, &camera);
+        assert_eq!(first.direction, second.direction);
+        assert_eq!(first.direction, -camera.w);
+        assert_ne!(first.origin, second.origin);
+        // Both origins lie in the plane through the camera center facing the view direction.
+        for ray in [first, second] {
+            assert!((ray.origin - camera.center).dot(&camera.w).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn outermost_panorama_columns_meet_behind_the_camera() {
+        let camera: Camera = Camera::builder()
+            .width(64)
+            .samples(4)
+            .projection(Projection::Equirectangular)
+            .seed(90)
+            .build();
+        assert_eq!(camera.image_height, 32);
+        let j: i32 = camera.image_height / 2;
+        for sample in 0..camera.samples_per_pixel {
+            let left: Vec3 = Ray::get_ray(0, j, sample, &camera).direction.unit_vector();
+            let right: Vec3 = Ray::get_ray(63, j, sample, &camera).direction.unit_vector();
+            let center: Vec3 = Ray::get_ray(32, j, sample, &camera).direction.unit_vector();
+            // The image wraps around, the outer columns look backwards, opposite to the center.
+            assert!(left.dot(&right) > 0.95);
+            assert!(left.dot(&center) < -0.95);
+            assert!(right.dot(&center) < -0.95);
+            assert!(center.dot(&camera.w.neg()) > 0.95);
+        }
+    }
+
+    #[test]
+    fn shadow_epsilon_skips_self_hits_but_keeps_real_ones() {
+        let sphere = |center: Point, radius: f64| {
+            let mut world: Hittables = Hittables::init();
+            world.add(Arc::new(Sphere::new(center, radius, MaterialId(0))));
+            return world;
+        };
+        // A large epsilon still finds the large sphere below the ray.
+        let ground: Hittables = sphere(Point::new(0.0, -1000.0, 0.0), 1000.0);
+        let coarse: Camera = Camera::builder().shadow_epsilon(0.1).build();
+        let down: Ray = Ray::new(Point::new(0.0, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0), 0.0);
+        assert!((down.hit_distance(&ground, &coarse) - 5.0).abs() < 1e-9);
+
+        // A ray leaving a small sphere from just below its surface, as a rounded hit point does,
+        // does not hit the sphere again with a small epsilon.
+        let small: Hittables = sphere(Point::new(0.0, 0.0, 0.0), 1e-4);
+        let fine: Camera = Camera::builder().shadow_epsilon(1e-6).build();
+        let origin: Point = Point::new(1e-4 - 1e-12, 0.0, 0.0);
+        let outward: Ray = Ray::new(origin, Vec3::new(1.0, 0.0, 0.0), 0.0);
+        assert_eq!(outward.hit_distance(&small, &fine), POSITIVE_INFINITY);
+        // Through the sphere, the far side is still hit.
+        let inward: Ray = Ray::new(origin, Vec3::new(-1.0, 0.0, 0.0), 0.0);
+        assert!((inward.hit_distance(&small, &fine) - 2e-4).abs() < 1e-10);
+        // The default epsilon would be larger than the whole sphere and miss it.
+        let default: Camera = Camera::builder().build();
+        assert_eq!(inward.hit_distance(&small, &default), POSITIVE_INFINITY);
     }
 }
@@ -0,0 +1,444 @@
+use crate::camera::camera::Camera;
+use crate::error::error::RenderError;
+use crate::hittables::hittables::{Hittable, Hittables};
+use crate::hittables::sphere::Sphere;
+use crate::lights::lights::PointLight;
+use crate::materials::arena::{MaterialArena, MaterialId};
+use crate::materials::materials::{
+    Dielectric, DiffuseLight, Isotropic, Lambertian, Metal, RoughMetal, SpotLight,
+};
+use crate::vector::vector::{Color, Point, Vec3};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Error that occurs when the contents of a scene file do not describe a valid scene in the JSON
+/// or TOML format, or when a camera parameter of the scene lies outside its valid range.
+#[derive(Debug)]
+pub enum SceneParseError {
+    Json(serde_json::Error),
+    Toml(toml::de::Error),
+    InvalidCamera {
+        field: &'static str,
+        message: String,
+    },
+}
+
+impl fmt::Display for SceneParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneParseError::Json(err) => write!(f, "invalid JSON scene: {err}"),
+            SceneParseError::Toml(err) => write!(f, "invalid TOML scene: {err}"),
+            SceneParseError::InvalidCamera { field, message } => {
+                write!(f, "invalid scene camera: `{field}` {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SceneParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SceneParseError::Json(err) => Some(err),
+            SceneParseError::Toml(err) => Some(err),
+            SceneParseError::InvalidCamera { .. } => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for RenderError {
+    fn from(err: serde_json::Error) -> Self {
+        RenderError::SceneParse(SceneParseError::Json(err))
+    }
+}
+
+impl From<toml::de::Error> for RenderError {
+    fn from(err: toml::de::Error) -> Self {
+        RenderError::SceneParse(SceneParseError::Toml(err))
+    }
+}
+
+/// Description of a scene, as it is written in a scene file. It mirrors the runtime `Camera` and
+/// the objects of the world, with vectors written as `[x, y, z]` arrays. In TOML files, the
+/// spheres and point lights are written as `[[sphere]]` and `[[light]]` array-of-tables entries.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SceneDescription {
+    #[serde(default)]
+    pub camera: CameraDescription,
+    #[serde(default, alias = "sphere")]
+    pub spheres: Vec<SphereDescription>,
+    #[serde(default, alias = "light")]
+    pub lights: Vec<PointLightDescription>,
+}
+
+/// Description of the base parameters of a `Camera`, see `Camera::initialize`. Parameters that
+/// are left out of the scene file take the value of the default cover image camera.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CameraDescription {
+    pub aspect_ratio: f64,
+    pub image_width: i32,
+    pub center: [f64; 3],
+    pub samples_per_pixel: i32,
+    pub max_depth: i32,
+    pub vfov: f64,
+    pub look_at: [f64; 3],
+    pub vup: [f64; 3],
+    pub defocus_angle: f64,
+    pub focus_dist: f64,
+    pub shutter_open: f64,
+    pub shutter_close: f64,
+}
+
+impl Default for CameraDescription {
+    fn default() -> Self {
+        Self {
+            aspect_ratio: 16.0 / 9.0,
+            image_width: 400,
+            center: [13.0, 2.0, 3.0],
+            samples_per_pixel: 100,
+            max_depth: 50,
+            vfov: 20.0,
+            look_at: [0.0, 0.0, 0.0],
+            vup: [0.0, 1.0, 0.0],
+            defocus_angle: 0.6,
+            focus_dist: 10.0,
+            shutter_open: 0.0,
+            shutter_close: 0.0,
+        }
+    }
+}
+
+/// Description of a `Sphere`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SphereDescription {
+    pub center: [f64; 3],
+    pub radius: f64,
+    pub material: MaterialDescription,
+}
+
+/// Description of a `PointLight`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PointLightDescription {
+    pub position: [f64; 3],
+    pub intensity: [f64; 3],
+}
+
+/// Description of a material, tagged by its `type`, e.g.
+/// `{"type": "lambertian", "albedo": [0.5, 0.5, 0.5]}`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum MaterialDescription {
+    Lambertian {
+        albedo: [f64; 3],
+    },
+    Metal {
+        albedo: [f64; 3],
+        fuzz: f64,
+    },
+    RoughMetal {
+        albedo: [f64; 3],
+        roughness: f64,
+    },
+    Dielectric {
+        #[serde(default = "white")]
+        albedo: [f64; 3],
+        refractive_index: f64,
+        #[serde(default)]
+        absorption: [f64; 3],
+    },
+    DiffuseLight {
+        emission: [f64; 3],
+    },
+    SpotLight {
+        emission: [f64; 3],
+        direction: [f64; 3],
+        inner_angle: f64,
+        outer_angle: f64,
+    },
+    Isotropic {
+        albedo: [f64; 3],
+    },
+}
+
+/// Default albedo of a `Dielectric`, which does not attenuate the light.
+fn white() -> [f64; 3] {
+    return [1.0, 1.0, 1.0];
+}
+
+impl CameraDescription {
+    /// Check the parameters that the command line arguments also restrict: the image width, the
+    /// amount of samples and the maximum depth must be at least one, and the aspect ratio must be
+    /// positive. Without these checks, a scene file could crash the render or silently give a
+    /// black image.
+    pub fn validate(&self) -> Result<(), SceneParseError> {
+        let invalid = |field: &'static str, message: String| {
+            return Err(SceneParseError::InvalidCamera { field, message });
+        };
+        if self.image_width < 1 {
+            return invalid(
+                "image_width",
+                format!("must be at least 1, got {}", self.image_width),
+            );
+        }
+        if self.samples_per_pixel < 1 {
+            return invalid(
+                "samples_per_pixel",
+                format!("must be at least 1, got {}", self.samples_per_pixel),
+            );
+        }
+        if self.max_depth < 1 {
+            return invalid(
+                "max_depth",
+                format!("must be at least 1, got {}", self.max_depth),
+            );
+        }
+        if !(self.aspect_ratio.is_finite() && self.aspect_ratio > 0.0) {
+            return invalid(
+                "aspect_ratio",
+                format!("must be positive, got {}", self.aspect_ratio),
+            );
+        }
+        return Ok(());
+    }
+    /// Build the runtime `Camera`.
+    pub fn build(&self) -> Camera {
+        return Camera::builder()
+            .aspect_ratio(self.aspect_ratio)
+            .width(self.image_width)
+            .center(Point::from(self.center))
+            .samples(self.samples_per_pixel)
+            .max_depth(self.max_depth)
+            .vfov(self.vfov)
+            .look_at(Point::from(self.look_at))
+            .vup(Vec3::from(self.vup))
+            .defocus_angle(self.defocus_angle)
+            .focus_dist(self.focus_dist)
+            .shutter(self.shutter_open, self.shutter_close)
+            .build();
+    }
+}
+
+impl SphereDescription {
+    /// Build the runtime `Sphere`, with its material stored in `materials`.
+    pub fn build(&self, materials: &mut MaterialArena) -> Arc<dyn Hittable> {
+        let material: MaterialId = self.material.build(materials);
+        return Arc::new(Sphere::new(Point::from(self.center), self.radius, material));
+    }
+}
+
+impl MaterialDescription {
+    /// Build the runtime material, store it in `materials` and return its id.
+    pub fn build(&self, materials: &mut MaterialArena) -> MaterialId {
+        return match *self {
+            MaterialDescription::Lambertian { albedo } => {
+                materials.add(Lambertian::new(Color::from(albedo)))
+            }
+            MaterialDescription::Metal { albedo, fuzz } => {
+                materials.add(Metal::new(Color::from(albedo), fuzz))
+            }
+            MaterialDescription::RoughMetal { albedo, roughness } => {
+                materials.add(RoughMetal::new(Color::from(albedo), roughness))
+            }
+            MaterialDescription::Dielectric {
+                albedo,
+                refractive_index,
+                absorption,
+            } => materials.add(Dielectric::with_absorption(
+                Color::from(albedo),
+                refractive_index,
+                Color::from(absorption),
+            )),
+            MaterialDescription::DiffuseLight { emission } => {
+                materials.add(DiffuseLight::new(Color::from(emission)))
+            }
+            MaterialDescription::SpotLight {
+                emission,
+                direction,
+                inner_angle,
+                outer_angle,
+            } => materials.add(SpotLight::new(
+                Color::from(emission),
+                Vec3::from(direction),
+                inner_angle,
+                outer_angle,
+            )),
+            MaterialDescription::Isotropic { albedo } => {
+                materials.add(Isotropic::new(Color::from(albedo)))
+            }
+        };
+    }
+}
+
+impl PointLightDescription {
+    /// Build the runtime `PointLight`.
+    pub fn build(&self) -> PointLight {
+        return PointLight::new(Point::from(self.position), Color::from(self.intensity));
+    }
+}
+
+impl SceneDescription {
+    /// Build the runtime `Camera`, with the lights of the scene, and the world of the scene.
+    pub fn build(&self) -> (Camera, Hittables) {
+        let mut camera: Camera = self.camera.build();
+        for light in self.lights.iter() {
+            camera.lights.push(Box::new(light.build()));
+        }
+        let mut materials: MaterialArena = MaterialArena::new();
+        let spheres: Vec<Arc<dyn Hittable>> = self
+            .spheres
+            .iter()
+            .map(|sphere| sphere.build(&mut materials))
+            .collect();
+        return (camera, Hittables::with_materials(spheres, materials));
+    }
+}
+
+/// Read the contents of the scene file at `path`. The path is added to the message of I/O errors.
+fn read_scene_file(path: &Path) -> Result<String, RenderError> {
+    return std::fs::read_to_string(path).map_err(|err| {
+        let message: String = format!("could not read scene file `{}`: {err}", path.display());
+        RenderError::Io(std::io::Error::new(err.kind(), message))
+    });
+}
+
+/// Check the camera of a scene `description` that was read from a file and build the `Camera` and
+/// the world it describes.
+fn build_checked(description: &SceneDescription) -> Result<(Camera, Hittables), RenderError> {
+    description.camera.validate()?;
+    return Ok(description.build());
+}
+
+/// Read the JSON scene file at `path` and build the `Camera` and the world it describes. Camera
+/// parameters outside their valid range give an error that names the parameter.
+pub fn load_scene(path: &Path) -> Result<(Camera, Hittables), RenderError> {
+    let contents: String = read_scene_file(path)?;
+    let description: SceneDescription = serde_json::from_str(&contents)?;
+    return build_checked(&description);
+}
+
+/// Read the TOML scene file at `path` and build the `Camera` and the world it describes. Camera
+/// parameters outside their valid range give an error that names the parameter.
+pub fn load_scene_toml(path: &Path) -> Result<(Camera, Hittables), RenderError> {
+    let contents: String = read_scene_file(path)?;
+    let description: SceneDescription = toml::from_str(&contents)?;
+    return build_checked(&description);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn two_sphere_scene_round_trips_through_json() {
+        let description: SceneDescription = SceneDescription {
+            camera: CameraDescription {
+                image_width: 32,
+                samples_per_pixel: 4,
+                ..CameraDescription::default()
+            },
+            spheres: vec![
+                SphereDescription {
+                    center: [0.0, -1000.0, 0.0],
+                    radius: 1000.0,
+                    material: MaterialDescription::Lambertian {
+                        albedo: [0.5, 0.5, 0.5],
+                    },
+                },
+                SphereDescription {
+                    center: [0.0, 1.0, 0.0],
+                    radius: 1.0,
+                    material: MaterialDescription::Metal {
+                        albedo: [0.7, 0.6, 0.5],
+                        fuzz: 0.1,
+                    },
+                },
+            ],
+            lights: Vec::new(),
+        };
+        let json: String = serde_json::to_string(&description).unwrap();
+        assert!(json.contains(r#""type":"lambertian""#));
+        let parsed: SceneDescription = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, description);
+
+        // The file is loaded into a world with both spheres.
+        let path = std::env::temp_dir().join(format!("scene-{}.json", std::process::id()));
+        std::fs::write(&path, &json).unwrap();
+        let loaded = load_scene(&path);
+        std::fs::remove_file(&path).unwrap();
+        let (camera, world) = loaded.unwrap();
+        assert_eq!(camera.image_width, 32);
+        assert_eq!(world.len(), 2);
+    }
+
+    #[test]
+    fn json_scene_with_zero_width_is_rejected() {
+        let path = std::env::temp_dir().join(format!("zero-width-{}.json", std::process::id()));
+        std::fs::write(&path, r#"{"camera":{"image_width":0}}"#).unwrap();
+        let loaded = load_scene(&path);
+        std::fs::remove_file(&path).unwrap();
+        match loaded {
+            Err(err) => assert!(err.to_string().contains("image_width"), "{err}"),
+            Ok(_) => panic!("a scene without pixels should not load"),
+        }
+
+        // The other camera parameters are checked as well.
+        let mut camera: CameraDescription = CameraDescription {
+            samples_per_pixel: 0,
+            ..CameraDescription::default()
+        };
+        assert!(camera.validate().is_err());
+        camera.samples_per_pixel = 1;
+        camera.max_depth = -3;
+        assert!(camera.validate().is_err());
+        camera.max_depth = 1;
+        camera.aspect_ratio = 0.0;
+        assert!(camera.validate().is_err());
+        camera.aspect_ratio = 1.5;
+        assert!(camera.validate().is_ok());
+    }
+
+    #[test]
+    fn toml_scene_with_one_lambertian_sphere() {
+        let contents: &str = r#"
+            [camera]
+            image_width = 64
+
+            [[sphere]]
+            center = [0.0, 0.0, -1.0]
+            radius = 0.5
+            material = { type = "lambertian", albedo = [0.1, 0.2, 0.5] }
+        "#;
+        let description: SceneDescription = toml::from_str(contents).unwrap();
+        assert_eq!(
+            description.spheres[0].material,
+            MaterialDescription::Lambertian {
+                albedo: [0.1, 0.2, 0.5]
+            }
+        );
+        let (camera, world) = description.build();
+        assert_eq!(camera.image_width, 64);
+        assert_eq!(world.len(), 1);
+
+        // Unknown material types are reported by name.
+        let unknown: String = contents.replace("lambertian", "velvet");
+        let err: toml::de::Error = toml::from_str::<SceneDescription>(&unknown).unwrap_err();
+        assert!(err.to_string().contains("velvet"));
+    }
+
+    #[test]
+    fn toml_scene_with_zero_width_is_rejected() {
+        let path = std::env::temp_dir().join(format!("zero-width-{}.toml", std::process::id()));
+        std::fs::write(&path, "[camera]\nimage_width = 0\n").unwrap();
+        let loaded = load_scene_toml(&path);
+        std::fs::remove_file(&path).unwrap();
+        match loaded {
+            Err(err) => assert!(err.to_string().contains("image_width"), "{err}"),
+            Ok(_) => panic!("a scene without pixels should not load"),
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use crate::camera::camera::Camera;
+use crate::environment::environment::GradientSky;
+use crate::hittables::boxprim::BoxPrim;
+use crate::hittables::hittables::{Hittable, Hittables};
+use crate::hittables::quad::Quad;
+use crate::hittables::rotate::RotateY;
+use crate::hittables::sphere::Sphere;
+use crate::hittables::translate::Translate;
+use crate::materials::arena::MaterialId;
+use crate::materials::materials::{Dielectric, DiffuseLight, Lambertian, Metal};
+use crate::util::utils;
+use crate::vector::vector::{Color, Point, Vec3};
+use std::sync::Arc;
+
+/// Create the Cornell box, the standard test scene for light transport. It is a room of 555 units
+/// on every side with a green wall on the left, a red wall on the right, white floor, ceiling and
+/// back wall, and a square light in the middle of the ceiling. Two white boxes, a tall one and a
+/// short one, are rotated a bit about the y-axis and placed on the floor. The camera looks into
+/// the box through the open front side, and the light is sampled directly, see
+/// `CameraBuilder::light_surface`. Only the light lights the scene, the background is black.
+pub fn cornell_box() -> (Camera, Hittables) {
+    let mut world: Hittables = Hittables::init();
+
+    let red: MaterialId = world.add_material(Lambertian::new(Color::new(0.65, 0.05, 0.05)));
+    let white: MaterialId = world.add_material(Lambertian::new(Color::new(0.73, 0.73, 0.73)));
+    let green: MaterialId = world.add_material(Lambertian::new(Color::new(0.12, 0.45, 0.15)));
+    let light: MaterialId = world.add_material(DiffuseLight::new(Color::new(15.0, 15.0, 15.0)));
+
+    // Side walls, the camera looks along +z, so the wall at x = 555 is on the left.
+    world.add(Arc::new(Quad::new(
+        Point::new(555.0, 0.0, 0.0),
+        Vec3::new(0.0, 555.0, 0.0),
+        Vec3::new(0.0, 0.0, 555.0),
+        green,
+    )));
+    world.add(Arc::new(Quad::new(
+        Point::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 555.0, 0.0),
+        Vec3::new(0.0, 0.0, 555.0),
+        red,
+    )));
+    // Floor
+    world.add(Arc::new(Quad::new(
+        Point::new(0.0, 0.0, 0.0),
+        Vec3::new(555.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, 555.0),
+        white,
+    )));
+    // Ceiling
+    world.add(Arc::new(Quad::new(
+        Point::new(555.0, 555.0, 555.0),
+        Vec3::new(-555.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, -555.0),
+        white,
+    )));
+    // Back wall
+    world.add(Arc::new(Quad::new(
+        Point::new(0.0, 0.0, 555.0),
+        Vec3::new(555.0, 0.0, 0.0),
+        Vec3::new(0.0, 555.0, 0.0),
+        white,
+    )));
+
+    // The light hangs just below the ceiling, so that it is not hidden by it.
+    let ceiling_light: Arc<dyn Hittable> = Arc::new(Quad::new(
+        Point::new(343.0, 554.0, 332.0),
+        Vec3::new(-130.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, -105.0),
+        light,
+    ));
+    world.add(ceiling_light.clone());
+
+    // Tall box at the back left and short box at the front right.
+    let tall_box: Arc<dyn Hittable> = Arc::new(BoxPrim::new(
+        Point::new(0.0, 0.0, 0.0),
+        Point::new(165.0, 330.0, 165.0),
+        white,
+    ));
+    let tall_box: Arc<dyn Hittable> = Arc::new(RotateY::new(tall_box, 15.0));
+    world.add(Arc::new(Translate::new(
+        tall_box,
+        Vec3::new(265.0, 0.0, 295.0),
+    )));
+    let short_box: Arc<dyn Hittable> = Arc::new(BoxPrim::new(
+        Point::new(0.0, 0.0, 0.0),
+        Point::new(165.0, 165.0, 165.0),
+        white,
+    ));
+    let short_box: Arc<dyn Hittable> = Arc::new(RotateY::new(short_box, -18.0));
+    world.add(Arc::new(Translate::new(
+        short_box,
+        Vec3::new(130.0, 0.0, 65.0),
+    )));
+
+    let black: Color = Color::new(0.0, 0.0, 0.0);
+    let camera: Camera = Camera::builder()
+        .aspect_ratio(1.0)
+        .width(600)
+        .samples(200)
+        .max_depth(50)
+        .center(Point::new(278.0, 278.0, -800.0))
+        .look_at(Point::new(278.0, 278.0, 0.0))
+        .vup(Vec3::new(0.0, 1.0, 0.0))
+        .vfov(40.0)
+        .defocus_angle(0.0)
+        .environment(Box::new(GradientSky::new(black, black)))
+        .light_surface(ceiling_light)
+        .build();
+
+    return (camera, world);
+}
+
+/// Create the world of the cover image, a large amount of small random spheres around three large
+/// spheres. The random numbers of the current thread are seeded with `seed` first, see
+/// `utils::seed_rng`, so the same seed always gives the same world. Spheres that would overlap the
+/// large metal sphere are left out, so the number of spheres depends on the seed.
+pub fn random_spheres(seed: u64) -> Hittables {
+    utils::seed_rng(seed);
+
+    // Define the world -- cover image
+    let mut world: Hittables = Hittables::init();
+
+    let material_ground = world.add_material(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    // All the glass spheres share the same material.
+    let material_glass = world.add_material(Dielectric::new(Color::new(1.0, 1.0, 1.0), 1.5));
+    world.add(Arc::new(Sphere::new(
+        Point::new(0.0, -1000.0, 0.0),
+        1000.0,
+        material_ground,
+    )));
+
+    for a in -11..11 {
+        for b in -11..11 {
+            let choose_mat: f64 = utils::get_random();
+            let sphere_center: Point = Point::new(
+                a as f64 + 0.9 * utils::get_random(),
+                0.2,
+                b as f64 + 0.9 * utils::get_random(),
+            );
+
+            if (sphere_center - Point::new(4.0, 0.2, 0.0)).length() > 0.9 {
+                if choose_mat < 0.8 {
+                    // Diffuse
+                    let albedo = Color::get_random_vector() * Color::get_random_vector();
+                    let sphere_material = world.add_material(Lambertian::new(albedo));
+                    world.add(Arc::new(Sphere::new(sphere_center, 0.2, sphere_material)));
+                } else if choose_mat < 0.95 {
+                    // Metal
+                    let albedo = Color::get_random_vector_in_range(0.5, 1.0);
+                    let fuzz = utils::get_random_in_range(0.5, 1.0);
+                    let sphere_material = world.add_material(Metal::new(albedo, fuzz));
+                    world.add(Arc::new(Sphere::new(sphere_center, 0.2, sphere_material)));
+                } else {
+                    // Glass
+                    world.add(Arc::new(Sphere::new(sphere_center, 0.2, material_glass)));
+                }
+            }
+        }
+    }
+
+    world.add(Arc::new(Sphere::new(
+        Point::new(0.0, 1.0, 0.0),
+        1.0,
+        material_glass,
+    )));
+
+    let material_2 = world.add_material(Lambertian::new(Color::new(0.4, 0.2, 0.1)));
+    world.add(Arc::new(Sphere::new(
+        Point::new(-4.0, 1.0, 0.0),
+        1.0,
+        material_2,
+    )));
+
+    let material_3 = world.add_material(Metal::new(Color::new(0.7, 0.6, 0.5), 0.0));
+    world.add(Arc::new(Sphere::new(
+        Point::new(4.0, 1.0, 0.0),
+        1.0,
+        material_3,
+    )));
+
+    return world;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hittables::record::HitRecord;
+    use crate::raycaster::ray::Ray;
+    use crate::util::utils::{Interval, POSITIVE_INFINITY};
+
+    #[test]
+    fn cornell_box_has_its_surfaces_and_the_camera_looks_inside() {
+        let (camera, world) = cornell_box();
+        // Five walls, the light and two boxes.
+        assert_eq!(world.len(), 8);
+        assert_eq!(camera.light_surfaces.len(), 1);
+        assert!(world.material(MaterialId(3)).is_some());
+        assert!(world.material(MaterialId(4)).is_none());
+
+        // The camera in front of the open side looks along +z, straight at the back wall.
+        assert!((camera.w - Vec3::new(0.0, 0.0, -1.0)).length() < 1e-12);
+        assert!(camera.center.z < 0.0);
+        let ray: Ray = Ray::new(camera.center, -camera.w, 0.0);
+        let hit_record: HitRecord = world.ray_hit(&ray, Interval::new(0.001, POSITIVE_INFINITY));
+        assert!(hit_record.hit);
+        assert!(hit_record.point.z > 0.0 && hit_record.point.z <= 555.0);
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_random_spheres() {
+        let lengths: Vec<usize> = (0..8).map(|seed| random_spheres(seed).len()).collect();
+        for seed in 0..8 {
+            assert_eq!(random_spheres(seed).len(), lengths[seed as usize]);
+        }
+        // The spheres near the large metal sphere are left out, which depends on the seed.
+        assert!(lengths.iter().any(|length| *length != lengths[0]));
+    }
+}
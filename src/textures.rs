@@ -0,0 +1,4 @@
+pub mod image_texture;
+pub mod perlin;
+pub mod textures;
+pub mod worley;
@@ -0,0 +1,114 @@
+use super::textures::Texture;
+use crate::util::utils::{srgb_to_linear, Interval};
+use crate::vector::vector::{Color, Point};
+use image::DynamicImage;
+use std::sync::Arc;
+
+/// An `ImageTexture` maps an image onto a surface using the surface coordinates (u,v). The pixels
+/// are stored as linear colors in scanline order, and are shared between clones of the texture.
+/// If the image could not be loaded, the texture has no pixels and returns magenta everywhere, so
+/// the problem is visible in the render.
+#[derive(Clone, Debug)]
+pub struct ImageTexture {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Arc<[Color]>,
+}
+
+impl ImageTexture {
+    /// Create new instance of `ImageTexture` by loading the image at `path`. Any format supported
+    /// by the `image` crate with the enabled features (PNG, JPEG and HDR) can be loaded.
+    pub fn new(path: &str) -> Self {
+        let image = match image::open(path) {
+            Ok(image) => image,
+            Err(err) => {
+                log::error!("Error loading image texture `{path}`: {err}");
+                return Self {
+                    width: 0,
+                    height: 0,
+                    pixels: Arc::new([]),
+                };
+            }
+        };
+        let pixels: Arc<[Color]> = match image {
+            // Floating point images, such as HDR images, already store linear values.
+            DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_) => image
+                .to_rgb32f()
+                .pixels()
+                .map(|pixel| Color::from(pixel.0.map(|channel| channel as f64)))
+                .collect(),
+            // Convert the sRGB encoded bytes into linear colors.
+            _ => image
+                .to_rgb8()
+                .pixels()
+                .map(|pixel| {
+                    Color::from(
+                        pixel
+                            .0
+                            .map(|channel| srgb_to_linear(channel as f64 / 255.0)),
+                    )
+                })
+                .collect(),
+        };
+        Self {
+            width: image.width() as usize,
+            height: image.height() as usize,
+            pixels,
+        }
+    }
+}
+
+impl Texture for ImageTexture {
+    /// Sample the pixel nearest to the surface coordinates (u,v). The coordinates are clamped to
+    /// [0,1], and `v` is flipped since images are stored from top to bottom.
+    fn value(&self, u: f64, v: f64, _point: &Point) -> Color {
+        // Without image data, return magenta to make the problem visible.
+        if self.pixels.is_empty() {
+            return Color::new(1.0, 0.0, 1.0);
+        }
+
+        let unit_interval: Interval = Interval::new(0.0, 1.0);
+        let u: f64 = unit_interval.clamp(u);
+        let v: f64 = 1.0 - unit_interval.clamp(v);
+
+        let i: usize = ((u * self.width as f64) as usize).min(self.width - 1);
+        let j: usize = ((v * self.height as f64) as usize).min(self.height - 1);
+        return self.pixels[j * self.width + i];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::RgbImage;
+
+    #[test]
+    fn corners_of_a_two_by_two_checkerboard() {
+        // White in the top left and bottom right, black elsewhere.
+        let bytes: Vec<u8> = vec![255, 255, 255, 0, 0, 0, 0, 0, 0, 255, 255, 255];
+        let path = std::env::temp_dir().join(format!("checker-{}.png", std::process::id()));
+        RgbImage::from_raw(2, 2, bytes)
+            .unwrap()
+            .save(&path)
+            .unwrap();
+        let texture: ImageTexture = ImageTexture::new(path.to_str().unwrap());
+        std::fs::remove_file(&path).unwrap();
+
+        let white: Color = Color::new(1.0, 1.0, 1.0);
+        let black: Color = Color::new(0.0, 0.0, 0.0);
+        let origin: Point = Point::new(0.0, 0.0, 0.0);
+        assert_eq!((texture.width, texture.height), (2, 2));
+        // The top of the image is at v = 1.
+        assert_eq!(texture.value(0.0, 1.0, &origin), white);
+        assert_eq!(texture.value(1.0, 1.0, &origin), black);
+        assert_eq!(texture.value(0.0, 0.0, &origin), black);
+        assert_eq!(texture.value(1.0, 0.0, &origin), white);
+    }
+
+    #[test]
+    fn missing_file_gives_magenta() {
+        let texture: ImageTexture = ImageTexture::new("does/not/exist.png");
+        let color: Color = texture.value(0.5, 0.5, &Point::new(0.0, 0.0, 0.0));
+        assert_eq!(color, Color::new(1.0, 0.0, 1.0));
+    }
+}
@@ -0,0 +1,184 @@
+use super::textures::Texture;
+use crate::util::utils::get_random;
+use crate::vector::vector::{Color, Point, Vec3};
+use std::sync::Arc;
+
+/// Number of random gradient vectors in the lattice, must be a power of two.
+pub const POINT_COUNT: usize = 256;
+
+/// Perlin noise generator. Every point of the integer lattice gets a random unit gradient vector,
+/// selected by hashing the lattice coordinates through three random permutations. The noise in
+/// between lattice points is a smooth interpolation of these gradients.
+/// See: <https://raytracing.github.io/books/RayTracingTheNextWeek.html#perlinnoise>
+#[derive(Clone, Debug)]
+pub struct Perlin {
+    pub random_vectors: Vec<Vec3>,
+    pub perm_x: Vec<usize>,
+    pub perm_y: Vec<usize>,
+    pub perm_z: Vec<usize>,
+}
+
+impl Perlin {
+    /// Create new instance of `Perlin` with random gradients and permutations.
+    pub fn new() -> Self {
+        Self {
+            random_vectors: (0..POINT_COUNT)
+                .map(|_| Vec3::get_random_vector_in_range(-1.0, 1.0).unit_vector())
+                .collect(),
+            perm_x: generate_permutation(),
+            perm_y: generate_permutation(),
+            perm_z: generate_permutation(),
+        }
+    }
+    /// Get the noise value at `point`, which lies in [-1,1]. The gradients at the eight
+    /// surrounding lattice points are dotted with the offsets to `point` and trilinearly
+    /// interpolated, with a Hermite cubic to smooth out the lattice.
+    pub fn noise(&self, point: &Point) -> f64 {
+        // Position within the lattice cell.
+        let u: f64 = point.x - point.x.floor();
+        let v: f64 = point.y - point.y.floor();
+        let w: f64 = point.z - point.z.floor();
+        // Lattice coordinates of the cell.
+        let i: i64 = point.x.floor() as i64;
+        let j: i64 = point.y.floor() as i64;
+        let k: i64 = point.z.floor() as i64;
+        let mask: i64 = POINT_COUNT as i64 - 1;
+
+        // Get the gradient vectors at the corners of the cell.
+        let mut corners: [[[Vec3; 2]; 2]; 2] = [[[Vec3::new(0.0, 0.0, 0.0); 2]; 2]; 2];
+        for (di, plane) in corners.iter_mut().enumerate() {
+            for (dj, line) in plane.iter_mut().enumerate() {
+                for (dk, corner) in line.iter_mut().enumerate() {
+                    let index: usize = self.perm_x[((i + di as i64) & mask) as usize]
+                        ^ self.perm_y[((j + dj as i64) & mask) as usize]
+                        ^ self.perm_z[((k + dk as i64) & mask) as usize];
+                    *corner = self.random_vectors[index];
+                }
+            }
+        }
+        return perlin_interpolation(&corners, u, v, w);
+    }
+    /// Get the turbulence at `point`, which is the absolute value of a sum of `depth` octaves of
+    /// noise, each with double the frequency and half the weight of the previous one.
+    pub fn turbulence(&self, point: &Point, depth: i32) -> f64 {
+        let mut accumulated: f64 = 0.0;
+        let mut current_point: Point = *point;
+        let mut weight: f64 = 1.0;
+
+        for _ in 0..depth {
+            accumulated += weight * self.noise(&current_point);
+            weight *= 0.5;
+            current_point *= 2.0;
+        }
+        return accumulated.abs();
+    }
+}
+
+impl Default for Perlin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Generate a random permutation of the numbers 0 to `POINT_COUNT - 1` with a Fisher-Yates
+/// shuffle.
+pub fn generate_permutation() -> Vec<usize> {
+    let mut permutation: Vec<usize> = (0..POINT_COUNT).collect();
+    for i in (1..POINT_COUNT).rev() {
+        let target: usize = ((get_random() * (i + 1) as f64) as usize).min(i);
+        permutation.swap(i, target);
+    }
+    return permutation;
+}
+
+/// Trilinearly interpolate the contributions of the gradient vectors at the eight corners of a
+/// lattice cell, at the position (u,v,w) within the cell. The position is first smoothed with the
+/// Hermite cubic $3t^2 - 2t^3$.
+fn perlin_interpolation(corners: &[[[Vec3; 2]; 2]; 2], u: f64, v: f64, w: f64) -> f64 {
+    let uu: f64 = u * u * (3.0 - 2.0 * u);
+    let vv: f64 = v * v * (3.0 - 2.0 * v);
+    let ww: f64 = w * w * (3.0 - 2.0 * w);
+
+    let mut accumulated: f64 = 0.0;
+    for (i, plane) in corners.iter().enumerate() {
+        for (j, line) in plane.iter().enumerate() {
+            for (k, corner) in line.iter().enumerate() {
+                let (fi, fj, fk) = (i as f64, j as f64, k as f64);
+                let offset: Vec3 = Vec3::new(u - fi, v - fj, w - fk);
+                accumulated += (fi * uu + (1.0 - fi) * (1.0 - uu))
+                    * (fj * vv + (1.0 - fj) * (1.0 - vv))
+                    * (fk * ww + (1.0 - fk) * (1.0 - ww))
+                    * corner.dot(&offset);
+            }
+        }
+    }
+    return accumulated;
+}
+
+/// A `NoiseTexture` gives a marble-like pattern: gray bands along the z-axis whose phase is
+/// disturbed by Perlin turbulence. The `scale` sets the frequency of the bands. The noise
+/// generator is shared between clones of the texture.
+#[derive(Clone, Debug)]
+pub struct NoiseTexture {
+    pub noise: Arc<Perlin>,
+    pub scale: f64,
+}
+
+impl NoiseTexture {
+    /// Create new instance of `NoiseTexture` with a new random noise generator.
+    pub fn new(scale: f64) -> Self {
+        Self {
+            noise: Arc::new(Perlin::new()),
+            scale,
+        }
+    }
+}
+
+impl Texture for NoiseTexture {
+    /// The sine of the scaled z-coordinate, shifted by the turbulence, mapped onto [0,1] gray.
+    fn value(&self, _u: f64, _v: f64, point: &Point) -> Color {
+        let phase: f64 = self.scale * point.z + 10.0 * self.noise.turbulence(point, 7);
+        return Color::new(0.5, 0.5, 0.5) * (1.0 + phase.sin());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::utils::{get_random_in_range, seed_rng};
+
+    #[test]
+    fn noise_is_bounded() {
+        seed_rng(22);
+        let perlin: Perlin = Perlin::new();
+        for _ in 0..10_000 {
+            let point: Point = Point::new(
+                get_random_in_range(-50.0, 50.0),
+                get_random_in_range(-50.0, 50.0),
+                get_random_in_range(-50.0, 50.0),
+            );
+            assert!(perlin.noise(&point).abs() <= 1.0);
+        }
+    }
+
+    #[test]
+    fn noise_is_continuous_across_lattice_boundaries() {
+        seed_rng(22);
+        let perlin: Perlin = Perlin::new();
+        let epsilon: f64 = 1e-7;
+        for boundary in -3..4 {
+            for offset in [0.1, 0.37, 0.8] {
+                for axis in 0..3 {
+                    // A point on the cell face, moved slightly to either side of it.
+                    let mut on_face: Point = Point::new(offset, offset + 0.2, offset - 0.3);
+                    on_face[axis] = boundary as f64;
+                    let mut step: Vec3 = Vec3::new(0.0, 0.0, 0.0);
+                    step[axis] = epsilon;
+                    let below: f64 = perlin.noise(&(on_face - step));
+                    let above: f64 = perlin.noise(&(on_face + step));
+                    assert!((below - above).abs() < 1e-5);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use crate::util::utils::Interval;
+use crate::vector::vector::{Color, Point};
+use std::sync::Arc;
+
+/// Any `Texture` should implement what color the surface has at a given location. The location
+/// is given both by the surface coordinates (u,v) and by the `point` in 3D space. Textures are
+/// owned by materials which are shared between the rendering threads, hence they must be
+/// `Send + Sync`.
+pub trait Texture: Send + Sync {
+    fn value(&self, u: f64, v: f64, point: &Point) -> Color;
+}
+
+/// A `SolidColor` texture has the same color `albedo` everywhere.
+#[derive(Clone, Copy, Debug)]
+pub struct SolidColor {
+    pub albedo: Color,
+}
+
+impl SolidColor {
+    /// Create new instance of `SolidColor`.
+    pub fn new(albedo: Color) -> Self {
+        Self { albedo }
+    }
+}
+
+impl Texture for SolidColor {
+    /// A `SolidColor` returns its `albedo` regardless of the location.
+    fn value(&self, _u: f64, _v: f64, _point: &Point) -> Color {
+        return self.albedo;
+    }
+}
+
+/// A `CheckerTexture` divides space into cubes of side `1 / frequency` and alternates between the
+/// `even` and `odd` texture from one cube to the next, like a 3D checkerboard.
+#[derive(Clone)]
+pub struct CheckerTexture {
+    pub frequency: f64,
+    pub even: Arc<dyn Texture>,
+    pub odd: Arc<dyn Texture>,
+}
+
+impl CheckerTexture {
+    /// Create new instance of `CheckerTexture`.
+    pub fn new(frequency: f64, even: Arc<dyn Texture>, odd: Arc<dyn Texture>) -> Self {
+        Self {
+            frequency,
+            even,
+            odd,
+        }
+    }
+    /// Create new instance of `CheckerTexture` alternating between two solid colors.
+    pub fn from_colors(frequency: f64, even: Color, odd: Color) -> Self {
+        Self::new(
+            frequency,
+            Arc::new(SolidColor::new(even)),
+            Arc::new(SolidColor::new(odd)),
+        )
+    }
+}
+
+impl Texture for CheckerTexture {
+    /// The cube containing `point` is identified by the floored, scaled coordinates. The parity
+    /// of their sum determines which texture is used.
+    fn value(&self, u: f64, v: f64, point: &Point) -> Color {
+        let x: i64 = (self.frequency * point.x).floor() as i64;
+        let y: i64 = (self.frequency * point.y).floor() as i64;
+        let z: i64 = (self.frequency * point.z).floor() as i64;
+
+        if (x + y + z).rem_euclid(2) == 0 {
+            return self.even.value(u, v, point);
+        } else {
+            return self.odd.value(u, v, point);
+        }
+    }
+}
+
+/// Coordinate axis of a point in 3D space.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+impl Axis {
+    /// Get the coordinate of `point` along the axis.
+    pub fn coordinate(&self, point: &Point) -> f64 {
+        match self {
+            Axis::X => {
+                return point.x;
+            }
+            Axis::Y => {
+                return point.y;
+            }
+            Axis::Z => {
+                return point.z;
+            }
+        }
+    }
+}
+
+/// A `GradientTexture` blends linearly from the color `start` to the color `end` along one `axis`
+/// of the hit point. The blend runs over the `range`
+/// of that coordinate, below the range the color is `start` and above it `end`. This is handy for
+/// a sky projected onto geometry or for debugging.
+#[derive(Clone, Copy, Debug)]
+pub struct GradientTexture {
+    pub start: Color,
+    pub end: Color,
+    pub axis: Axis,
+    pub range: Interval,
+}
+
+impl GradientTexture {
+    /// Create new instance of `GradientTexture`.
+    pub fn new(start: Color, end: Color, axis: Axis, range: Interval) -> Self {
+        Self {
+            start,
+            end,
+            axis,
+            range,
+        }
+    }
+}
+
+impl Texture for GradientTexture {
+    /// The coordinate along the `axis` is clamped to the `range` and its relative position in
+    /// the range is the interpolation parameter. A range without size switches from `start` to
+    /// `end` at once.
+    fn value(&self, _u: f64, _v: f64, point: &Point) -> Color {
+        let coordinate: f64 = self.axis.coordinate(point);
+        if self.range.size() <= 0.0 {
+            if coordinate < self.range.min {
+                return self.start;
+            } else {
+                return self.end;
+            }
+        }
+        let t: f64 = (self.range.clamp(coordinate) - self.range.min) / self.range.size();
+        return self.start.lerp(&self.end, t);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checker_alternates_between_adjacent_integer_cells() {
+        let even: Color = Color::new(1.0, 1.0, 1.0);
+        let odd: Color = Color::new(0.2, 0.3, 0.1);
+        let checker: CheckerTexture = CheckerTexture::from_colors(1.0, even, odd);
+        assert_eq!(checker.value(0.0, 0.0, &Point::new(0.5, 0.5, 0.5)), even);
+        assert_eq!(checker.value(0.0, 0.0, &Point::new(1.5, 0.5, 0.5)), odd);
+        assert_eq!(checker.value(0.0, 0.0, &Point::new(1.5, 1.5, 0.5)), even);
+        assert_eq!(checker.value(0.0, 0.0, &Point::new(-0.5, 0.5, 0.5)), odd);
+        // Doubling the frequency halves the cells.
+        let fine: CheckerTexture = CheckerTexture::from_colors(2.0, even, odd);
+        assert_eq!(fine.value(0.0, 0.0, &Point::new(0.75, 0.25, 0.25)), odd);
+    }
+
+    #[test]
+    fn gradient_blends_over_the_range_and_clamps_outside() {
+        let start: Color = Color::new(1.0, 0.0, 0.0);
+        let end: Color = Color::new(0.0, 0.0, 1.0);
+        let gradient: GradientTexture =
+            GradientTexture::new(start, end, Axis::Y, Interval::new(-1.0, 3.0));
+        let at_height = |y: f64| gradient.value(0.0, 0.0, &Point::new(5.0, y, -7.0));
+        assert_eq!(at_height(1.0), Color::new(0.5, 0.0, 0.5));
+        assert_eq!(at_height(0.0), Color::new(0.75, 0.0, 0.25));
+        assert_eq!(at_height(-1.0), start);
+        assert_eq!(at_height(-10.0), start);
+        assert_eq!(at_height(3.0), end);
+        assert_eq!(at_height(10.0), end);
+
+        // Only the chosen axis matters.
+        let along_x: GradientTexture =
+            GradientTexture::new(start, end, Axis::X, Interval::new(-1.0, 3.0));
+        assert_eq!(
+            along_x.value(0.0, 0.0, &Point::new(1.0, 10.0, -10.0)),
+            Color::new(0.5, 0.0, 0.5)
+        );
+    }
+}
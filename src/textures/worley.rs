@@ -0,0 +1,128 @@
+use super::perlin::{generate_permutation, POINT_COUNT};
+use super::textures::Texture;
+use crate::vector::vector::{Color, Point, Vec3};
+use std::sync::Arc;
+
+/// Worley, or cellular, noise generator. Space is divided into unit cubes, and every cube holds
+/// one feature point at a random position within it. The position is selected by hashing the
+/// cube coordinates through three random permutations, as for the gradients of `Perlin`, so the
+/// feature points repeat after `POINT_COUNT` cubes.
+/// See: <https://dl.acm.org/doi/10.1145/237170.237267>
+#[derive(Clone, Debug)]
+pub struct Worley {
+    pub feature_offsets: Vec<Vec3>,
+    pub perm_x: Vec<usize>,
+    pub perm_y: Vec<usize>,
+    pub perm_z: Vec<usize>,
+}
+
+impl Worley {
+    /// Create new instance of `Worley` with random feature points and permutations.
+    pub fn new() -> Self {
+        Self {
+            feature_offsets: (0..POINT_COUNT)
+                .map(|_| Vec3::get_random_vector())
+                .collect(),
+            perm_x: generate_permutation(),
+            perm_y: generate_permutation(),
+            perm_z: generate_permutation(),
+        }
+    }
+    /// Get the feature point of the cube with lattice coordinates (i,j,k).
+    pub fn feature_point(&self, i: i64, j: i64, k: i64) -> Point {
+        let mask: i64 = POINT_COUNT as i64 - 1;
+        let index: usize = self.perm_x[(i & mask) as usize]
+            ^ self.perm_y[(j & mask) as usize]
+            ^ self.perm_z[(k & mask) as usize];
+        return Point::new(i as f64, j as f64, k as f64) + self.feature_offsets[index];
+    }
+    /// Get the distance from `point` to the nearest feature point, called F1. Only the cube
+    /// containing `point` and its 26 neighbours are searched, which almost always contain the
+    /// nearest feature point. The distance is continuous in `point`, also across the faces of
+    /// the cubes, and mostly lies in [0,1].
+    pub fn distance(&self, point: &Point) -> f64 {
+        let i: i64 = point.x.floor() as i64;
+        let j: i64 = point.y.floor() as i64;
+        let k: i64 = point.z.floor() as i64;
+
+        let mut nearest_squared: f64 = f64::INFINITY;
+        for di in -1..=1 {
+            for dj in -1..=1 {
+                for dk in -1..=1 {
+                    let feature: Point = self.feature_point(i + di, j + dj, k + dk);
+                    nearest_squared = nearest_squared.min((feature - *point).length_squared());
+                }
+            }
+        }
+        return nearest_squared.sqrt();
+    }
+}
+
+impl Default for Worley {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A `WorleyTexture` gives a cellular pattern, e.g. for stone, scales or cracked surfaces: the
+/// gray value is the distance to the nearest feature point of Worley noise, so it is black at the
+/// feature points and lightens towards the borders between the cells. The `frequency` sets the
+/// amount of cells per unit length. The noise generator is shared between clones of the texture.
+#[derive(Clone, Debug)]
+pub struct WorleyTexture {
+    pub noise: Arc<Worley>,
+    pub frequency: f64,
+}
+
+impl WorleyTexture {
+    /// Create new instance of `WorleyTexture` with a new random noise generator.
+    pub fn new(frequency: f64) -> Self {
+        Self {
+            noise: Arc::new(Worley::new()),
+            frequency,
+        }
+    }
+}
+
+impl Texture for WorleyTexture {
+    /// The distance to the nearest feature point at the scaled `point`, clamped to [0,1] gray.
+    fn value(&self, _u: f64, _v: f64, point: &Point) -> Color {
+        let distance: f64 = self.noise.distance(&(*point * self.frequency)).min(1.0);
+        return Color::new(distance, distance, distance);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::utils::{get_random_in_range, seed_rng};
+
+    #[test]
+    fn value_has_no_seams_across_grid_lines() {
+        seed_rng(104);
+        let texture: WorleyTexture = WorleyTexture::new(2.0);
+        let epsilon: f64 = 1e-9;
+        for _ in 0..1_000 {
+            let point: Point = Point::new(
+                get_random_in_range(-20.0, 20.0),
+                get_random_in_range(-20.0, 20.0),
+                get_random_in_range(-20.0, 20.0),
+            );
+            // Move each coordinate in turn onto a grid line of the scaled noise, at x = 0.5 etc.
+            for axis in 0..3 {
+                let mut below: Point = point;
+                below[axis] = (point[axis] * 2.0).round() / 2.0 - epsilon;
+                let mut above: Point = below;
+                above[axis] += 2.0 * epsilon;
+                assert_ne!(
+                    (below[axis] * 2.0).floor(),
+                    (above[axis] * 2.0).floor(),
+                    "the points should lie in different cells"
+                );
+                let difference: f64 =
+                    (texture.value(0.0, 0.0, &below).x - texture.value(0.0, 0.0, &above).x).abs();
+                assert!(difference < 1e-6, "seam of {difference} at {below:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use crate::util::utils;
+use crate::vector::vector::{Point, Vec3};
+use std::ops::Mul;
+
+/// A 4x4 matrix for affine transformations in homogeneous coordinates, stored in row-major
+/// order. Points are transformed with homogeneous coordinate $w = 1$, so they are affected by
+/// translations, while vectors are transformed with $w = 0$, so they are not.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Mat4 {
+    pub m: [[f64; 4]; 4],
+}
+
+/// Implement matrix multiplication of `Mat4` structures. The product `a * b` first applies `b` and
+/// then `a`.
+impl Mul for Mat4 {
+    type Output = Self;
+    fn mul(self, other: Self) -> Self {
+        let mut m: [[f64; 4]; 4] = [[0.0; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, entry) in row.iter_mut().enumerate() {
+                *entry = (0..4).map(|k| self.m[i][k] * other.m[k][j]).sum();
+            }
+        }
+        Self { m }
+    }
+}
+
+impl Default for Mat4 {
+    /// By default, `Mat4` is the identity matrix.
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Mat4 {
+    /// Create new `Mat4` instance.
+    pub fn new(m: [[f64; 4]; 4]) -> Self {
+        Self { m }
+    }
+    /// Get the identity matrix, which leaves everything unchanged.
+    pub fn identity() -> Self {
+        Self::new([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+    /// Get the matrix that translates points by `offset`.
+    pub fn translation(offset: Vec3) -> Self {
+        Self::new([
+            [1.0, 0.0, 0.0, offset.x],
+            [0.0, 1.0, 0.0, offset.y],
+            [0.0, 0.0, 1.0, offset.z],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+    /// Get the matrix that scales each axis by the corresponding component of `factors`.
+    pub fn scaling(factors: Vec3) -> Self {
+        Self::new([
+            [factors.x, 0.0, 0.0, 0.0],
+            [0.0, factors.y, 0.0, 0.0],
+            [0.0, 0.0, factors.z, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+    /// Get the matrix that rotates by `angle` degrees about `axis`, counterclockwise when looking
+    /// down the axis towards the origin. The `axis` is normalised, so it does not need to be of
+    /// unit length. This is Rodrigues' rotation formula in matrix form.
+    /// See: <https://en.wikipedia.org/wiki/Rotation_matrix#Rotation_matrix_from_axis_and_angle>
+    pub fn rotation(axis: Vec3, angle: f64) -> Self {
+        let a: Vec3 = axis.unit_vector();
+        let theta: f64 = utils::degrees_to_radians(angle);
+        let (sin_theta, cos_theta) = theta.sin_cos();
+        let t: f64 = 1.0 - cos_theta;
+        Self::new([
+            [
+                cos_theta + a.x * a.x * t,
+                a.x * a.y * t - a.z * sin_theta,
+                a.x * a.z * t + a.y * sin_theta,
+                0.0,
+            ],
+            [
+                a.y * a.x * t + a.z * sin_theta,
+                cos_theta + a.y * a.y * t,
+                a.y * a.z * t - a.x * sin_theta,
+                0.0,
+            ],
+            [
+                a.z * a.x * t - a.y * sin_theta,
+                a.z * a.y * t + a.x * sin_theta,
+                cos_theta + a.z * a.z * t,
+                0.0,
+            ],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+    /// Transform a point, e.g. with homogeneous coordinate $w = 1$.
+    pub fn mul_point(&self, point: Point) -> Point {
+        let m = &self.m;
+        return Point::new(
+            m[0][0] * point.x + m[0][1] * point.y + m[0][2] * point.z + m[0][3],
+            m[1][0] * point.x + m[1][1] * point.y + m[1][2] * point.z + m[1][3],
+            m[2][0] * point.x + m[2][1] * point.y + m[2][2] * point.z + m[2][3],
+        );
+    }
+    /// Transform a vector, e.g. with homogeneous coordinate $w = 0$, so translations are ignored.
+    pub fn mul_vector(&self, vector: Vec3) -> Vec3 {
+        let m = &self.m;
+        return Vec3::new(
+            m[0][0] * vector.x + m[0][1] * vector.y + m[0][2] * vector.z,
+            m[1][0] * vector.x + m[1][1] * vector.y + m[1][2] * vector.z,
+            m[2][0] * vector.x + m[2][1] * vector.y + m[2][2] * vector.z,
+        );
+    }
+    /// Get the transpose of the matrix.
+    pub fn transpose(&self) -> Self {
+        let mut m: [[f64; 4]; 4] = [[0.0; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, entry) in row.iter_mut().enumerate() {
+                *entry = self.m[j][i];
+            }
+        }
+        Self { m }
+    }
+    /// Get the inverse of the matrix with Gauss-Jordan elimination and partial pivoting. If the
+    /// matrix is singular, `None` is returned.
+    pub fn inverse(&self) -> Option<Self> {
+        let epsilon: f64 = 1e-12;
+        let mut a: [[f64; 4]; 4] = self.m;
+        let mut inverse: [[f64; 4]; 4] = Self::identity().m;
+
+        for column in 0..4 {
+            // Swap the row with the largest pivot into place.
+            let pivot_row: usize = (column..4)
+                .max_by(|&i, &j| a[i][column].abs().total_cmp(&a[j][column].abs()))
+                .unwrap_or(column);
+            if a[pivot_row][column].abs() < epsilon {
+                return None;
+            }
+            a.swap(column, pivot_row);
+            inverse.swap(column, pivot_row);
+
+            // Normalise the pivot row.
+            let pivot: f64 = a[column][column];
+            for j in 0..4 {
+                a[column][j] /= pivot;
+                inverse[column][j] /= pivot;
+            }
+            // Eliminate the column from all other rows.
+            for i in 0..4 {
+                if i != column {
+                    let factor: f64 = a[i][column];
+                    for j in 0..4 {
+                        a[i][j] -= factor * a[column][j];
+                        inverse[i][j] -= factor * inverse[column][j];
+                    }
+                }
+            }
+        }
+        return Some(Self::new(inverse));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translate_then_rotate_and_back_through_the_inverse() {
+        let translation: Mat4 = Mat4::translation(Vec3::new(1.0, 0.0, 0.0));
+        let rotation: Mat4 = Mat4::rotation(Vec3::new(0.0, 0.0, 1.0), 90.0);
+        let matrix: Mat4 = rotation * translation;
+
+        // (1,0,0) is moved to (2,0,0) and then turned a quarter counterclockwise about z.
+        let point: Point = Point::new(1.0, 0.0, 0.0);
+        let moved: Point = matrix.mul_point(point);
+        assert!((moved - Point::new(0.0, 2.0, 0.0)).length() < 1e-12);
+        // Vectors are only rotated.
+        let vector: Vec3 = matrix.mul_vector(Vec3::new(1.0, 0.0, 0.0));
+        assert!((vector - Vec3::new(0.0, 1.0, 0.0)).length() < 1e-12);
+
+        let inverse: Mat4 = matrix.inverse().unwrap();
+        assert!((inverse.mul_point(moved) - point).length() < 1e-12);
+    }
+
+    #[test]
+    fn singular_matrix_has_no_inverse() {
+        assert!(Mat4::scaling(Vec3::new(1.0, 0.0, 1.0)).inverse().is_none());
+    }
+}
@@ -1,76 +1,419 @@
+use crate::error::error::RenderError;
 use crate::vector::vector::{Color, Vec3};
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
 use rand::prelude::*;
-use std::io::Write;
+use rand_chacha::ChaCha12Rng;
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
 
 // Define useful constants.
-pub const POSITIVE_INFINITY: f64 = std::f64::MAX;
-pub const NEGATIVE_INFINITY: f64 = std::f64::MIN;
-// If we could, we would set these as constants.
-//pub const EMPTY: Interval = Interval::new(POSITIVE_INFINITY, NEGATIVE_INFINITY);
-//pub const UNIVERSE: Interval = Interval::new(NEGATIVE_INFINITY, POSITIVE_INFINITY);
-
-/// Create ./result/ directory if it does not exist.
-pub fn create_result_dir() {
-    match std::fs::create_dir("result") {
-        Ok(_) => {}
-        Err(err) => {
-            log::info!("`result` directory already exists: {err}");
+pub const POSITIVE_INFINITY: f64 = f64::MAX;
+pub const NEGATIVE_INFINITY: f64 = f64::MIN;
+/// The empty `Interval`, it contains no values.
+pub const EMPTY: Interval = Interval::new(POSITIVE_INFINITY, NEGATIVE_INFINITY);
+/// The `Interval` that contains all values.
+pub const UNIVERSE: Interval = Interval::new(NEGATIVE_INFINITY, POSITIVE_INFINITY);
+
+/// Create the directory that contains `path`, along with its parents, if it does not exist.
+pub fn create_parent_dir(path: &Path) -> Result<(), RenderError> {
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            std::fs::create_dir_all(parent)?;
         }
     }
+    return Ok(());
+}
+
+/// Template of the render progress bars, showing the elapsed time, the progress and the estimated
+/// remaining time.
+/// See: <https://docs.rs/indicatif/latest/indicatif/#templates>
+pub const PROGRESS_TEMPLATE: &str =
+    "{msg} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {percent:>3}% (ETA {eta})";
+/// Maximum amount of times per second the progress bar is redrawn.
+pub const PROGRESS_REFRESH_RATE: u8 = 4;
+
+/// Create a progress bar with `length` steps and the `message` in front of it. The bar is drawn
+/// on stderr, at most `PROGRESS_REFRESH_RATE` times per second, so that frequent increments from
+/// many threads do not flood the terminal. If stderr is not a terminal, the bar is hidden.
+pub fn progress_bar(length: u64, message: &'static str) -> ProgressBar {
+    let style: ProgressStyle = match ProgressStyle::with_template(PROGRESS_TEMPLATE) {
+        Ok(style) => style.progress_chars("=> "),
+        Err(err) => {
+            log::error!("Invalid progress bar template, using the default: {err}");
+            ProgressStyle::default_bar()
+        }
+    };
+    return ProgressBar::with_draw_target(
+        Some(length),
+        ProgressDrawTarget::stderr_with_hz(PROGRESS_REFRESH_RATE),
+    )
+    .with_style(style)
+    .with_message(message);
+}
+
+/// Encoding of the pixel values in a PPM file. The `Ascii` format is the plain PPM format (P3),
+/// where every channel is written as a human readable integer. The `Binary` format is the raw PPM
+/// format (P6), where every channel is written as a single byte.
+/// See: <https://netpbm.sourceforge.net/doc/ppm.html>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PpmFormat {
+    Ascii,
+    Binary,
 }
+
 /// Add heading for identification of plain PPM files along with image dimensions.
 /// See: <https://netpbm.sourceforge.net/doc/ppm.html>
-pub fn add_ppm_header(file: &mut std::fs::File, img_width: i32, img_height: i32) {
-    match file.write_all(format!("P3\n{} {}\n255\n", img_width, img_height).as_bytes()) {
-        Ok(_) => {}
-        Err(err) => {
-            log::error!("Error writing to file: {err}");
-            std::process::exit(1);
+pub fn add_ppm_header(
+    writer: &mut impl Write,
+    img_width: i32,
+    img_height: i32,
+) -> Result<(), RenderError> {
+    writer.write_all(format!("P3\n{} {}\n255\n", img_width, img_height).as_bytes())?;
+    return Ok(());
+}
+/// Add heading for identification of raw PPM files along with image dimensions.
+/// See: <https://netpbm.sourceforge.net/doc/ppm.html>
+pub fn add_ppm_header_p6(
+    writer: &mut impl Write,
+    img_width: i32,
+    img_height: i32,
+) -> Result<(), RenderError> {
+    writer.write_all(format!("P6\n{} {}\n255\n", img_width, img_height).as_bytes())?;
+    return Ok(());
+}
+/// Write the linear `pixels` of an image of the given dimensions, in scanline order from the top,
+/// to a Portable Float Map (PFM) file at `path`. Every channel is written as a little-endian
+/// `f32` without exposure, tone mapping, gamma encoding or clamping, so the full range of the
+/// render is kept for processing in other tools. The PFM format stores the rows from the bottom
+/// up, and a negative scale in the header marks the values as little-endian.
+/// See: <https://www.pauldebevec.com/Research/HDR/PFM/>
+pub fn write_pfm(
+    path: &Path,
+    width: i32,
+    height: i32,
+    pixels: &[Color],
+) -> Result<(), RenderError> {
+    let with_path = |err: std::io::Error| {
+        let message: String = format!("could not write PFM file `{}`: {err}", path.display());
+        RenderError::Io(std::io::Error::new(err.kind(), message))
+    };
+    let mut writer = BufWriter::new(File::create(path).map_err(with_path)?);
+    writer
+        .write_all(format!("PF\n{} {}\n-1.0\n", width, height).as_bytes())
+        .map_err(with_path)?;
+    for row in pixels.chunks(width as usize).rev() {
+        for color in row {
+            for channel in color.as_array() {
+                writer
+                    .write_all(&(channel as f32).to_le_bytes())
+                    .map_err(with_path)?;
+            }
+        }
+    }
+    writer.flush().map_err(with_path)?;
+    return Ok(());
+}
+/// Tone mapping operator, which compresses the unbounded linear radiance into the [0,1] range
+/// before the color transfer. Without tone mapping, all values above one are clamped, which
+/// blows out bright regions.
+/// See: <https://64.github.io/tonemapping/>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ToneMap {
+    /// No tone mapping, values above one are clamped.
+    None,
+    /// Reinhard operator $c / (1 + c)$.
+    Reinhard,
+    /// Extended Reinhard operator $c (1 + c / w^2) / (1 + c)$, which maps the given white point
+    /// $w$ to one.
+    ReinhardExtended(f64),
+    /// Krzysztof Narkowicz's rational fit of the ACES filmic curve.
+    AcesFilmic,
+}
+
+impl ToneMap {
+    /// Apply the tone mapping operator to a single linear color channel.
+    pub fn apply(&self, value: f64) -> f64 {
+        match self {
+            ToneMap::None => {
+                return value;
+            }
+            ToneMap::Reinhard => {
+                return value / (1.0 + value);
+            }
+            ToneMap::ReinhardExtended(white_point) => {
+                return value * (1.0 + value / (white_point * white_point)) / (1.0 + value);
+            }
+            ToneMap::AcesFilmic => {
+                let (a, b, c, d, e) = (2.51, 0.03, 2.43, 0.59, 0.14);
+                let mapped: f64 = (value * (a * value + b)) / (value * (c * value + d) + e);
+                return Interval::new(0.0, 1.0).clamp(mapped);
+            }
         }
     }
+    /// Apply the tone mapping operator to each channel of a linear `Color`.
+    pub fn map_color(&self, color: &Color) -> Color {
+        return Color::new(
+            self.apply(color.x),
+            self.apply(color.y),
+            self.apply(color.z),
+        );
+    }
 }
-/// Conversion from linear to gamma, this is an implementation of the inverse `gamma 2` transform
-pub fn linear_to_gamma(linear_value: f64) -> f64 {
+
+/// Conversion from linear to gamma, this is an implementation of the inverse gamma transform
+/// $x^{1 / \gamma}$. A `gamma` of 1 leaves the values linear, 2 gives the square root and 2.2
+/// approximates sRGB. Negative values are mapped to zero.
+pub fn linear_to_gamma(linear_value: f64, gamma: f64) -> f64 {
     if linear_value > 0.0 {
-        return linear_value.sqrt();
+        return linear_value.powf(1.0 / gamma);
     } else {
         return 0.0;
     }
 }
-/// Write `Color` to image file as required by the plain PPM file format.
-/// See: <https://netpbm.sourceforge.net/doc/ppm.html>
-pub fn write_color(file: &mut std::fs::File, color: &Color) {
+/// Conversion from linear values to the sRGB encoding, the inverse of `srgb_to_linear`. The curve
+/// is linear near zero and a power curve with an exponent of 1 / 2.4 above. Negative values are
+/// mapped to zero.
+/// See: <https://en.wikipedia.org/wiki/SRGB#Transformation>
+pub fn linear_to_srgb(linear_value: f64) -> f64 {
+    if linear_value <= 0.0 {
+        return 0.0;
+    } else if linear_value <= 0.0031308 {
+        return 12.92 * linear_value;
+    } else {
+        return 1.055 * linear_value.powf(1.0 / 2.4) - 0.055;
+    }
+}
+/// Conversion from the sRGB encoding, as used by most image files, to linear values.
+/// See: <https://en.wikipedia.org/wiki/SRGB#Transformation>
+pub fn srgb_to_linear(srgb_value: f64) -> f64 {
+    if srgb_value <= 0.04045 {
+        return srgb_value / 12.92;
+    } else {
+        return ((srgb_value + 0.055) / 1.055).powf(2.4);
+    }
+}
+/// Transfer function that encodes linear color values for display, after tone mapping.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorTransfer {
+    /// Inverse gamma transform with the given gamma, see `linear_to_gamma`.
+    Gamma(f64),
+    /// Piecewise sRGB transfer function, see `linear_to_srgb`.
+    Srgb,
+}
+
+impl ColorTransfer {
+    /// Encode a single linear color channel.
+    pub fn apply(&self, linear_value: f64) -> f64 {
+        match self {
+            ColorTransfer::Gamma(gamma) => {
+                return linear_to_gamma(linear_value, *gamma);
+            }
+            ColorTransfer::Srgb => {
+                return linear_to_srgb(linear_value);
+            }
+        }
+    }
+}
+
+/// Replace a non-finite color channel, e.g. infinity or NaN, with zero.
+pub fn finite_or_zero(value: f64) -> f64 {
+    if value.is_finite() {
+        return value;
+    } else {
+        return 0.0;
+    }
+}
+/// Convert a linear `Color` into the [0,255] byte values of its RGB channels, as required by the
+/// PPM file formats. The `tone_map` is applied before the color `transfer` function. Non-finite
+/// channels are written as zero, so that they show up as dark pixels instead of arbitrary values.
+pub fn color_to_bytes(color: &Color, tone_map: ToneMap, transfer: ColorTransfer) -> [u8; 3] {
     // Define intensity interval.
     let intensity: Interval = Interval::new(0.0, 0.999);
-    // Apply linear to gamma transform
-    let r: f64 = linear_to_gamma(color.x);
-    let g: f64 = linear_to_gamma(color.y);
-    let b: f64 = linear_to_gamma(color.z);
-
-    // Transform [0,1] f64 values into [0,255] i32 values
-    let ir: i32 = (256.0 * intensity.clamp(r)) as i32;
-    let ig: i32 = (256.0 * intensity.clamp(g)) as i32;
-    let ib: i32 = (256.0 * intensity.clamp(b)) as i32;
-
-    // Write to RGB color to image file.
-    match file.write_all(format!("{} {} {}\n", ir, ig, ib).as_bytes()) {
-        Ok(_) => {}
-        Err(err) => {
-            log::error!("Error writing colors to image: {err}");
-            std::process::exit(1);
-        }
+    // Replace infinite and NaN channels
+    let color: Color = Color::new(
+        finite_or_zero(color.x),
+        finite_or_zero(color.y),
+        finite_or_zero(color.z),
+    );
+    // Apply tone mapping
+    let color: Color = tone_map.map_color(&color);
+    // Apply transfer function
+    let r: f64 = transfer.apply(color.x);
+    let g: f64 = transfer.apply(color.y);
+    let b: f64 = transfer.apply(color.z);
+
+    // Transform [0,1] f64 values into [0,255] u8 values
+    let ir: u8 = (256.0 * intensity.clamp(r)) as u8;
+    let ig: u8 = (256.0 * intensity.clamp(g)) as u8;
+    let ib: u8 = (256.0 * intensity.clamp(b)) as u8;
+    return [ir, ig, ib];
+}
+/// Write `Color` to image writer as required by the plain PPM file format.
+/// See: <https://netpbm.sourceforge.net/doc/ppm.html>
+pub fn write_color(
+    writer: &mut impl Write,
+    color: &Color,
+    tone_map: ToneMap,
+    transfer: ColorTransfer,
+) -> Result<(), RenderError> {
+    let [ir, ig, ib] = color_to_bytes(color, tone_map, transfer);
+
+    // Write to RGB color to image writer.
+    writer.write_all(format!("{} {} {}\n", ir, ig, ib).as_bytes())?;
+    return Ok(());
+}
+/// Write `Color` to image writer as required by the raw PPM file format, e.g. as three bytes.
+/// See: <https://netpbm.sourceforge.net/doc/ppm.html>
+pub fn write_color_p6(
+    writer: &mut impl Write,
+    color: &Color,
+    tone_map: ToneMap,
+    transfer: ColorTransfer,
+) -> Result<(), RenderError> {
+    writer.write_all(&color_to_bytes(color, tone_map, transfer))?;
+    return Ok(());
+}
+/// Power of the normalized distance from the image center with which the vignette darkens the
+/// pixels. The higher the power, the more the darkening is confined to the corners.
+pub const VIGNETTE_FALLOFF: f64 = 2.0;
+/// Darken the pixels of the rendered image `buffer` of the given dimensions towards the corners,
+/// as happens in real lenses. Every pixel is scaled by $\max(0, 1 - s r^p)$, with $s$ the
+/// `strength`, $r$ the distance of the pixel to the image center relative to the distance of the
+/// corners and $p$ the `VIGNETTE_FALLOFF`. The buffer holds linear colors, so the vignette is
+/// applied before tone mapping and gamma encoding. A `strength` of zero leaves the image as is,
+/// a `strength` of one makes the corners black.
+pub fn vignette(buffer: &mut [Color], width: i32, height: i32, strength: f64) {
+    if strength == 0.0 {
+        return;
+    }
+    let center_x: f64 = (width as f64 - 1.0) / 2.0;
+    let center_y: f64 = (height as f64 - 1.0) / 2.0;
+    let corner_distance: f64 = (center_x * center_x + center_y * center_y).sqrt().max(1.0);
+    for (index, color) in buffer.iter_mut().enumerate() {
+        let x: f64 = (index % width as usize) as f64 - center_x;
+        let y: f64 = (index / width as usize) as f64 - center_y;
+        let r: f64 = (x * x + y * y).sqrt() / corner_distance;
+        *color *= (1.0 - strength * r.powf(VIGNETTE_FALLOFF)).max(0.0);
     }
 }
+/// Get the longitude and latitude in radians of the location (x,y) on an equirectangular image of
+/// `width` by `height` pixels, in pixels from the upper left corner. The longitude runs from
+/// $-\pi$ at the left edge to $\pi$ at the right edge, and the latitude from $\pi / 2$ at the top
+/// edge to $-\pi / 2$ at the bottom edge.
+pub fn equirectangular_angles(x: f64, y: f64, width: i32, height: i32) -> (f64, f64) {
+    let longitude: f64 = (2.0 * x / (width as f64) - 1.0) * std::f64::consts::PI;
+    let latitude: f64 = (0.5 - y / (height as f64)) * std::f64::consts::PI;
+    return (longitude, latitude);
+}
+/// Get the factor that turns the sum of the colors of `samples` samples of a pixel into their
+/// average. Without any samples the sum is black, and so is the average.
+pub fn sample_scale(samples: i32) -> f64 {
+    return 1.0 / (samples.max(1) as f64);
+}
 /// Convert degrees into radians.
 pub fn degrees_to_radians(degrees: f64) -> f64 {
     return degrees * std::f64::consts::PI / 180.0;
 }
+/// Smooth Hermite interpolation between zero at `edge0` and one at `edge1`,
+/// $$ s(t) = 3t^2 - 2t^3, \quad t = \frac{x - e_0}{e_1 - e_0} \in [0, 1], $$
+/// which is zero below `edge0` and one above `edge1`.
+pub fn smoothstep(edge0: f64, edge1: f64, x: f64) -> f64 {
+    let t: f64 = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    return t * t * (3.0 - 2.0 * t);
+}
 
+/// State of a random number generator. Wraps a `ChaCha12Rng`, the generator behind `StdRng`, so
+/// that it can either be seeded for reproducible results, or be seeded from the operating system's
+/// entropy. Unlike `StdRng`, its position in the sequence can be read and restored, e.g. to resume
+/// a render from a checkpoint.
+#[derive(Clone, Debug)]
+pub struct RngState {
+    rng: ChaCha12Rng,
+}
+
+impl RngState {
+    /// Create new `RngState` instance seeded with `seed`.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: ChaCha12Rng::seed_from_u64(seed),
+        }
+    }
+    /// Create new `RngState` instance seeded from the operating system's entropy.
+    pub fn from_entropy() -> Self {
+        Self {
+            rng: ChaCha12Rng::from_entropy(),
+        }
+    }
+    /// Create new `RngState` instance with the generator of pixel (i,j), see `pixel_rng`.
+    pub fn for_pixel(seed: u64, i: i32, j: i32) -> Self {
+        Self {
+            rng: pixel_rng(seed, i, j),
+        }
+    }
+    /// Get the position in the sequence of random numbers, counted in 32-bit words.
+    pub fn word_pos(&self) -> u128 {
+        return self.rng.get_word_pos();
+    }
+    /// Move to the position `word_pos` in the sequence of random numbers, see `word_pos`.
+    pub fn set_word_pos(&mut self, word_pos: u128) {
+        self.rng.set_word_pos(word_pos);
+    }
+    /// Get a random `f64` between 0 and 1.
+    pub fn next_f64(&mut self) -> f64 {
+        return self.rng.gen();
+    }
+}
+
+thread_local! {
+    /// Random number generator of the current thread, used by all random sampling functions.
+    static RNG: RefCell<RngState> = RefCell::new(RngState::from_entropy());
+}
+
+/// Seed the random number generator of the current thread. After seeding, the sequence of random
+/// numbers drawn on this thread is fully determined by `seed`.
+/// Note: Every thread has its own generator, and the rendering work is distributed over the
+/// threads of the `rayon` pool, so the order in which a thread draws its numbers depends on the
+/// scheduling. To make a render reproducible, the camera reseeds the generator for every pixel
+/// with `seed_pixel_rng` instead.
+pub fn seed_rng(seed: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = RngState::new(seed));
+}
+/// Create the random number generator of pixel (i,j), seeded with a hash of the global `seed`
+/// and the pixel location. Every pixel gets an independent generator that does not depend on the
+/// thread the pixel is rendered on.
+pub fn pixel_rng(seed: u64, i: i32, j: i32) -> ChaCha12Rng {
+    // Mix the inputs with the SplitMix64 finalizer, so that neighbouring pixels get unrelated
+    // seeds.
+    let mix = |mut z: u64| {
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        return z ^ (z >> 31);
+    };
+    let location: u64 = ((i as u32 as u64) << 32) | (j as u32 as u64);
+    let hash: u64 = mix(mix(seed.wrapping_add(0x9e3779b97f4a7c15)) ^ location);
+    return ChaCha12Rng::seed_from_u64(hash);
+}
+/// Seed the random number generator of the current thread with the generator of pixel (i,j), see
+/// `pixel_rng`. Rendering a pixel on a single thread right after this makes it bit-identical for
+/// every amount of threads.
+pub fn seed_pixel_rng(seed: u64, i: i32, j: i32) {
+    RNG.with(|rng| {
+        *rng.borrow_mut() = RngState {
+            rng: pixel_rng(seed, i, j),
+        }
+    });
+}
+/// Swap the random number generator of the current thread with `state`. Swapping a stored state
+/// in, drawing some numbers and swapping it back out continues the sequence of that state later
+/// on, possibly on another thread.
+pub fn swap_rng(state: &mut RngState) {
+    RNG.with(|rng| std::mem::swap(&mut *rng.borrow_mut(), state));
+}
 /// Get a random `f64` between 0 and 1.
 pub fn get_random() -> f64 {
-    let mut rng = rand::thread_rng();
-    let val: f64 = rng.gen();
-    return val;
+    return RNG.with(|rng| rng.borrow_mut().next_f64());
 }
 /// Get a random `f64` within the range [min, max].
 pub fn get_random_in_range(min: f64, max: f64) -> f64 {
@@ -80,6 +423,146 @@ pub fn get_random_in_range(min: f64, max: f64) -> f64 {
 pub fn sample_square() -> Vec3 {
     return Vec3::new(get_random() - 0.5, get_random() - 0.5, 0.0);
 }
+/// Map a uniform `f64` within [0, 1) to [-1, 1], distributed as the tent function $1 - |x|$, by
+/// inverting its cumulative distribution function.
+pub fn warp_tent(uniform: f64) -> f64 {
+    let r: f64 = 2.0 * uniform;
+    if r < 1.0 {
+        return r.sqrt() - 1.0;
+    } else {
+        return 1.0 - (2.0 - r).sqrt();
+    }
+}
+/// Map a pair of uniform `f64` values within [0, 1) to a pair of independent standard normally
+/// distributed values, using the Box-Muller transform.
+pub fn warp_gaussian_pair(uniform1: f64, uniform2: f64) -> (f64, f64) {
+    // Avoid the logarithm of zero.
+    let radius: f64 = (-2.0 * (1.0 - uniform1).ln()).sqrt();
+    let angle: f64 = 2.0 * std::f64::consts::PI * uniform2;
+    return (radius * angle.cos(), radius * angle.sin());
+}
+/// Get the element with the given `index` of the van der Corput sequence in the given `base`,
+/// which is the radical inverse of the index: its digits in the base are mirrored around the
+/// decimal point. For base 2, the sequence starts at index 1 with 0.5, 0.25, 0.75, 0.125, ...
+/// Using a different prime base for every dimension gives the Halton sequence.
+pub fn sample_dimension(index: u64, base: u64) -> f64 {
+    let mut index: u64 = index;
+    let mut result: f64 = 0.0;
+    let mut digit_weight: f64 = 1.0 / base as f64;
+    while index > 0 {
+        result += (index % base) as f64 * digit_weight;
+        index /= base;
+        digit_weight /= base as f64;
+    }
+    return result;
+}
+
+/// Source of the uniform random numbers from which the pixel offsets and the points on the lens of
+/// the samples are made. `Random` draws independent random numbers. `Halton` uses the Halton
+/// sequence with bases 2 and 3 for the two pixel dimensions, and bases 5 and 7 for the two lens
+/// dimensions. It is a low-discrepancy sequence, whose points cover the pixel or lens more evenly
+/// than random points, which reduces the noise at the same amount of samples.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Sampler {
+    Random,
+    Halton,
+}
+
+impl Sampler {
+    /// Get the two uniform numbers within [0, 1) for the sample with the given `index`. Every
+    /// pixel should use its own range of indices, so that the Halton points differ per pixel.
+    pub fn sample_2d(&self, index: u64) -> (f64, f64) {
+        match self {
+            Sampler::Random => {
+                return (get_random(), get_random());
+            }
+            Sampler::Halton => {
+                // Skip index zero, which is zero in every base.
+                return (
+                    sample_dimension(index + 1, 2),
+                    sample_dimension(index + 1, 3),
+                );
+            }
+        }
+    }
+    /// Get a point within the unit disk in the xy-plane for the sample with the given `index`, as
+    /// in `sample_2d`. The Halton points are mapped onto the disk with `concentric_disk`, which
+    /// keeps them evenly spread.
+    pub fn sample_lens(&self, index: u64) -> Vec3 {
+        match self {
+            Sampler::Random => {
+                return Vec3::get_random_in_unit_disk();
+            }
+            Sampler::Halton => {
+                return concentric_disk(
+                    sample_dimension(index + 1, 5),
+                    sample_dimension(index + 1, 7),
+                );
+            }
+        }
+    }
+}
+
+/// Map a pair of uniform `f64` values within [0, 1) to a point within the unit disk in the
+/// xy-plane with Shirley and Chiu's concentric mapping. The square $[-1,1]^2$ is mapped onto the
+/// disk ring by ring, so that concentric squares become concentric circles. Unlike polar
+/// coordinates, the mapping preserves the relative areas and does not squeeze neighbouring points
+/// together, so evenly spread points in the square stay evenly spread on the disk.
+/// See: <https://doi.org/10.1080/10867651.1997.10487479>
+pub fn concentric_disk(uniform1: f64, uniform2: f64) -> Vec3 {
+    let a: f64 = 2.0 * uniform1 - 1.0;
+    let b: f64 = 2.0 * uniform2 - 1.0;
+    if a == 0.0 && b == 0.0 {
+        return Vec3::new(0.0, 0.0, 0.0);
+    }
+    let quarter_pi: f64 = std::f64::consts::FRAC_PI_4;
+    let (radius, angle) = {
+        if a.abs() > b.abs() {
+            (a, quarter_pi * (b / a))
+        } else {
+            (b, 2.0 * quarter_pi - quarter_pi * (a / b))
+        }
+    };
+    return Vec3::new(radius * angle.cos(), radius * angle.sin(), 0.0);
+}
+
+/// Reconstruction filter of the pixels, which determines how the samples of a pixel are spread
+/// around its center. Every sample has the same weight, so the filter shape comes entirely from
+/// the distribution of the sample offsets. Filters that extend beyond the pixel blend
+/// neighbouring pixels slightly, which trades some sharpness for less aliasing at edges.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PixelFilter {
+    /// Uniform offsets within the pixel square.
+    Box,
+    /// Offsets within one pixel of the center in each direction, weighted linearly towards the
+    /// center.
+    Tent,
+    /// Normally distributed offsets with the given standard deviation in pixels.
+    Gaussian(f64),
+}
+
+impl PixelFilter {
+    /// Get a random offset from the pixel center in pixels, distributed according to the filter.
+    pub fn sample(&self) -> Vec3 {
+        return self.offset(get_random(), get_random());
+    }
+    /// Map two uniform numbers within [0, 1) to an offset from the pixel center in pixels,
+    /// distributed according to the filter.
+    pub fn offset(&self, uniform1: f64, uniform2: f64) -> Vec3 {
+        match self {
+            PixelFilter::Box => {
+                return Vec3::new(uniform1 - 0.5, uniform2 - 0.5, 0.0);
+            }
+            PixelFilter::Tent => {
+                return Vec3::new(warp_tent(uniform1), warp_tent(uniform2), 0.0);
+            }
+            PixelFilter::Gaussian(sigma) => {
+                let (x, y) = warp_gaussian_pair(uniform1, uniform2);
+                return Vec3::new(sigma * x, sigma * y, 0.0);
+            }
+        }
+    }
+}
 
 /// Struct that contains a minimum and maximum value
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -91,18 +574,41 @@ pub struct Interval {
 impl Default for Interval {
     /// By default, `Interval` is set to $[- \infty, + \infty]$.
     fn default() -> Self {
-        Self {
-            min: NEGATIVE_INFINITY,
-            max: POSITIVE_INFINITY,
-        }
+        return UNIVERSE;
     }
 }
 
 impl Interval {
     /// Create new `Interval` instance.
-    pub fn new(min: f64, max: f64) -> Self {
+    pub const fn new(min: f64, max: f64) -> Self {
         Self { min, max }
     }
+    /// Create the smallest `Interval` that contains both intervals `a` and `b`.
+    pub fn enclose(a: Self, b: Self) -> Self {
+        Self {
+            min: a.min.min(b.min),
+            max: a.max.max(b.max),
+        }
+    }
+    /// Get the overlap of the `Interval` with `other`, e.g. for clipping a range of ray
+    /// parameters against the slab of a bounding box. Returns `None` if the intervals are
+    /// disjoint. Intervals that only touch overlap in a single value.
+    pub fn intersect(&self, other: &Self) -> Option<Self> {
+        let overlap: Self = Self::new(self.min.max(other.min), self.max.min(other.max));
+        if overlap.is_empty() {
+            return None;
+        }
+        return Some(overlap);
+    }
+    /// Check if the `Interval` contains no values, which is the case when `min > max`.
+    pub fn is_empty(&self) -> bool {
+        return self.min > self.max;
+    }
+    /// Grow the `Interval` by `delta`, e.g. `delta / 2` is added on both sides.
+    pub fn expand(&self, delta: f64) -> Self {
+        let padding: f64 = delta / 2.0;
+        return Self::new(self.min - padding, self.max + padding);
+    }
     /// Get the size of the interval, e.g. size([a, b]) = b - a.
     pub fn size(&self) -> f64 {
         return self.max - self.min;
@@ -127,3 +633,283 @@ impl Interval {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write `pixels` as a 2 pixel wide PPM image in the given `format`.
+    fn encode(pixels: &[Color], format: PpmFormat) -> Vec<u8> {
+        let mut output: Vec<u8> = Vec::new();
+        let height: i32 = pixels.len() as i32 / 2;
+        let transfer: ColorTransfer = ColorTransfer::Gamma(2.0);
+        match format {
+            PpmFormat::Ascii => {
+                add_ppm_header(&mut output, 2, height).unwrap();
+                for color in pixels {
+                    write_color(&mut output, color, ToneMap::None, transfer).unwrap();
+                }
+            }
+            PpmFormat::Binary => {
+                add_ppm_header_p6(&mut output, 2, height).unwrap();
+                for color in pixels {
+                    write_color_p6(&mut output, color, ToneMap::None, transfer).unwrap();
+                }
+            }
+        }
+        return output;
+    }
+
+    #[test]
+    fn ascii_and_binary_ppm_decode_to_the_same_pixels() {
+        let pixels: Vec<Color> = vec![
+            Color::new(0.0, 0.0, 0.0),
+            Color::new(1.0, 1.0, 1.0),
+            Color::new(0.25, 0.5, 0.75),
+            Color::new(2.0, 0.01, 0.6),
+        ];
+        let ascii: String = String::from_utf8(encode(&pixels, PpmFormat::Ascii)).unwrap();
+        let binary: Vec<u8> = encode(&pixels, PpmFormat::Binary);
+
+        // The plain format lists the channels as integers after the magic number, the size and
+        // the maximum value.
+        let mut tokens = ascii.split_whitespace();
+        assert_eq!(tokens.next(), Some("P3"));
+        let ascii_header: Vec<&str> = tokens.by_ref().take(3).collect();
+        assert_eq!(ascii_header, ["2", "2", "255"]);
+        let ascii_channels: Vec<u8> = tokens.map(|token| token.parse().unwrap()).collect();
+
+        // The raw format has the same header, followed by one byte per channel.
+        let binary_header: &[u8] = b"P6\n2 2\n255\n";
+        assert!(binary.starts_with(binary_header));
+        let binary_channels: &[u8] = &binary[binary_header.len()..];
+
+        assert_eq!(ascii_channels.len(), 12);
+        assert_eq!(ascii_channels, binary_channels);
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_random_numbers() {
+        let draw = || {
+            seed_rng(42);
+            let mut values: Vec<f64> = vec![get_random(), get_random_in_range(-3.0, 3.0)];
+            let offset: Vec3 = sample_square();
+            values.extend([offset.x, offset.y, offset.z]);
+            return values;
+        };
+        let first: Vec<f64> = draw();
+        assert_eq!(first, draw());
+        seed_rng(43);
+        assert_ne!(first[0], get_random());
+    }
+
+    #[test]
+    fn enclose_and_expand_intervals() {
+        let enclosed: Interval =
+            Interval::enclose(Interval::new(1.0, 3.0), Interval::new(2.0, 5.0));
+        assert_eq!(enclosed, Interval::new(1.0, 5.0));
+        assert_eq!(
+            Interval::new(0.0, 0.0).expand(2.0),
+            Interval::new(-1.0, 1.0)
+        );
+        assert_eq!(
+            Interval::enclose(EMPTY, Interval::new(2.0, 5.0)),
+            Interval::new(2.0, 5.0)
+        );
+        assert!(!EMPTY.contains(0.0));
+        assert!(UNIVERSE.contains(1e300));
+    }
+
+    #[test]
+    fn reinhard_maps_bright_values_toward_but_below_one() {
+        let mapped: f64 = ToneMap::Reinhard.apply(1000.0);
+        assert!(mapped < 1.0);
+        assert!(mapped > 0.99);
+        assert!(ToneMap::Reinhard.apply(1e6) > mapped);
+        assert_eq!(ToneMap::Reinhard.apply(1.0), 0.5);
+    }
+
+    #[test]
+    fn gamma_one_leaves_mid_gray_unchanged() {
+        let gray: Color = Color::new(0.5, 0.5, 0.5);
+        let bytes: [u8; 3] = color_to_bytes(&gray, ToneMap::None, ColorTransfer::Gamma(1.0));
+        for byte in bytes {
+            assert!((byte as f64 - 0.5 * 255.0).abs() <= 1.0);
+        }
+        // The default gamma of 2 is the square root.
+        assert_eq!(linear_to_gamma(0.25, 2.0), 0.5);
+    }
+
+    #[test]
+    fn progress_template_constructs_a_style() {
+        assert!(ProgressStyle::with_template(PROGRESS_TEMPLATE).is_ok());
+        let bar: ProgressBar = progress_bar(100, "Pixels");
+        assert_eq!(bar.length(), Some(100));
+        assert_eq!(bar.message(), "Pixels");
+        bar.inc(40);
+        assert_eq!(bar.position(), 40);
+    }
+
+    #[test]
+    fn tent_offsets_are_more_concentrated_than_box_offsets() {
+        seed_rng(56);
+        // Mean distance from the center along x, relative to the half width of the filter, which
+        // is 1/2 for uniform offsets and 1/3 for linearly weighted ones.
+        let relative_spread = |filter: PixelFilter, radius: f64| {
+            let count: usize = 20_000;
+            let mut total: f64 = 0.0;
+            for _ in 0..count {
+                let offset: Vec3 = filter.sample();
+                assert!(offset.x.abs() <= radius && offset.y.abs() <= radius);
+                total += offset.x.abs() / radius;
+            }
+            return total / count as f64;
+        };
+        let box_spread: f64 = relative_spread(PixelFilter::Box, 0.5);
+        let tent_spread: f64 = relative_spread(PixelFilter::Tent, 1.0);
+        assert!((box_spread - 0.5).abs() < 0.01);
+        assert!((tent_spread - 1.0 / 3.0).abs() < 0.01);
+        assert!(tent_spread < box_spread);
+    }
+
+    #[test]
+    fn halton_base_two_matches_the_known_sequence() {
+        let expected: [f64; 7] = [0.5, 0.25, 0.75, 0.125, 0.625, 0.375, 0.875];
+        for (index, value) in expected.iter().enumerate() {
+            assert_eq!(sample_dimension(index as u64 + 1, 2), *value);
+        }
+        assert_eq!(sample_dimension(0, 2), 0.0);
+        // Base 3 starts with 1/3, 2/3, 1/9.
+        assert!((sample_dimension(1, 3) - 1.0 / 3.0).abs() < 1e-15);
+        assert!((sample_dimension(2, 3) - 2.0 / 3.0).abs() < 1e-15);
+        assert!((sample_dimension(3, 3) - 1.0 / 9.0).abs() < 1e-15);
+        // The sampler starts at index one.
+        assert_eq!(Sampler::Halton.sample_2d(0).0, 0.5);
+    }
+
+    #[test]
+    fn intersect_gives_the_overlap_or_none() {
+        assert_eq!(
+            Interval::new(0.0, 5.0).intersect(&Interval::new(3.0, 10.0)),
+            Some(Interval::new(3.0, 5.0))
+        );
+        assert!(Interval::new(0.0, 1.0)
+            .intersect(&Interval::new(2.0, 3.0))
+            .is_none());
+        // Touching intervals overlap in one value.
+        assert_eq!(
+            Interval::new(0.0, 1.0).intersect(&Interval::new(1.0, 2.0)),
+            Some(Interval::new(1.0, 1.0))
+        );
+        assert!(Interval::new(1.0, 0.0).is_empty());
+        assert!(!Interval::new(0.0, 0.0).is_empty());
+    }
+
+    #[test]
+    fn srgb_transfer_matches_the_reference_curve() {
+        assert_eq!(linear_to_srgb(0.0), 0.0);
+        assert!((linear_to_srgb(1.0) - 1.0).abs() < 1e-12);
+        // Linear segment below the threshold.
+        assert!((linear_to_srgb(0.002) - 0.02584).abs() < 1e-12);
+        // Linear 0.2140 is the reference midtone 0.5 of the sRGB curve.
+        assert!((linear_to_srgb(0.214041) - 0.5).abs() < 1e-5);
+        assert!((srgb_to_linear(linear_to_srgb(0.18)) - 0.18).abs() < 1e-12);
+        assert_eq!(ColorTransfer::Srgb.apply(0.5), linear_to_srgb(0.5));
+    }
+
+    #[test]
+    fn nan_and_infinite_channels_are_written_as_zero() {
+        let transfer: ColorTransfer = ColorTransfer::Gamma(2.0);
+        let mut output: Vec<u8> = Vec::new();
+        let nan: Color = Color::new(f64::NAN, f64::NAN, f64::NAN);
+        write_color(&mut output, &nan, ToneMap::None, transfer).unwrap();
+        assert_eq!(output, b"0 0 0\n");
+
+        // Only the non-finite channels are replaced.
+        let mixed: Color = Color::new(f64::INFINITY, 1.0, f64::NAN);
+        assert_eq!(color_to_bytes(&mixed, ToneMap::None, transfer), [0, 255, 0]);
+    }
+
+    #[test]
+    fn vignette_darkens_the_corners_more_than_the_center() {
+        let gray: Color = Color::new(0.5, 0.5, 0.5);
+        let mut buffer: Vec<Color> = vec![gray; 5 * 5];
+        vignette(&mut buffer, 5, 5, 0.5);
+        assert_eq!(buffer[2 * 5 + 2], gray);
+        assert!(buffer[0].x < buffer[5 + 1].x);
+        assert!(buffer[5 + 1].x < gray.x);
+        assert!((buffer[0].x - 0.25).abs() < 1e-12);
+
+        // A strength of zero leaves the image as is.
+        let mut untouched: Vec<Color> = vec![gray; 5 * 5];
+        vignette(&mut untouched, 5, 5, 0.0);
+        assert!(untouched.iter().all(|color| *color == gray));
+    }
+
+    #[test]
+    fn pfm_round_trip_keeps_the_linear_values() {
+        let pixels: Vec<Color> = vec![
+            Color::new(0.0, 0.5, 1.0),
+            Color::new(12.5, -0.25, 1e-4),
+            Color::new(0.1, 0.2, 0.3),
+            Color::new(1000.0, 3.0, 0.7),
+        ];
+        let path: std::path::PathBuf = std::env::temp_dir().join("raytracing_round_trip.pfm");
+        write_pfm(&path, 2, 2, &pixels).unwrap();
+        let bytes: Vec<u8> = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let header: &[u8] = b"PF\n2 2\n-1.0\n";
+        assert!(bytes.starts_with(header));
+        let values: Vec<f32> = bytes[header.len()..]
+            .chunks(4)
+            .map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap()))
+            .collect();
+        assert_eq!(values.len(), 4 * 3);
+        // The rows are stored from the bottom up.
+        let rows: Vec<&[f32]> = values.chunks(2 * 3).rev().collect();
+        let read: Vec<Color> = rows
+            .concat()
+            .chunks(3)
+            .map(|rgb| Color::new(rgb[0] as f64, rgb[1] as f64, rgb[2] as f64))
+            .collect();
+        for (read, written) in read.iter().zip(pixels.iter()) {
+            assert_eq!(*read, written.cast::<f32>().cast::<f64>());
+        }
+    }
+
+    #[test]
+    fn concentric_lens_samples_lie_in_the_disk_and_spread_evenly() {
+        // For uniform points on the disk, the squared radius is uniform within [0,1], so every
+        // ring of equal area should receive the same amount of points.
+        let rings: usize = 8;
+        let samples: u64 = 256;
+        let ring_spread = |sampler: Sampler| {
+            let mut counts: Vec<f64> = vec![0.0; rings];
+            for index in 0..samples {
+                let point: Vec3 = sampler.sample_lens(index);
+                assert_eq!(point.z, 0.0);
+                assert!(
+                    point.length_squared() <= 1.0,
+                    "{point:?} lies outside the disk"
+                );
+                let ring: usize = ((point.length_squared() * rings as f64) as usize).min(rings - 1);
+                counts[ring] += 1.0;
+            }
+            let expected: f64 = samples as f64 / rings as f64;
+            let variance: f64 = counts
+                .iter()
+                .map(|count| (count - expected) * (count - expected))
+                .sum::<f64>()
+                / rings as f64;
+            return variance;
+        };
+        seed_rng(107);
+        let halton: f64 = ring_spread(Sampler::Halton);
+        let random: f64 = ring_spread(Sampler::Random);
+        assert!(
+            halton < random,
+            "Halton variance {halton} is not below random {random}"
+        );
+    }
+}
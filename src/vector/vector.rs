@@ -1,21 +1,31 @@
 use crate::util::utils::{get_random, get_random_in_range};
-use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use num_traits::Float;
+use std::ops::{
+    Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign,
+};
 
-/// Custom three dimensional vector structure.
+/// Custom three dimensional vector structure. The components are `f64` by default, but any
+/// floating point type can be used, e.g. `f32` halves the memory of large meshes. The random
+/// sampling functions are only available for `f64` vectors, which the renderer uses.
 #[derive(Debug, Clone, Copy, PartialEq)]
-pub struct Vec3 {
-    pub x: f64,
-    pub y: f64,
-    pub z: f64,
+pub struct Vec3<F = f64> {
+    pub x: F,
+    pub y: F,
+    pub z: F,
 }
 
 /// Type alias for `Vec3` structure. Used for representing points in 3D.
 pub type Point = Vec3;
 /// Type alias for `Vec3` structure. Used for representing RGB colors.
 pub type Color = Vec3;
+/// Type alias for `Vec3` structure with single precision components.
+pub type Vec3f32 = Vec3<f32>;
+
+/// Default threshold of `Vec3::near_zero`, below which a component counts as zero.
+pub const NEAR_ZERO_EPSILON: f64 = 1e-8;
 
 /// Implement addition of `Vec3` structures. This allows us to use the `+` symbol.
-impl Add for Vec3 {
+impl<F: Float> Add for Vec3<F> {
     type Output = Self;
     fn add(self, other: Self) -> Self {
         Self {
@@ -26,7 +36,7 @@ impl Add for Vec3 {
     }
 }
 /// Implement subtraction of two `Vec3` structures. This allows us to use the `-` symbol.
-impl Sub for Vec3 {
+impl<F: Float> Sub for Vec3<F> {
     type Output = Self;
     fn sub(self, other: Self) -> Self {
         Self {
@@ -36,10 +46,11 @@ impl Sub for Vec3 {
         }
     }
 }
-/// Implement scalar multiplication of `Vec3` structure with `f64` value. This allows us to use the `*` symbol.
-impl Mul<f64> for Vec3 {
+/// Implement scalar multiplication of `Vec3` structure with a value of its component type. This
+/// allows us to use the `*` symbol.
+impl<F: Float> Mul<F> for Vec3<F> {
     type Output = Self;
-    fn mul(self, multiplier: f64) -> Self {
+    fn mul(self, multiplier: F) -> Self {
         Self {
             x: self.x * multiplier,
             y: self.y * multiplier,
@@ -48,7 +59,7 @@ impl Mul<f64> for Vec3 {
     }
 }
 /// Implement multiplication of two `Vec3` structures as point-wise multiplication.
-impl Mul<Vec3> for Vec3 {
+impl<F: Float> Mul<Vec3<F>> for Vec3<F> {
     type Output = Self;
     fn mul(self, other: Self) -> Self {
         Self {
@@ -58,67 +69,147 @@ impl Mul<Vec3> for Vec3 {
         }
     }
 }
-/// Implement scalar division of `Vec3` structure with `f64` value. This allows us to use the `/`
-/// symbol.
-impl Div<f64> for Vec3 {
+/// Implement scalar division of `Vec3` structure with a value of its component type. This allows
+/// us to use the `/` symbol.
+impl<F: Float> Div<F> for Vec3<F> {
     type Output = Self;
-    fn div(self, divisor: f64) -> Self {
-        self * (1.0 / divisor)
+    fn div(self, divisor: F) -> Self {
+        self * (F::one() / divisor)
     }
 }
 /// Implement `+=` symbol for two `Vec3` structures
-impl AddAssign for Vec3 {
+impl<F: Float> AddAssign for Vec3<F> {
     fn add_assign(&mut self, other: Self) {
         *self = *self + other;
     }
 }
 /// Implement `-=` symbol for two `Vec3` structures
-impl SubAssign for Vec3 {
+impl<F: Float> SubAssign for Vec3<F> {
     fn sub_assign(&mut self, other: Self) {
         *self = *self - other;
     }
 }
-/// Implement `*=` symbol for multiplying `Vec3` structure with `f64` value
-impl MulAssign<f64> for Vec3 {
-    fn mul_assign(&mut self, multiplier: f64) {
+/// Implement `*=` symbol for multiplying `Vec3` structure with a value of its component type
+impl<F: Float> MulAssign<F> for Vec3<F> {
+    fn mul_assign(&mut self, multiplier: F) {
         *self = *self * multiplier;
     }
 }
 /// Implement `*=` symbol for multiplying `Vec3` structures with each other.
-impl MulAssign<Vec3> for Vec3 {
+impl<F: Float> MulAssign<Vec3<F>> for Vec3<F> {
     fn mul_assign(&mut self, other: Self) {
         *self = *self * other;
     }
 }
-/// Implement `/=` symbol for dividing `Vec3` structure with `f64` value
-impl DivAssign<f64> for Vec3 {
-    fn div_assign(&mut self, divisor: f64) {
-        *self = *self * (1.0 / divisor)
+/// Implement `/=` symbol for dividing `Vec3` structure with a value of its component type
+impl<F: Float> DivAssign<F> for Vec3<F> {
+    fn div_assign(&mut self, divisor: F) {
+        *self = *self / divisor;
     }
 }
 
-impl Neg for Vec3 {
+impl<F: Float> Neg for Vec3<F> {
     type Output = Self;
     fn neg(self) -> Self {
-        self * (-1.0)
+        self * (-F::one())
     }
 }
 
-impl Vec3 {
+/// Implement indexing of the components of a `Vec3` structure by axis, where 0, 1 and 2
+/// correspond to x, y and z. Like a slice, any other index panics.
+impl<F> Index<usize> for Vec3<F> {
+    type Output = F;
+    fn index(&self, axis: usize) -> &F {
+        match axis {
+            0 => &self.x,
+            1 => &self.y,
+            2 => &self.z,
+            _ => panic!("index out of bounds: a `Vec3` has 3 axes but the index is {axis}"),
+        }
+    }
+}
+/// Implement mutable indexing of the components of a `Vec3` structure by axis, see `Index`.
+impl<F> IndexMut<usize> for Vec3<F> {
+    fn index_mut(&mut self, axis: usize) -> &mut F {
+        match axis {
+            0 => &mut self.x,
+            1 => &mut self.y,
+            2 => &mut self.z,
+            _ => panic!("index out of bounds: a `Vec3` has 3 axes but the index is {axis}"),
+        }
+    }
+}
+
+/// Create a `Vec3` from an `[x, y, z]` array.
+impl<F: Float> From<[F; 3]> for Vec3<F> {
+    fn from(array: [F; 3]) -> Self {
+        Self::new(array[0], array[1], array[2])
+    }
+}
+/// Create a `Vec3` from an `(x, y, z)` tuple.
+impl<F: Float> From<(F, F, F)> for Vec3<F> {
+    fn from(tuple: (F, F, F)) -> Self {
+        Self::new(tuple.0, tuple.1, tuple.2)
+    }
+}
+/// Convert a single precision `Vec3` into a double precision one, which is exact.
+impl From<Vec3<f32>> for Vec3<f64> {
+    fn from(vector: Vec3<f32>) -> Self {
+        return vector.cast();
+    }
+}
+
+/// Serialize a `Vec3` as an `[x, y, z]` array instead of a struct, which keeps scene files
+/// compact.
+#[cfg(feature = "serde")]
+impl<F: Float + serde::Serialize> serde::Serialize for Vec3<F> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        return self.as_array().serialize(serializer);
+    }
+}
+/// Deserialize a `Vec3` from an `[x, y, z]` array, see `Serialize`.
+#[cfg(feature = "serde")]
+impl<'de, F: Float + serde::Deserialize<'de>> serde::Deserialize<'de> for Vec3<F> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        return <[F; 3]>::deserialize(deserializer).map(Self::from);
+    }
+}
+
+impl<F: Float> Vec3<F> {
     /// Create new `Vec3` instance.
-    pub fn new(x: f64, y: f64, z: f64) -> Self {
+    pub fn new(x: F, y: F, z: F) -> Self {
         Self { x, y, z }
     }
+    /// Get the components as an `[x, y, z]` array.
+    pub fn as_array(&self) -> [F; 3] {
+        return [self.x, self.y, self.z];
+    }
+    /// Get the axis of the largest component, where 0, 1 and 2 correspond to x, y and z. On a tie,
+    /// the first of the axes is returned.
+    pub fn max_axis(&self) -> usize {
+        if self.x >= self.y && self.x >= self.z {
+            return 0;
+        } else if self.y >= self.z {
+            return 1;
+        } else {
+            return 2;
+        }
+    }
+    /// Linearly interpolate between `self` at $t = 0$ and `other` at $t = 1$, e.g.
+    /// $(1 - t) \cdot \text{self} + t \cdot \text{other}$. The endpoints are returned exactly.
+    pub fn lerp(&self, other: &Self, t: F) -> Self {
+        return *self * (F::one() - t) + *other * t;
+    }
     /// Get Euclidean norm of `Vec3` squared.
-    pub fn length_squared(&self) -> f64 {
+    pub fn length_squared(&self) -> F {
         return self.x * self.x + self.y * self.y + self.z * self.z;
     }
     /// Get Euclidean norm of `Vec3`.
-    pub fn length(&self) -> f64 {
+    pub fn length(&self) -> F {
         return self.length_squared().sqrt();
     }
     /// Get dot product between two `Vec3` structures.
-    pub fn dot(&self, other: &Self) -> f64 {
+    pub fn dot(&self, other: &Self) -> F {
         return self.x * other.x + self.y * other.y + self.z * other.z;
     }
     /// Get cross product between two `Vec3` structures. Not that this operation is not symmetric!
@@ -133,6 +224,78 @@ impl Vec3 {
     pub fn unit_vector(&self) -> Self {
         return *self / self.length();
     }
+    /// Get two unit vectors that, together with `self`, form a right-handed orthonormal basis.
+    /// The vector `self` is assumed to be of unit length.
+    /// See: <https://graphics.pixar.com/library/OrthonormalB/paper.pdf>
+    pub fn orthonormal_basis(&self) -> (Self, Self) {
+        let sign: F = F::one().copysign(self.z);
+        let a: F = -F::one() / (sign + self.z);
+        let b: F = self.x * self.y * a;
+        let tangent: Self = Self::new(
+            F::one() + sign * self.x * self.x * a,
+            sign * b,
+            -sign * self.x,
+        );
+        let bitangent: Self = Self::new(b, sign + self.y * self.y * a, -self.y);
+        return (tangent, bitangent);
+    }
+    /// Check if the vector is effectively the zero vector, e.g. the absolute values of all of its
+    /// components lie below `NEAR_ZERO_EPSILON`, see `near_zero_eps`.
+    pub fn near_zero(&self) -> bool {
+        return self.near_zero_eps(NEAR_ZERO_EPSILON);
+    }
+    /// Check if the absolute values of all components of the vector lie strictly below `epsilon`,
+    /// so a component exactly at `epsilon` is not near zero. The threshold should follow the scale
+    /// of the scene and the precision of the components, e.g. `f32` vectors need a larger one. If
+    /// `epsilon` cannot be represented in the component type, its machine epsilon is used.
+    pub fn near_zero_eps(&self, epsilon: f64) -> bool {
+        let epsilon: F = F::from(epsilon).unwrap_or_else(F::epsilon);
+        self.x.abs() < epsilon && self.y.abs() < epsilon && self.z.abs() < epsilon
+    }
+    /// Check if all components of the vector are finite, e.g. none of them is infinite or NaN.
+    pub fn is_finite(&self) -> bool {
+        self.x.is_finite() && self.y.is_finite() && self.z.is_finite()
+    }
+    /// Reflect vector with respect to `normal`
+    pub fn reflect(&self, normal: Self) -> Self {
+        let two: F = F::one() + F::one();
+        return *self - normal * two * self.dot(&normal);
+    }
+    /// Refract vector with respect to `normal` and ratio of refractive indices. In this
+    /// function, it is the refractive index of the incoming ray divided by the refractive
+    /// index of the outgoing ray.
+    pub fn refract(&self, normal: Self, refractive_index_fraction: F) -> Self {
+        let cos_theta: F = normal.dot(&self.neg()).min(F::one());
+        let out_perp: Self = (*self + normal * cos_theta) * refractive_index_fraction;
+        let out_parallel = -normal * (F::one() - out_perp.length_squared()).abs().sqrt();
+        return out_perp + out_parallel;
+    }
+    /// Rotate the vector by `angle` radians about `axis`, counterclockwise when looking down the
+    /// axis towards the origin. The axis is normalised, so it does not need to be of unit length.
+    /// With $\vec{k}$ the unit axis, Rodrigues' rotation formula gives
+    /// $$ \vec{v}' = \vec{v} \cos \theta + (\vec{k} \times \vec{v}) \sin \theta +
+    /// \vec{k} (\vec{k} \cdot \vec{v}) (1 - \cos \theta). $$
+    /// A zero angle or a zero axis leaves the vector unchanged.
+    pub fn rotate_around(&self, axis: &Self, angle: F) -> Self {
+        let axis_length: F = axis.length();
+        if angle == F::zero() || axis_length == F::zero() {
+            return *self;
+        }
+        let k: Self = *axis / axis_length;
+        let (sin_theta, cos_theta) = angle.sin_cos();
+        return *self * cos_theta
+            + k.cross(self) * sin_theta
+            + k * (k.dot(self) * (F::one() - cos_theta));
+    }
+    /// Convert the components to another floating point type, e.g. `f32` to reduce the memory of
+    /// stored vectors. Components that are too large for the other type become infinite.
+    pub fn cast<G: Float>(&self) -> Vec3<G> {
+        let convert = |value: F| G::from(value).unwrap_or_else(G::nan);
+        return Vec3::new(convert(self.x), convert(self.y), convert(self.z));
+    }
+}
+
+impl Vec3 {
     /// Get random vector in unit square, e.g. all directions are random values in (0,1).
     pub fn get_random_vector() -> Self {
         Self {
@@ -180,7 +343,21 @@ impl Vec3 {
             return -on_unit_sphere;
         }
     }
-    /// Get random vector in the unit disk.
+    /// Get random unit vector on the same hemisphere as the provided unit `normal` vector,
+    /// distributed with a probability density proportional to the cosine of the angle with the
+    /// `normal`, e.g. $\text{pdf}(\omega) = \cos \theta / \pi$. The vector never points below
+    /// the surface.
+    pub fn get_random_cosine_direction(normal: Self) -> Self {
+        let phi: f64 = 2.0 * std::f64::consts::PI * get_random();
+        let r2: f64 = get_random();
+        let (tangent, bitangent) = normal.orthonormal_basis();
+        return tangent * (phi.cos() * r2.sqrt())
+            + bitangent * (phi.sin() * r2.sqrt())
+            + normal * (1.0 - r2).sqrt();
+    }
+    /// Get random vector in the unit disk in the xy-plane by randomly sampling x and y within the
+    /// bounding square and returning only when the sample lies within the disk, as in
+    /// `get_random_in_unit_sphere`. The z-component is always zero.
     pub fn get_random_in_unit_disk() -> Self {
         loop {
             let p: Self = Self::new(
@@ -193,23 +370,135 @@ impl Vec3 {
             }
         }
     }
-    /// Check if the vector is effectively the zero vector, e.g. all of its components lie
-    /// below a certain threshold value.
-    pub fn near_zero(&self) -> bool {
-        let epsilon: f64 = 1e-8;
-        self.x.abs() < epsilon && self.y.abs() < epsilon && self.z.abs() < epsilon
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::utils::seed_rng;
+
+    #[test]
+    fn index_each_axis_and_find_the_largest() {
+        let mut vector: Vec3 = Vec3::new(1.0, 5.0, 3.0);
+        assert_eq!((vector[0], vector[1], vector[2]), (1.0, 5.0, 3.0));
+        assert_eq!(vector.max_axis(), 1);
+        vector[2] = 7.0;
+        assert_eq!(vector.z, 7.0);
+        assert_eq!(vector.max_axis(), 2);
+        assert_eq!(Vec3::new(2.0, 2.0, 2.0).max_axis(), 0);
     }
-    /// Reflect vector with respect to `normal`
-    pub fn reflect(&self, normal: Self) -> Self {
-        return *self - normal * 2.0 * self.dot(&normal);
+
+    #[test]
+    #[should_panic(expected = "index out of bounds")]
+    fn index_past_z_panics() {
+        let _ = Vec3::new(1.0, 2.0, 3.0)[3];
     }
-    /// Refract vector with respect to `normal` and ratio of refractive indices. In this
-    /// function, it is the refractive index of the incoming ray divided by the refractive
-    /// index of the outgoing ray.
-    pub fn refract(&self, normal: Self, refractive_index_fraction: f64) -> Self {
-        let cos_theta: f64 = normal.dot(&self.neg()).min(1.0);
-        let out_perp: Self = (*self + normal * cos_theta) * refractive_index_fraction;
-        let out_parallel = -normal * (1.0 - out_perp.length_squared()).abs().sqrt();
-        return out_perp + out_parallel;
+
+    #[test]
+    fn lerp_hits_both_endpoints_and_the_midpoint() {
+        let start: Vec3 = Vec3::new(0.1, -2.0, 3.0);
+        let end: Vec3 = Vec3::new(0.7, 4.0, -1.0);
+        assert_eq!(start.lerp(&end, 0.0), start);
+        assert_eq!(start.lerp(&end, 1.0), end);
+        let midpoint: Vec3 = start.lerp(&end, 0.5);
+        assert!((midpoint - Vec3::new(0.4, 1.0, 1.0)).length() < 1e-12);
+    }
+
+    #[test]
+    fn from_array_and_tuple_round_trip_through_as_array() {
+        let vector: Vec3 = Vec3::new(1.0, 2.0, 3.0);
+        assert_eq!(Vec3::from([1.0, 2.0, 3.0]), vector);
+        assert_eq!(Vec3::from((1.0, 2.0, 3.0)), vector);
+        assert_eq!(vector.as_array(), [1.0, 2.0, 3.0]);
+        assert_eq!(Vec3::from(vector.as_array()), vector);
+    }
+
+    #[test]
+    fn cosine_directions_never_point_below_the_surface() {
+        seed_rng(60);
+        let normals: [Vec3; 4] = [
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(0.0, 0.0, -1.0),
+            Vec3::new(1.0, -2.0, 0.5).unit_vector(),
+            Vec3::new(0.0, 1.0, 0.0),
+        ];
+        for normal in normals {
+            let mut mean_cosine: f64 = 0.0;
+            for _ in 0..5000 {
+                let direction: Vec3 = Vec3::get_random_cosine_direction(normal);
+                assert!((direction.length() - 1.0).abs() < 1e-9);
+                let cosine: f64 = direction.dot(&normal);
+                assert!(cosine >= 0.0);
+                mean_cosine += cosine / 5000.0;
+            }
+            // The mean cosine of a cosine weighted hemisphere is 2/3.
+            assert!((mean_cosine - 2.0 / 3.0).abs() < 0.02);
+        }
+    }
+
+    #[test]
+    fn single_and_double_precision_normalise_to_the_same_direction() {
+        let single: Vec3f32 = Vec3f32::new(3.0, -4.0, 12.0).unit_vector();
+        let double: Vec3 = Vec3::new(3.0, -4.0, 12.0).unit_vector();
+        assert!((single.length() - 1.0).abs() < 1e-6);
+        assert!((Vec3::from(single) - double).length() < 1e-6);
+        assert!((double.cast::<f32>() - single).length() < 1e-6);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn vector_serializes_as_an_array_and_back() {
+        let vector: Vec3 = Vec3::new(1.0, 2.0, 3.0);
+        let json: String = serde_json::to_string(&vector).unwrap();
+        assert_eq!(json, "[1.0,2.0,3.0]");
+        let read: Vec3 = serde_json::from_str(&json).unwrap();
+        assert_eq!(read, vector);
+        assert!(serde_json::from_str::<Vec3>("[1.0,2.0]").is_err());
+    }
+
+    #[test]
+    fn near_zero_is_strict_at_the_epsilon() {
+        assert!(Vec3::new(0.0, 0.0, 0.0).near_zero());
+        assert!(Vec3::new(9.9e-9, -9.9e-9, 0.0).near_zero());
+        // Exactly at the epsilon, or just above it in any component, is not near zero.
+        assert!(!Vec3::new(NEAR_ZERO_EPSILON, 0.0, 0.0).near_zero());
+        assert!(!Vec3::new(0.0, -NEAR_ZERO_EPSILON, 0.0).near_zero());
+        assert!(!Vec3::new(0.0, 0.0, 1.1e-8).near_zero());
+
+        let vector: Vec3 = Vec3::new(0.5, -0.5, 0.25);
+        assert!(!vector.near_zero_eps(0.5));
+        assert!(vector.near_zero_eps(0.5 + 1e-12));
+        // The threshold of an `f32` vector is converted to its precision.
+        assert!(Vec3f32::new(1e-9, 0.0, 0.0).near_zero());
+    }
+
+    #[test]
+    fn quarter_turn_about_z_takes_x_to_y() {
+        let x: Vec3 = Vec3::new(1.0, 0.0, 0.0);
+        let half_pi: f64 = std::f64::consts::FRAC_PI_2;
+        // The axis does not need to be of unit length.
+        let rotated: Vec3 = x.rotate_around(&Vec3::new(0.0, 0.0, 5.0), half_pi);
+        assert!((rotated - Vec3::new(0.0, 1.0, 0.0)).length() < 1e-12);
+        // A zero angle or a zero axis leave the vector as is.
+        let vector: Vec3 = Vec3::new(0.3, -1.2, 2.0);
+        assert_eq!(vector.rotate_around(&Vec3::new(1.0, 1.0, 0.0), 0.0), vector);
+        assert_eq!(
+            vector.rotate_around(&Vec3::new(0.0, 0.0, 0.0), half_pi),
+            vector
+        );
+    }
+
+    #[test]
+    fn random_in_unit_disk_lies_flat_inside_the_circle() {
+        seed_rng(108);
+        let mut largest_squared: f64 = 0.0;
+        for _ in 0..10_000 {
+            let point: Vec3 = Vec3::get_random_in_unit_disk();
+            assert_eq!(point.z, 0.0);
+            assert!(point.x * point.x + point.y * point.y < 1.0);
+            largest_squared = largest_squared.max(point.length_squared());
+        }
+        // The samples reach out towards the rim instead of staying near the center.
+        assert!(largest_squared > 0.99);
     }
 }